@@ -0,0 +1,147 @@
+use crate::{Cell, CellStorage, TickStats, Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// Set in an age when the cell was alive when last counted, so that cells
+/// which changed since can be told apart from those which didn't.
+const ALIVE: u32 = 1 << 31;
+
+/// The age of a cell which has been dead for as long as can be counted.
+pub(crate) const LONG_DEAD: u32 = ALIVE - 1;
+
+impl Universe {
+    /// Ages for counting to start from: live cells have just been born and
+    /// dead cells have been dead as long as can be counted.
+    fn fresh_ages(&self) -> Vec<u32> {
+        (0..self.cells.len())
+            .map(|idx| {
+                if self.cells.is_alive(idx) {
+                    ALIVE
+                } else {
+                    LONG_DEAD
+                }
+            })
+            .collect()
+    }
+
+    /// Number of ticks the cell at an index has survived for, or 0 if it is
+    /// dead or ages aren't being counted.
+    pub(crate) fn age(&self, idx: usize) -> u32 {
+        match self.ages.as_ref().map(|ages| ages[idx]) {
+            Some(age) if age & ALIVE != 0 => age & LONG_DEAD,
+            _ => 0,
+        }
+    }
+
+    /// Ages after a resize, which start again if they are being tracked and
+    /// otherwise are counted again from the next tick.
+    pub(crate) fn reset_ages(&mut self) {
+        self.ages = if self.age_tracking {
+            Some(self.fresh_ages())
+        } else {
+            None
+        };
+    }
+
+    /// Count how many ticks each cell has been alive, or dead, for, killing
+    /// live cells which have reached the maximum age.
+    pub(crate) fn update_ages(&mut self, stats: &mut TickStats) {
+        if self.max_age.is_none() && !self.age_tracking {
+            return;
+        }
+        // Counting from scratch treats every live cell as just born.
+        let mut ages = self
+            .ages
+            .take()
+            .unwrap_or_else(|| vec![LONG_DEAD; self.cells.len()]);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let alive = self.cells.is_alive(idx);
+                if alive != (ages[idx] & ALIVE != 0) {
+                    ages[idx] = if alive { ALIVE } else { 0 };
+                    continue;
+                }
+                if ages[idx] & LONG_DEAD != LONG_DEAD {
+                    ages[idx] += 1;
+                }
+                let too_old = self
+                    .max_age
+                    .is_some_and(|max_age| ages[idx] & LONG_DEAD > max_age);
+                if alive && too_old && !self.is_wall(idx) {
+                    self.cells.set(idx, Cell::DEAD);
+                    ages[idx] = 0;
+                    stats.record_change(row, col, false);
+                    stats.population -= 1;
+                }
+            }
+        }
+        self.ages = Some(ages);
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Kill cells once they have survived `max_age` ticks, whatever their
+    /// neighbours, which keeps otherwise frozen regions churning. `None`
+    /// lets cells live forever.
+    pub fn set_max_age(&mut self, max_age: Option<u32>) {
+        self.max_age = max_age;
+        if max_age.is_none() && !self.age_tracking {
+            self.ages = None;
+        }
+    }
+
+    pub fn max_age(&self) -> Option<u32> {
+        self.max_age
+    }
+
+    /// Number of ticks a cell has survived for, or 0 if it is dead or ages
+    /// aren't being tracked.
+    pub fn cell_age(&self, row: u32, col: u32) -> Result<u32, UniverseError> {
+        let idx = self.checked_index("cell_age", row, col)?;
+        Ok(self.age(idx))
+    }
+
+    /// Start or stop tracking how long each cell has been alive, or dead,
+    /// for colouring cells by age. Starting again resets the ages, unless
+    /// they are still being counted for `set_max_age`. Ages are updated each
+    /// tick, so cells edited in between keep their old age until the next
+    /// tick.
+    pub fn set_age_tracking(&mut self, enabled: bool) {
+        self.age_tracking = enabled;
+        if enabled && self.ages.is_none() {
+            self.ages = Some(self.fresh_ages());
+        } else if !enabled && self.max_age.is_none() {
+            self.ages = None;
+        }
+    }
+
+    /// Cap the ages given by `ages_as_bytes` at `cap` ticks, so that
+    /// everything older looks the same. Defaults to 255.
+    pub fn set_age_cap(&mut self, cap: u8) {
+        self.age_cap = cap;
+    }
+
+    pub fn age_cap(&self) -> u8 {
+        self.age_cap
+    }
+
+    /// One byte per cell in row-major order: the number of ticks since the
+    /// cell was born if it is alive, or since it died if it is dead, up to
+    /// the age cap. Cells which have never lived count as having been dead
+    /// since tracking started. Empty unless ages are being tracked.
+    pub fn ages_as_bytes(&self) -> Vec<u8> {
+        let ages = match &self.ages {
+            Some(ages) if self.age_tracking => ages,
+            _ => return Vec::new(),
+        };
+        let mut bytes = Vec::with_capacity((self.width * self.height) as usize);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let age = ages[self.get_index(row, col)] & LONG_DEAD;
+                bytes.push(age.min(self.age_cap as u32) as u8);
+            }
+        }
+        bytes
+    }
+}
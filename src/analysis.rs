@@ -0,0 +1,264 @@
+use crate::rng::Rng;
+use crate::rule::Rule;
+use crate::{CellStorage, Universe};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::prelude::*;
+
+/// How many generations an object is evolved for before giving up on
+/// finding a repeating state.
+const MAX_GENERATIONS: u32 = 1024;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectKind {
+    Empty,
+    Dies,
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Unknown,
+}
+
+/// The result of evolving an object in isolation.
+///
+/// For objects which settle into a cycle after some generations, `period`,
+/// `dx` and `dy` describe that cycle.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct ObjectAnalysis {
+    pub kind: ObjectKind,
+    pub period: u32,
+    pub dx: i32,
+    pub dy: i32,
+    /// Distance travelled per generation as a fraction of the speed of
+    /// light (one cell per generation).
+    pub speed: f64,
+}
+
+/// Advance a set of live cells on an unbounded plane by one generation of a
+/// rule.
+pub(crate) fn step_cells(cells: &HashSet<(i32, i32)>, rule: Rule) -> HashSet<(i32, i32)> {
+    let mut counts: HashMap<(i32, i32), u8> = HashMap::new();
+    for &(row, col) in cells.iter() {
+        for d_row in -1..=1 {
+            for d_col in -1..=1 {
+                if d_row != 0 || d_col != 0 {
+                    *counts.entry((row + d_row, col + d_col)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|&(cell, count)| rule.next(cells.contains(&cell), count))
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+/// Split a set of cells into its shape relative to the top-left corner of
+/// its bounding box, and the position of that corner.
+fn normalise(cells: &HashSet<(i32, i32)>) -> (Vec<(i32, i32)>, (i32, i32)) {
+    let min_row = cells.iter().map(|&(row, _)| row).min().unwrap_or(0);
+    let min_col = cells.iter().map(|&(_, col)| col).min().unwrap_or(0);
+    let mut shape: Vec<_> = cells
+        .iter()
+        .map(|&(row, col)| (row - min_row, col - min_col))
+        .collect();
+    shape.sort_unstable();
+    (shape, (min_row, min_col))
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Evolve the live cells in the given rectangle on an otherwise empty,
+    /// unbounded plane and classify the object they form. The rectangle is
+    /// clipped to the universe.
+    pub fn analyze_object(&self, x: u32, y: u32, w: u32, h: u32) -> ObjectAnalysis {
+        let mut cells = HashSet::new();
+        for row in y..y.saturating_add(h).min(self.height) {
+            for col in x..x.saturating_add(w).min(self.width) {
+                if self.cells.is_alive(self.get_index(row, col)) {
+                    cells.insert((row as i32, col as i32));
+                }
+            }
+        }
+
+        let mut analysis = ObjectAnalysis {
+            kind: ObjectKind::Empty,
+            period: 0,
+            dx: 0,
+            dy: 0,
+            speed: 0.0,
+        };
+        if cells.is_empty() {
+            return analysis;
+        }
+
+        let mut seen = HashMap::new();
+        for generation in 0..=MAX_GENERATIONS {
+            if cells.is_empty() {
+                analysis.kind = ObjectKind::Dies;
+                return analysis;
+            }
+            let (shape, (row, col)) = normalise(&cells);
+            if let Some(&(first_generation, first_row, first_col)) = seen.get(&shape) {
+                analysis.period = generation - first_generation;
+                analysis.dx = col - first_col;
+                analysis.dy = row - first_row;
+                analysis.speed =
+                    analysis.dx.abs().max(analysis.dy.abs()) as f64 / analysis.period as f64;
+                analysis.kind = if analysis.dx != 0 || analysis.dy != 0 {
+                    ObjectKind::Spaceship
+                } else if analysis.period == 1 {
+                    ObjectKind::StillLife
+                } else {
+                    ObjectKind::Oscillator
+                };
+                return analysis;
+            }
+            seen.insert(shape, (generation, row, col));
+            cells = step_cells(&cells, self.rule);
+        }
+        analysis.kind = ObjectKind::Unknown;
+        analysis
+    }
+}
+
+/// How closely the live cells match their own image under each symmetry of
+/// their bounding box.
+///
+/// Each score is the fraction of live cells whose mirrored or rotated
+/// counterpart is also alive, so `1.0` is an exact symmetry and values close
+/// to it are approximate symmetries.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct SymmetryReport {
+    /// Reflection in the horizontal axis (top to bottom).
+    pub horizontal: f64,
+    /// Reflection in the vertical axis (left to right).
+    pub vertical: f64,
+    /// Reflection in the leading diagonal.
+    pub diagonal: f64,
+    /// Reflection in the anti-diagonal.
+    pub anti_diagonal: f64,
+    /// Rotation by a quarter turn.
+    pub rotation_90: f64,
+    /// Rotation by a half turn.
+    pub rotation_180: f64,
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Score the live pattern against each of the symmetries of a square.
+    ///
+    /// An empty universe is reported as perfectly symmetric.
+    pub fn detect_symmetry(&self) -> SymmetryReport {
+        let mut cells = HashSet::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.cells.is_alive(self.get_index(row, col)) {
+                    cells.insert((row as i32, col as i32));
+                }
+            }
+        }
+        let row_sum = cells.iter().map(|&(row, _)| row).min().unwrap_or(0)
+            + cells.iter().map(|&(row, _)| row).max().unwrap_or(0);
+        let col_sum = cells.iter().map(|&(_, col)| col).min().unwrap_or(0)
+            + cells.iter().map(|&(_, col)| col).max().unwrap_or(0);
+
+        // Work in doubled coordinates relative to the centre of the bounding
+        // box so that the centre is always a whole number.
+        let score = |transform: fn(i32, i32) -> (i32, i32)| {
+            if cells.is_empty() {
+                return 1.0;
+            }
+            let matched = cells
+                .iter()
+                .filter(|&&(row, col)| {
+                    let (r, c) = transform(2 * row - row_sum, 2 * col - col_sum);
+                    let (r, c) = (r + row_sum, c + col_sum);
+                    r % 2 == 0 && c % 2 == 0 && cells.contains(&(r / 2, c / 2))
+                })
+                .count();
+            matched as f64 / cells.len() as f64
+        };
+
+        SymmetryReport {
+            horizontal: score(|r, c| (-r, c)),
+            vertical: score(|r, c| (r, -c)),
+            diagonal: score(|r, c| (c, r)),
+            anti_diagonal: score(|r, c| (-c, -r)),
+            rotation_90: score(|r, c| (c, -r)),
+            rotation_180: score(|r, c| (-r, -c)),
+        }
+    }
+}
+
+/// The outcome of a `density_sweep`, with one entry per initial density.
+#[wasm_bindgen]
+pub struct SweepReport {
+    densities: Vec<f64>,
+    mean_final_density: Vec<f64>,
+    survival_probability: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl SweepReport {
+    #[wasm_bindgen(getter)]
+    pub fn densities(&self) -> Vec<f64> {
+        self.densities.clone()
+    }
+
+    /// Mean fraction of cells alive at the end of the trials.
+    #[wasm_bindgen(getter)]
+    pub fn mean_final_density(&self) -> Vec<f64> {
+        self.mean_final_density.clone()
+    }
+
+    /// Fraction of trials which still had live cells at the end.
+    #[wasm_bindgen(getter)]
+    pub fn survival_probability(&self) -> Vec<f64> {
+        self.survival_probability.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// For each initial density, run `trials` random soups the size of this
+    /// universe for `generations` ticks and record how they ended up. The
+    /// soups follow this universe's rule and edges.
+    ///
+    /// Each trial is seeded from the position of its density in `densities`
+    /// and its trial number, so a sweep gives the same results every time it
+    /// is run.
+    pub fn density_sweep(&self, densities: &[f64], trials: u32, generations: u32) -> SweepReport {
+        let size = (self.width * self.height) as f64;
+        let mut report = SweepReport {
+            densities: densities.to_vec(),
+            mean_final_density: Vec::with_capacity(densities.len()),
+            survival_probability: Vec::with_capacity(densities.len()),
+        };
+        for (i, &density) in densities.iter().enumerate() {
+            let mut total_density = 0.0;
+            let mut survivors = 0;
+            for trial in 0..trials {
+                let mut rng = Rng::new(((i as u64) << 32) | trial as u64);
+                let mut universe = Universe::empty(self.width, self.height);
+                universe.apply_rule(self.rule);
+                universe.row_boundary = self.row_boundary;
+                universe.col_boundary = self.col_boundary;
+                universe.fill_random(&mut rng, density);
+                universe.tick_many(generations as usize);
+                let population = universe.cells.population();
+                total_density += population as f64 / size;
+                if population > 0 {
+                    survivors += 1;
+                }
+            }
+            let trials = trials.max(1) as f64;
+            report.mean_final_density.push(total_density / trials);
+            report.survival_probability.push(survivors as f64 / trials);
+        }
+        report
+    }
+}
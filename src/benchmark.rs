@@ -0,0 +1,178 @@
+//! A fixed set of workloads timed on each backend, so that devices can be
+//! compared and performance regressions reported with numbers.
+
+use crate::rng::Rng;
+use crate::{catalogue_pattern, timing, LogLevel, Universe};
+use wasm_bindgen::prelude::*;
+
+/// Width and height of the universe each workload runs in, which is large
+/// enough for the sparse backend to be used.
+const SIZE: u32 = 256;
+
+struct Workload {
+    name: &'static str,
+    generations: u32,
+    /// Creates the universe the workload starts from.
+    setup: fn() -> Universe,
+}
+
+const WORKLOADS: [Workload; 4] = [
+    Workload {
+        name: "empty",
+        generations: 1000,
+        setup: empty_universe,
+    },
+    Workload {
+        name: "random",
+        generations: 100,
+        setup: random_universe,
+    },
+    Workload {
+        name: "gosper gun",
+        generations: 1000,
+        setup: gosper_gun_universe,
+    },
+    // The R-pentomino settles down after 1103 generations on an unbounded
+    // plane.
+    Workload {
+        name: "r-pentomino",
+        generations: 1103,
+        setup: r_pentomino_universe,
+    },
+];
+
+const BACKENDS: [&str; 2] = ["dense", "sparse"];
+
+fn empty_universe() -> Universe {
+    Universe::empty(SIZE, SIZE)
+}
+
+/// Half the cells alive, the same ones on every run.
+fn random_universe() -> Universe {
+    let mut universe = Universe::empty(SIZE, SIZE);
+    let mut rng = Rng::new(1);
+    let cells: Vec<_> = (0..SIZE as i32)
+        .flat_map(|row| (0..SIZE as i32).map(move |col| (row, col)))
+        .filter(|_| rng.next_f64() < 0.5)
+        .collect();
+    universe.set_cells(&cells);
+    universe
+}
+
+fn pattern_universe(name: &str) -> Universe {
+    let mut universe = Universe::empty(SIZE, SIZE);
+    let pattern = catalogue_pattern(name).unwrap();
+    universe.insert_pattern(&pattern, SIZE / 2, SIZE / 2);
+    universe
+}
+
+fn gosper_gun_universe() -> Universe {
+    pattern_universe("gosper glider gun")
+}
+
+fn r_pentomino_universe() -> Universe {
+    pattern_universe("r-pentomino")
+}
+
+/// The time taken to run one workload on one backend.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct BenchmarkResult {
+    workload: String,
+    backend: String,
+    pub generations: u32,
+    /// Total time taken, in milliseconds.
+    pub ms: f64,
+}
+
+#[wasm_bindgen]
+impl BenchmarkResult {
+    /// `empty`, `random`, `gosper gun` or `r-pentomino`.
+    #[wasm_bindgen(getter)]
+    pub fn workload(&self) -> String {
+        self.workload.clone()
+    }
+
+    /// `dense` or `sparse`.
+    #[wasm_bindgen(getter)]
+    pub fn backend(&self) -> String {
+        self.backend.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generations_per_second(&self) -> f64 {
+        if self.ms > 0.0 {
+            self.generations as f64 * 1000.0 / self.ms
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The results of `run_benchmark_suite`.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct BenchmarkReport {
+    results: Vec<BenchmarkResult>,
+}
+
+#[wasm_bindgen]
+impl BenchmarkReport {
+    /// Every workload on every backend, in the order they were run.
+    pub fn results(&self) -> Vec<BenchmarkResult> {
+        self.results.clone()
+    }
+
+    /// The results as CSV with a header row, for pasting into a bug report.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("workload,backend,generations,ms,generations_per_second\n");
+        for result in self.results.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{:.3},{:.1}\n",
+                result.workload,
+                result.backend,
+                result.generations,
+                result.ms,
+                result.generations_per_second()
+            ));
+        }
+        csv
+    }
+}
+
+/// Time each workload on each backend: an empty universe, a universe with
+/// half its cells alive at random, a Gosper glider gun and an R-pentomino,
+/// each in a 256x256 universe. Backends are normally chosen automatically,
+/// but here each workload is run on both. This takes a few seconds.
+#[wasm_bindgen]
+pub fn run_benchmark_suite() -> BenchmarkReport {
+    let mut results = Vec::new();
+    for workload in WORKLOADS.iter() {
+        for &backend in BACKENDS.iter() {
+            let mut universe = (workload.setup)();
+            let start = timing::now();
+            for _ in 0..workload.generations {
+                // Ticking switches backend as the population changes, so
+                // choose it again before every tick.
+                universe.sparse = backend == "sparse";
+                universe.tick();
+            }
+            let ms = timing::now() - start;
+            log!(
+                LogLevel::Info,
+                "Benchmark {} on {}: {} generations in {:.1}ms",
+                workload.name,
+                backend,
+                workload.generations,
+                ms
+            );
+            results.push(BenchmarkResult {
+                workload: workload.name.to_string(),
+                backend: backend.to_string(),
+                generations: workload.generations,
+                ms,
+            });
+        }
+    }
+    BenchmarkReport { results }
+}
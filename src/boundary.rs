@@ -0,0 +1,108 @@
+use crate::Universe;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// What lies beyond a pair of opposite edges of the universe.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+    /// The edges are joined, so cells beyond one edge are the cells along
+    /// the opposite edge. With both pairs of edges wrapping the universe is
+    /// a torus.
+    Wrap,
+    /// Cells beyond the edge are always dead, so patterns behave much as on
+    /// an infinite plane until they reach the edge.
+    Absorbing,
+    /// Cells beyond the edge mirror the cells along it.
+    Reflecting,
+}
+
+/// What lies beyond each pair of opposite edges of the universe.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Topology {
+    pub top_bottom: Boundary,
+    pub left_right: Boundary,
+}
+
+#[wasm_bindgen]
+impl Topology {
+    #[wasm_bindgen(constructor)]
+    pub fn new(top_bottom: Boundary, left_right: Boundary) -> Topology {
+        Topology {
+            top_bottom,
+            left_right,
+        }
+    }
+}
+
+impl Default for Topology {
+    fn default() -> Topology {
+        Topology {
+            top_bottom: Boundary::Wrap,
+            left_right: Boundary::Wrap,
+        }
+    }
+}
+
+impl Boundary {
+    /// Positions along an axis of `size` cells, including positions in the
+    /// halo, which show the state of the cell at `pos`.
+    pub(crate) fn images(self, pos: i32, size: u32) -> [Option<i32>; 3] {
+        let size = size as i32;
+        let (low, high) = match self {
+            Boundary::Wrap => (size, -1),
+            Boundary::Absorbing => return [Some(pos), None, None],
+            Boundary::Reflecting => (-1, size),
+        };
+        [
+            Some(pos),
+            if pos == 0 { Some(low) } else { None },
+            if pos == size - 1 { Some(high) } else { None },
+        ]
+    }
+
+    /// The cell whose state shows at a position along an axis of `size`
+    /// cells, which may be just beyond either edge, or `None` if it is
+    /// always dead.
+    pub(crate) fn resolve(self, pos: i32, size: u32) -> Option<u32> {
+        let size = size as i32;
+        if pos >= 0 && pos < size {
+            return Some(pos as u32);
+        }
+        match self {
+            Boundary::Wrap => Some(pos.rem_euclid(size) as u32),
+            Boundary::Absorbing => None,
+            Boundary::Reflecting => Some(pos.clamp(0, size - 1) as u32),
+        }
+    }
+
+    /// The cell a position along an axis of `size` cells lands on when
+    /// placing cells, wrapping around joined edges, or `None` if it lies
+    /// beyond an edge which doesn't wrap.
+    pub(crate) fn place(self, pos: i32, size: u32) -> Option<u32> {
+        match self {
+            Boundary::Wrap => self.resolve(pos, size),
+            _ => Some(pos as u32).filter(|_| pos >= 0 && pos < size as i32),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Choose what lies beyond the top and bottom edges, and beyond the left
+    /// and right edges. Both default to `Wrap`.
+    pub fn set_boundary(&mut self, top_bottom: Boundary, left_right: Boundary) {
+        self.row_boundary = top_bottom;
+        self.col_boundary = left_right;
+    }
+
+    pub fn top_bottom_boundary(&self) -> Boundary {
+        self.row_boundary
+    }
+
+    pub fn left_right_boundary(&self) -> Boundary {
+        self.col_boundary
+    }
+}
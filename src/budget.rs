@@ -0,0 +1,133 @@
+use crate::{timing, LogLevel, Universe};
+use wasm_bindgen::prelude::*;
+
+/// Sent to the budget warning listener when frames keep taking too long.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct BudgetWarning {
+    /// How long the latest frame took, in milliseconds.
+    pub frame_ms: f64,
+    /// The number of generations ticked per frame from now on.
+    pub generations_per_frame: u32,
+    /// Whether the sparse backend was in use, which is only chosen
+    /// automatically for large, sparse universes.
+    pub sparse: bool,
+    suggestion: String,
+}
+
+#[wasm_bindgen]
+impl BudgetWarning {
+    /// What the frontend could do about it, for showing to the user.
+    #[wasm_bindgen(getter)]
+    pub fn suggestion(&self) -> String {
+        self.suggestion.clone()
+    }
+}
+
+/// Slows the simulation down when frames keep going over a time budget.
+#[derive(Default)]
+pub(crate) struct BudgetGuard {
+    /// The longest a frame should take, or 0 if there is no budget.
+    budget_ms: f64,
+    /// Number of frames in a row which can go over budget before the guard
+    /// steps in.
+    patience: u32,
+    over: u32,
+    listener: Option<js_sys::Function>,
+}
+
+impl Universe {
+    fn check_budget(&mut self, frame_ms: f64) {
+        let guard = &mut self.budget;
+        if guard.budget_ms <= 0.0 || frame_ms <= guard.budget_ms {
+            guard.over = 0;
+            return;
+        }
+        guard.over += 1;
+        if guard.over < guard.patience {
+            return;
+        }
+        guard.over = 0;
+        let suggestion = if self.frame_generations > 1 {
+            self.frame_generations /= 2;
+            format!(
+                "Slowed down to {} generations per frame.",
+                self.frame_generations
+            )
+        } else if self.sparse {
+            "A single generation is over budget. Try a smaller universe.".to_string()
+        } else {
+            "A single generation is over budget. Try a smaller universe, or a sparser \
+             pattern so that a large universe can switch to the sparse backend."
+                .to_string()
+        };
+        log!(LogLevel::Warn, "{}", suggestion);
+        let warning = BudgetWarning {
+            frame_ms,
+            generations_per_frame: self.frame_generations,
+            sparse: self.sparse,
+            suggestion,
+        };
+        if let Some(listener) = &guard.listener {
+            if let Err(err) = listener.call1(&JsValue::NULL, &warning.into()) {
+                log!(LogLevel::Error, "budget warning listener failed: {:?}", err);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Number of generations `tick_frame` advances by.
+    pub fn generations_per_frame(&self) -> u32 {
+        self.frame_generations
+    }
+
+    pub fn set_generations_per_frame(&mut self, generations: u32) {
+        self.frame_generations = generations.max(1);
+    }
+
+    /// Tick once per animation frame, advancing by `generations_per_frame`
+    /// generations and checking the time taken against the frame budget.
+    pub fn tick_frame(&mut self) {
+        let start = timing::now();
+        self.tick_many(self.frame_generations as usize);
+        self.check_budget(timing::now() - start);
+    }
+
+    /// Advance at `generations_per_second` however often this is called,
+    /// given the milliseconds since the last call, such as from
+    /// `requestAnimationFrame`. Fractions of a generation carry over to the
+    /// next call. Returns the number of generations ticked.
+    ///
+    /// At most a second's worth of generations is run at once, so that the
+    /// simulation doesn't try to catch up after the page has been hidden.
+    pub fn advance(&mut self, elapsed_ms: f64, generations_per_second: f64) -> u32 {
+        if !(elapsed_ms > 0.0 && generations_per_second > 0.0) {
+            return 0;
+        }
+        let due = self.pending_generations + elapsed_ms * generations_per_second / 1000.0;
+        let due = due.min(generations_per_second.max(1.0));
+        let ticks = due.floor();
+        self.pending_generations = due - ticks;
+        if ticks > 0.0 {
+            self.tick_many(ticks as usize);
+        }
+        ticks as u32
+    }
+
+    /// Halve `generations_per_frame` whenever `patience` frames in a row
+    /// take longer than `budget_ms` milliseconds, so slow devices stay
+    /// responsive. A budget of 0 turns the guard off.
+    pub fn set_frame_budget(&mut self, budget_ms: f64, patience: u32) {
+        self.budget.budget_ms = budget_ms;
+        self.budget.patience = patience.max(1);
+        self.budget.over = 0;
+    }
+
+    /// Call a function with a `BudgetWarning` whenever the frame budget
+    /// guard steps in.
+    pub fn on_budget_warning(&mut self, listener: js_sys::Function) {
+        self.budget.listener = Some(listener);
+    }
+}
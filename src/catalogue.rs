@@ -0,0 +1,82 @@
+//! Well-known patterns built into the crate, for frontends to offer as a
+//! palette.
+
+use crate::{parse_pattern, Pattern, Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// The name and RLE of each pattern in the catalogue.
+const CATALOGUE: [(&str, &str); 15] = [
+    ("block", "x = 2, y = 2\n2o$2o!"),
+    ("beehive", "x = 4, y = 3\nb2o$o2bo$b2o!"),
+    ("blinker", "x = 3, y = 1\n3o!"),
+    ("toad", "x = 4, y = 2\nb3o$3o!"),
+    ("beacon", "x = 4, y = 4\n2o$2o$2b2o$2b2o!"),
+    (
+        "pulsar",
+        "x = 13, y = 13
+2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$
+o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    ("pentadecathlon", "x = 10, y = 3\n2bo4bo$2ob4ob2o$2bo4bo!"),
+    ("glider", "x = 3, y = 3\nbo$2bo$3o!"),
+    ("lwss", "x = 5, y = 4\nbo2bo$o$o3bo$4o!"),
+    ("mwss", "x = 6, y = 5\n3bo$bo3bo$o$o4bo$5o!"),
+    ("hwss", "x = 7, y = 5\n3b2o$bo4bo$o$o5bo$6o!"),
+    (
+        "gosper glider gun",
+        "x = 36, y = 9
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$
+10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    ("r-pentomino", "x = 3, y = 3\nb2o$2o$bo!"),
+    ("acorn", "x = 7, y = 3\nbo$3bo$2o2b3o!"),
+    ("diehard", "x = 8, y = 3\n6bo$2o$bo3b3o!"),
+];
+
+/// The names of the patterns in the catalogue, such as `glider` and
+/// `gosper glider gun`.
+#[wasm_bindgen]
+pub fn catalogue_names() -> Vec<String> {
+    CATALOGUE.iter().map(|(name, _)| name.to_string()).collect()
+}
+
+/// A pattern from the catalogue, or `undefined` if there is none with the
+/// name.
+#[wasm_bindgen]
+pub fn catalogue_pattern(name: &str) -> Option<Pattern> {
+    CATALOGUE
+        .iter()
+        .find(|(entry, _)| *entry == name)
+        .map(|(_, rle)| parse_pattern(rle).unwrap())
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Make the live cells of a saved pattern or, failing that, a pattern
+    /// from the catalogue alive with its top-left corner at a cell, wrapping
+    /// around joined edges and leaving out cells beyond the others. The
+    /// pattern is mirrored from left to right if `flip` is true, then turned
+    /// clockwise by `rotation` quarter turns.
+    pub fn add_pattern(
+        &mut self,
+        name: &str,
+        row: u32,
+        col: u32,
+        rotation: u8,
+        flip: bool,
+    ) -> Result<(), UniverseError> {
+        let pattern = match self.patterns.get(name).cloned() {
+            Some(pattern) => pattern,
+            None => catalogue_pattern(name).ok_or_else(|| {
+                self.error(
+                    "add_pattern",
+                    format!("{:?}, row={}, col={}", name, row, col),
+                    format!("there is no pattern named {:?}", name),
+                )
+            })?,
+        };
+        let pattern = if flip { pattern.flipped() } else { pattern };
+        self.insert_pattern(&pattern.rotated(rotation), row, col);
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+//! The cells changed by the last call to `tick` or `tick_many`, so that
+//! frontends can redraw only those rather than the whole universe, and a
+//! count of changes to the cells which tells when that isn't enough.
+
+use crate::{TickStats, Universe};
+use wasm_bindgen::prelude::*;
+
+impl Universe {
+    /// Add the changes made by a tick to those of the current call.
+    pub(crate) fn record_changed(&mut self, stats: &TickStats) {
+        let width = self.width;
+        self.changed
+            .extend(stats.changes.iter().map(|&(row, col, _)| row * width + col));
+    }
+
+    /// Reduce the changes recorded over a call to the cells which ended up
+    /// in a different state, in order. Changes alternate between births and
+    /// deaths, so those are the cells which changed an odd number of times.
+    pub(crate) fn settle_changed(&mut self) {
+        self.changed.sort_unstable();
+        let mut write = 0;
+        let mut read = 0;
+        while read < self.changed.len() {
+            let cell = self.changed[read];
+            let run = self.changed[read..]
+                .iter()
+                .take_while(|&&other| other == cell)
+                .count();
+            if run % 2 == 1 {
+                self.changed[write] = cell;
+                write += 1;
+            }
+            read += run;
+        }
+        self.changed.truncate(write);
+    }
+
+    /// Note that the cells have been edited or moved through history, so
+    /// the changes from the last tick are no longer all that changed since
+    /// before it.
+    pub(crate) fn cells_edited(&mut self) {
+        self.cells_version += 1;
+    }
+
+    /// The cells changed since `cells_version` was `version`, if those are
+    /// just the cells changed by the last call to `tick_many`.
+    pub(crate) fn changed_since(&self, version: u64) -> Option<&[u32]> {
+        if version == self.changed_from && self.cells_version == version + 1 {
+            Some(&self.changed)
+        } else {
+            None
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// The cells which are alive now but weren't before the last call to
+    /// `tick` or `tick_many`, or the other way round, as `row * width + col`
+    /// in increasing order. Edits made since then aren't included.
+    pub fn changed_cells(&self) -> Vec<u32> {
+        self.changed.clone()
+    }
+}
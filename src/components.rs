@@ -0,0 +1,213 @@
+use crate::{Boundary, CellStorage, Universe};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::prelude::*;
+
+/// How far an object's centre has moved since it was first seen.
+struct Track {
+    centre: (f64, f64),
+    displacement: (f64, f64),
+    /// The generation the object was first seen in.
+    since: u64,
+}
+
+/// Component labels kept between calls so that objects keep their ids.
+pub(crate) struct Components {
+    /// The id of the component each cell belongs to, or 0 for dead cells,
+    /// laid out in the same way as the universe's cells.
+    ids: Vec<u32>,
+    next_id: u32,
+    tracks: HashMap<u32, Track>,
+}
+
+/// The shortest way to get from one position to another along an axis.
+fn axis_delta(from: f64, to: f64, size: u32, boundary: Boundary) -> f64 {
+    let delta = to - from;
+    if boundary == Boundary::Wrap {
+        let size = size as f64;
+        (delta + size / 2.0).rem_euclid(size) - size / 2.0
+    } else {
+        delta
+    }
+}
+
+impl Components {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        std::mem::size_of_val(self.ids.as_slice())
+    }
+}
+
+impl Universe {
+    /// Group the live cells into objects of cells connected through any of
+    /// their eight neighbours, respecting the boundaries. Returns the
+    /// component number of each cell, starting from 1, with 0 for dead
+    /// cells.
+    pub(crate) fn label_components(&self) -> Vec<u32> {
+        let stride = (self.width + 2) as usize;
+        let mut labels = vec![0; self.cells.len()];
+        let mut count = 0;
+        for idx in self.cells.live_indices() {
+            if labels[idx] != 0 || self.is_wall(idx) {
+                continue;
+            }
+            count += 1;
+            labels[idx] = count;
+            let mut stack = vec![idx];
+            while let Some(idx) = stack.pop() {
+                let (row, col) = ((idx / stride - 1) as i32, (idx % stride - 1) as i32);
+                for d_row in -1..=1 {
+                    for d_col in -1..=1 {
+                        let neighbor_row = self.row_boundary.resolve(row + d_row, self.height);
+                        let neighbor_col = self.col_boundary.resolve(col + d_col, self.width);
+                        if let (Some(r), Some(c)) = (neighbor_row, neighbor_col) {
+                            let neighbor = self.get_index(r, c);
+                            if labels[neighbor] == 0 && self.cells.is_alive(neighbor) {
+                                labels[neighbor] = count;
+                                stack.push(neighbor);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        labels
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Pointer to the id of the object each cell belongs to, as a `u32` per
+    /// cell laid out in the same way as the multi-state `cells` buffer, with
+    /// 0 for dead cells. Renderers can use it to draw each object in its own
+    /// colour.
+    ///
+    /// Each object keeps the id it had when this was last called if it
+    /// overlaps the cells it covered then, so calling this every tick keeps
+    /// colours stable as objects move.
+    pub fn component_ids(&mut self) -> *const u32 {
+        let labels = self.label_components();
+        let mut previous = self.components.take().unwrap_or(Components {
+            ids: Vec::new(),
+            next_id: 1,
+            tracks: HashMap::new(),
+        });
+        previous.ids.resize(labels.len(), 0);
+
+        // Give each component the previous id most of its cells had, taking
+        // the largest components first so they win any ties over an id.
+        let mut overlaps: HashMap<u32, HashMap<u32, u32>> = HashMap::new();
+        let mut sizes: HashMap<u32, u32> = HashMap::new();
+        for (&label, &id) in labels.iter().zip(previous.ids.iter()) {
+            if label != 0 {
+                *sizes.entry(label).or_insert(0) += 1;
+                if id != 0 {
+                    *overlaps.entry(label).or_default().entry(id).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut order: Vec<u32> = sizes.keys().cloned().collect();
+        order.sort_unstable_by_key(|label| (std::cmp::Reverse(sizes[label]), *label));
+        let mut taken = HashSet::new();
+        let mut ids = HashMap::new();
+        for label in order {
+            let mut candidates: Vec<(u32, u32)> = overlaps.get(&label).map_or(Vec::new(), |ids| {
+                ids.iter().map(|(&id, &n)| (id, n)).collect()
+            });
+            candidates.sort_unstable_by_key(|&(id, n)| (std::cmp::Reverse(n), id));
+            let id = match candidates.iter().find(|(id, _)| !taken.contains(id)) {
+                Some(&(id, _)) => id,
+                None => {
+                    previous.next_id += 1;
+                    previous.next_id - 1
+                }
+            };
+            taken.insert(id);
+            ids.insert(label, id);
+        }
+
+        let ids: Vec<u32> = labels
+            .iter()
+            .map(|label| if *label == 0 { 0 } else { ids[label] })
+            .collect();
+
+        // Follow the centre of each object to measure its velocity.
+        let stride = (self.width + 2) as usize;
+        let mut cells: HashMap<u32, Vec<(u32, u32)>> = HashMap::new();
+        for (idx, &id) in ids.iter().enumerate() {
+            if id != 0 {
+                let cell = ((idx / stride - 1) as u32, (idx % stride - 1) as u32);
+                cells.entry(id).or_default().push(cell);
+            }
+        }
+        let mut tracks = HashMap::new();
+        for (id, cells) in cells {
+            let centre = self.centroid(&cells);
+            let track = match previous.tracks.remove(&id) {
+                Some(track) => Track {
+                    centre,
+                    displacement: (
+                        track.displacement.0
+                            + axis_delta(track.centre.0, centre.0, self.height, self.row_boundary),
+                        track.displacement.1
+                            + axis_delta(track.centre.1, centre.1, self.width, self.col_boundary),
+                    ),
+                    since: track.since,
+                },
+                None => Track {
+                    centre,
+                    displacement: (0.0, 0.0),
+                    since: self.generation,
+                },
+            };
+            tracks.insert(id, track);
+        }
+
+        let components = Components {
+            ids,
+            next_id: previous.next_id,
+            tracks,
+        };
+        self.components = Some(components);
+        self.check_memory_growth();
+        self.components.as_ref().unwrap().ids.as_ptr()
+    }
+
+    /// Number of ids handed out to objects so far.
+    pub fn component_id_count(&self) -> u32 {
+        self.components
+            .as_ref()
+            .map_or(0, |components| components.next_id - 1)
+    }
+
+    /// The motion of each object found by the last call to `component_ids`
+    /// as `[id, row, col, rows_per_tick, cols_per_tick, ...]`, where `row`
+    /// and `col` are the object's centre and its velocity is averaged over
+    /// every tick since it was first seen.
+    ///
+    /// Calling `component_ids` every tick or two is needed to keep objects
+    /// matched up as they move.
+    pub fn object_velocities(&self) -> Vec<f64> {
+        let components = match &self.components {
+            Some(components) => components,
+            None => return Vec::new(),
+        };
+        let mut ids: Vec<_> = components.tracks.keys().cloned().collect();
+        ids.sort_unstable();
+        let mut velocities = Vec::with_capacity(ids.len() * 5);
+        for id in ids {
+            let track = &components.tracks[&id];
+            let ticks = (self.generation - track.since).max(1) as f64;
+            velocities.extend(&[
+                id as f64,
+                track.centre.0,
+                track.centre.1,
+                track.displacement.0 / ticks,
+                track.displacement.1 / ticks,
+            ]);
+        }
+        velocities
+    }
+
+    pub fn free_component_ids(&mut self) {
+        self.components = None;
+    }
+}
@@ -0,0 +1,137 @@
+use crate::errors;
+use crate::pattern::{parse_pattern, PatternFit, RuleMismatch};
+use crate::rng;
+use crate::rule::Rule;
+use crate::{Cell, CellStorage, MirrorMode, Topology, Universe, UniverseError};
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+/// How to set up a new universe. Every field is optional in JavaScript:
+///
+/// ```js
+/// Universe.new_with({
+///   width: 200,
+///   height: 120,
+///   rule: "B3/S23",
+///   topology: { top_bottom: "wrap", left_right: "absorbing" },
+///   seed: 42,
+///   density: 0.3,
+///   symmetry: "fourFold",
+///   initialPattern: "x = 3, y = 3\nbo$2bo$3o!",
+/// });
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct UniverseConfig {
+    pub width: u32,
+    pub height: u32,
+    /// The rule in B/S notation.
+    pub rule: String,
+    pub topology: Topology,
+    /// Seed for the random start and for features such as `spray`, or
+    /// `None` for a different start each time.
+    pub seed: Option<u64>,
+    /// Fraction of cells which start alive, which defaults to a half
+    /// without an initial pattern and none with one.
+    pub density: Option<f64>,
+    /// Symmetry of the random start about the centre of the universe.
+    pub symmetry: MirrorMode,
+    /// A pattern in any format read by `parse_pattern`, placed in the
+    /// centre of the universe on top of the random start.
+    pub initial_pattern: Option<String>,
+}
+
+impl Default for UniverseConfig {
+    fn default() -> UniverseConfig {
+        UniverseConfig {
+            width: 100,
+            height: 100,
+            rule: "B3/S23".to_string(),
+            topology: Topology::default(),
+            seed: None,
+            density: None,
+            symmetry: MirrorMode::Off,
+            initial_pattern: None,
+        }
+    }
+}
+
+impl Universe {
+    /// Create a universe from a configuration, failing if it has no cells,
+    /// more than `MAX_CELLS` or an unsupported rule.
+    pub fn from_config(config: UniverseConfig) -> Result<Universe, UniverseError> {
+        let args = || format!("{}x{}, rule={}", config.width, config.height, config.rule);
+        if let Err(err) = errors::grid_size(&[config.width, config.height]) {
+            return Err(UniverseError::constructing("new_with", args(), err));
+        }
+        let rule = match Rule::parse(&config.rule).filter(|rule| rule.is_supported()) {
+            Some(rule) => rule,
+            None => {
+                return Err(UniverseError::constructing(
+                    "new_with",
+                    args(),
+                    format!("the rule {} is not supported", config.rule),
+                ))
+            }
+        };
+        let mut universe = Self::empty(config.width, config.height);
+        universe.apply_rule(rule);
+        universe.set_boundary(config.topology.top_bottom, config.topology.left_right);
+        let seed = config.seed.unwrap_or_else(rng::random_seed);
+        universe.set_seed(seed);
+
+        let pattern = match &config.initial_pattern {
+            Some(text) => Some(parse_pattern(text).map_err(|err| {
+                UniverseError::constructing("new_with", args(), format!("invalid pattern: {}", err))
+            })?),
+            None => None,
+        };
+        let default_density = if pattern.is_some() { 0.0 } else { 0.5 };
+        universe.fill_symmetric(config.density.unwrap_or(default_density), config.symmetry);
+        if let Some(pattern) = &pattern {
+            universe.place_pattern(pattern, 0, 0, PatternFit::Centre, RuleMismatch::Warn)?;
+        }
+        universe.initial_cells = universe.cells.clone();
+        Ok(universe)
+    }
+
+    /// Make a random fraction of the cells alive, choosing once for each
+    /// set of cells which are images of each other under a mirror mode.
+    fn fill_symmetric(&mut self, density: f64, symmetry: MirrorMode) {
+        if density <= 0.0 {
+            return;
+        }
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let images = self.mirror_images(row as i32, col as i32, symmetry);
+                // Only the first of the images in reading order chooses.
+                if images.iter().any(|&image| image < (row, col)) {
+                    continue;
+                }
+                if self.rng.next_f64() < density {
+                    for (row, col) in images {
+                        let idx = self.get_index(row, col);
+                        self.cells.set(idx, Cell::ALIVE);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Create a universe from a configuration object, described by
+    /// `UniverseConfig`, with defaults for anything left out.
+    pub fn new_with(config: JsValue) -> Result<Universe, UniverseError> {
+        crate::utils::set_panic_hook();
+        let config = if config.is_undefined() || config.is_null() {
+            UniverseConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config).map_err(|err| {
+                UniverseError::constructing("new_with", "config".to_string(), err.to_string())
+            })?
+        };
+        Self::from_config(config)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::Universe;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+impl Universe {
+    /// Move the editing cursor by a number of rows and columns, wrapping
+    /// around the edges, for editing with the keyboard.
+    pub fn cursor_move(&mut self, d_row: i32, d_col: i32) {
+        let (row, col) = self.cursor;
+        self.cursor = (
+            (row as i32 + d_row).rem_euclid(self.height as i32) as u32,
+            (col as i32 + d_col).rem_euclid(self.width as i32) as u32,
+        );
+    }
+
+    /// Move the editing cursor to a cell.
+    pub fn cursor_set(&mut self, row: u32, col: u32) {
+        self.cursor = (row % self.height, col % self.width);
+    }
+
+    pub fn cursor_row(&self) -> u32 {
+        self.cursor.0
+    }
+
+    pub fn cursor_col(&self) -> u32 {
+        self.cursor.1
+    }
+
+    /// Toggle the cell under the cursor.
+    pub fn cursor_toggle(&mut self) {
+        let (row, col) = self.cursor;
+        self.toggle_index(self.get_index(row, col));
+    }
+
+    /// Place the named stamp or saved pattern with its anchor under the
+    /// cursor. Returns false if it couldn't be placed.
+    pub fn cursor_stamp(&mut self, name: &str) -> bool {
+        let (row, col) = self.cursor;
+        self.stamp(name, row, col)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{Boundary, CellStorage, Universe};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+impl Universe {
+    /// A blurred map of how densely populated each part of the universe is,
+    /// for drawing a glowing layer beneath the cells.
+    ///
+    /// The universe is divided into `scale` by `scale` blocks, each of which
+    /// becomes one value giving the fraction of its cells which are alive,
+    /// and the values are then box blurred over `radius` blocks in each
+    /// direction. The values are returned row by row, with
+    /// `ceil(width / scale)` values in each row.
+    pub fn density_field(&self, scale: u32, radius: u32) -> Vec<f32> {
+        let scale = scale.max(1);
+        let cols = self.width.div_ceil(scale) as usize;
+        let rows = self.height.div_ceil(scale) as usize;
+        let mut field = vec![0.0; rows * cols];
+        let stride = (self.width + 2) as usize;
+        for idx in self.cells.live_indices() {
+            let (row, col) = (idx / stride - 1, idx % stride - 1);
+            field[row / scale as usize * cols + col / scale as usize] += 1.0;
+        }
+        for (i, value) in field.iter_mut().enumerate() {
+            // Blocks along the bottom and right edges can be cut short.
+            let block_rows = (self.height - (i / cols) as u32 * scale).min(scale);
+            let block_cols = (self.width - (i % cols) as u32 * scale).min(scale);
+            *value /= (block_rows * block_cols) as f32;
+        }
+
+        let radius = radius as isize;
+        let blur = |field: &[f32], len: usize, at: &dyn Fn(usize) -> usize, wrap: bool| {
+            let mut blurred = vec![0.0; len];
+            for (i, value) in blurred.iter_mut().enumerate() {
+                let mut sum = 0.0;
+                for offset in -radius..=radius {
+                    let j = i as isize + offset;
+                    let j = if wrap {
+                        j.rem_euclid(len as isize)
+                    } else if j < 0 || j >= len as isize {
+                        continue;
+                    } else {
+                        j
+                    };
+                    sum += field[at(j as usize)];
+                }
+                *value = sum / (2 * radius + 1) as f32;
+            }
+            blurred
+        };
+        let mut horizontal = vec![0.0; rows * cols];
+        for row in 0..rows {
+            let line = blur(
+                &field,
+                cols,
+                &|col| row * cols + col,
+                self.col_boundary == Boundary::Wrap,
+            );
+            horizontal[row * cols..(row + 1) * cols].copy_from_slice(&line);
+        }
+        for col in 0..cols {
+            let line = blur(
+                &horizontal,
+                rows,
+                &|row| row * cols + col,
+                self.row_boundary == Boundary::Wrap,
+            );
+            for (row, value) in line.into_iter().enumerate() {
+                field[row * cols + col] = value;
+            }
+        }
+        field
+    }
+}
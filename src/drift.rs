@@ -0,0 +1,65 @@
+use crate::{TickStats, Universe};
+use wasm_bindgen::prelude::*;
+
+/// The fastest drift in cells per tick along either axis. Anything faster
+/// would only carry cells round a wrapping universe more than once a tick.
+const MAX_VELOCITY: f64 = 1024.0;
+
+/// A steady flow which carries every cell along with it.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Drift {
+    /// Velocity in cells per tick.
+    velocity: (f64, f64),
+    /// Distance travelled which hasn't yet added up to a whole cell.
+    remainder: (f64, f64),
+}
+
+impl Universe {
+    /// Carry the cells along with the drift for one tick, recording the
+    /// cells moved in `stats`.
+    pub(crate) fn update_drift(&mut self, stats: &mut TickStats) {
+        let drift = &mut self.drift;
+        if drift.velocity == (0.0, 0.0) {
+            return;
+        }
+        drift.remainder.0 += drift.velocity.0;
+        drift.remainder.1 += drift.velocity.1;
+        let d_row = drift.remainder.0.trunc();
+        let d_col = drift.remainder.1.trunc();
+        drift.remainder.0 -= d_row;
+        drift.remainder.1 -= d_col;
+        if d_row != 0.0 || d_col != 0.0 {
+            self.shift_cells(d_row as i32, d_col as i32, stats);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Make every cell drift by a number of rows and columns per tick, as if
+    /// the universe were a flowing medium. Fractional velocities build up
+    /// until they move the cells by a whole cell. Velocities are limited to
+    /// 1024 cells per tick either way, and anything which isn't a number is
+    /// taken as 0.
+    pub fn set_drift(&mut self, rows_per_tick: f64, cols_per_tick: f64) {
+        let clamp = |velocity: f64| {
+            if velocity.is_nan() {
+                0.0
+            } else {
+                velocity.clamp(-MAX_VELOCITY, MAX_VELOCITY)
+            }
+        };
+        self.drift = Drift {
+            velocity: (clamp(rows_per_tick), clamp(cols_per_tick)),
+            remainder: (0.0, 0.0),
+        };
+    }
+
+    pub fn drift_rows_per_tick(&self) -> f64 {
+        self.drift.velocity.0
+    }
+
+    pub fn drift_cols_per_tick(&self) -> f64 {
+        self.drift.velocity.1
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{Cell, CellStorage, Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// Which part of a round a duel is in.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuelPhase {
+    /// Player one is placing cells.
+    PlayerOne,
+    /// Player two is placing cells.
+    PlayerTwo,
+    /// Both players have placed their cells and the universe is ready to
+    /// run.
+    Simulation,
+}
+
+/// A two player game where players take turns placing cells in their own
+/// colour and then watch them compete for territory.
+pub(crate) struct Duel {
+    phase: DuelPhase,
+    cells_per_turn: u32,
+    placed: u32,
+    ticks_per_round: u32,
+    /// Sum of each player's territory over every simulated tick.
+    scores: [u32; 2],
+}
+
+impl Universe {
+    /// The number of cells owned by each player.
+    fn territory(&self) -> [u32; 2] {
+        let mut territory = [0; 2];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                match self.cells.get(self.get_index(row, col)).state() {
+                    1 => territory[0] += 1,
+                    2 => territory[1] += 1,
+                    _ => {}
+                }
+            }
+        }
+        territory
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start a duel on an empty universe. Each round the players take turns
+    /// to place up to `cells_per_turn` cells, then `duel_simulate` runs
+    /// `ticks_per_round` ticks.
+    ///
+    /// The rule gains two colours, as with `Immigration`: player one's cells
+    /// have state 1 and player two's have state 2. New cells take the colour
+    /// of the majority of their parents.
+    pub fn start_duel(&mut self, cells_per_turn: u32, ticks_per_round: u32) {
+        self.reset_rule(self.rule.with_colours(2));
+        self.duel = Some(Duel {
+            phase: DuelPhase::PlayerOne,
+            cells_per_turn,
+            placed: 0,
+            ticks_per_round,
+            scores: [0; 2],
+        });
+    }
+
+    /// Stop the duel, leaving the cells as they are.
+    pub fn end_duel(&mut self) {
+        self.duel = None;
+    }
+
+    pub fn duel_phase(&self) -> Option<DuelPhase> {
+        self.duel.as_ref().map(|duel| duel.phase)
+    }
+
+    /// Number of cells the current player can still place this turn.
+    pub fn duel_cells_remaining(&self) -> u32 {
+        match &self.duel {
+            Some(duel) if duel.phase != DuelPhase::Simulation => duel.cells_per_turn - duel.placed,
+            _ => 0,
+        }
+    }
+
+    /// Place a cell for the player whose turn it is. Returns false if it
+    /// isn't a placement phase, the player has no cells left this turn or
+    /// the cell is already alive.
+    pub fn duel_place(&mut self, row: u32, col: u32) -> Result<bool, UniverseError> {
+        let idx = self.checked_index("duel_place", row, col)?;
+        let alive = self.cells.is_alive(idx);
+        let duel = match &mut self.duel {
+            Some(duel) => duel,
+            None => return Ok(false),
+        };
+        let cell = match duel.phase {
+            DuelPhase::PlayerOne => Cell(1),
+            DuelPhase::PlayerTwo => Cell(2),
+            DuelPhase::Simulation => return Ok(false),
+        };
+        if alive || duel.placed >= duel.cells_per_turn {
+            return Ok(false);
+        }
+        duel.placed += 1;
+        self.cells.set(idx, cell);
+        self.cells_edited();
+        Ok(true)
+    }
+
+    /// Finish the current player's turn, moving on to the other player or
+    /// to the simulation.
+    pub fn duel_end_turn(&mut self) {
+        if let Some(duel) = &mut self.duel {
+            duel.phase = match duel.phase {
+                DuelPhase::PlayerOne => DuelPhase::PlayerTwo,
+                _ => DuelPhase::Simulation,
+            };
+            duel.placed = 0;
+        }
+    }
+
+    /// Run the simulation phase of a round, adding each player's territory
+    /// after every tick to their score, then hand back to player one.
+    /// Does nothing outside the simulation phase.
+    pub fn duel_simulate(&mut self) {
+        let ticks = match &self.duel {
+            Some(duel) if duel.phase == DuelPhase::Simulation => duel.ticks_per_round,
+            _ => return,
+        };
+        for _ in 0..ticks {
+            self.tick();
+            let territory = self.territory();
+            if let Some(duel) = &mut self.duel {
+                duel.scores[0] += territory[0];
+                duel.scores[1] += territory[1];
+            }
+        }
+        if let Some(duel) = &mut self.duel {
+            duel.phase = DuelPhase::PlayerOne;
+        }
+    }
+
+    /// The number of cells currently owned by each player as
+    /// `[player one, player two]`.
+    pub fn duel_territory(&self) -> Vec<u32> {
+        self.territory().to_vec()
+    }
+
+    /// Each player's score as `[player one, player two]`.
+    pub fn duel_scores(&self) -> Vec<u32> {
+        self.duel
+            .as_ref()
+            .map_or(vec![0, 0], |duel| duel.scores.to_vec())
+    }
+}
@@ -0,0 +1,151 @@
+use crate::Universe;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// An error from a universe method, carrying the call that failed and the
+/// size of the universe at the time so frontends can explain what went
+/// wrong.
+///
+/// In JavaScript it is thrown as an `Error` named `UniverseError`, with the
+/// context also available as its `method`, `args`, `width` and `height`
+/// properties.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniverseError {
+    method: &'static str,
+    args: String,
+    width: u32,
+    height: u32,
+    message: String,
+}
+
+impl UniverseError {
+    pub fn method(&self) -> &str {
+        self.method
+    }
+
+    pub fn args(&self) -> &str {
+        &self.args
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for UniverseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Errors from constructors happen before there is a universe.
+        if self.width == 0 && self.height == 0 {
+            return write!(f, "{}({}): {}", self.method, self.args, self.message);
+        }
+        write!(
+            f,
+            "{}({}) on a {}x{} universe: {}",
+            self.method, self.args, self.width, self.height, self.message
+        )
+    }
+}
+
+impl std::error::Error for UniverseError {}
+
+impl From<UniverseError> for JsValue {
+    fn from(error: UniverseError) -> JsValue {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("UniverseError");
+        let set = |key: &str, value: JsValue| {
+            js_sys::Reflect::set(&js_error, &key.into(), &value).ok();
+        };
+        set("method", error.method.into());
+        set("args", error.args.as_str().into());
+        set("width", error.width.into());
+        set("height", error.height.into());
+        js_error.into()
+    }
+}
+
+/// The most cells a universe or grid can have, which keeps sizes from
+/// overflowing and allocations within what wasm memory can hold.
+pub(crate) const MAX_CELLS: u64 = 1 << 26;
+
+/// The number of cells in a grid with sides of the given lengths, or why a
+/// grid can't have that size: there are none or more than `MAX_CELLS`.
+pub(crate) fn grid_size(sides: &[u32]) -> Result<usize, String> {
+    let size = sides
+        .iter()
+        .try_fold(1u64, |size, &side| size.checked_mul(side as u64))
+        .filter(|&size| size <= MAX_CELLS);
+    match size {
+        Some(0) => Err("there must be at least one cell".to_string()),
+        Some(size) => Ok(size as usize),
+        None => Err(format!("there can be at most {} cells", MAX_CELLS)),
+    }
+}
+
+impl UniverseError {
+    /// The number of cells in a grid with sides of the given lengths, or an
+    /// error from the constructor `method` if it can't have that size.
+    pub(crate) fn checked_size(
+        method: &'static str,
+        sides: &[u32],
+    ) -> Result<usize, UniverseError> {
+        grid_size(sides).map_err(|message| {
+            let args = sides
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join("x");
+            UniverseError::constructing(method, args, message)
+        })
+    }
+
+    /// An error from a constructor, before there is a universe.
+    pub(crate) fn constructing(
+        method: &'static str,
+        args: String,
+        message: impl Into<String>,
+    ) -> UniverseError {
+        UniverseError {
+            method,
+            args,
+            width: 0,
+            height: 0,
+            message: message.into(),
+        }
+    }
+}
+
+impl Universe {
+    pub(crate) fn error(
+        &self,
+        method: &'static str,
+        args: String,
+        message: impl Into<String>,
+    ) -> UniverseError {
+        UniverseError {
+            method,
+            args,
+            width: self.width,
+            height: self.height,
+            message: message.into(),
+        }
+    }
+
+    /// The index of a cell given to a method, or an error if the cell lies
+    /// outside the universe.
+    pub(crate) fn checked_index(
+        &self,
+        method: &'static str,
+        row: u32,
+        col: u32,
+    ) -> Result<usize, UniverseError> {
+        if row < self.height && col < self.width {
+            Ok(self.get_index(row, col))
+        } else {
+            Err(self.error(
+                method,
+                format!("row={}, col={}", row, col),
+                "the cell is outside the universe",
+            ))
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{CellStorage, TickStats, Universe, UniverseError};
+use std::fmt::Write;
+use wasm_bindgen::prelude::*;
+
+/// What the height of each cell shows when exporting the universe as a
+/// landscape.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeightSource {
+    /// Number of ticks each live cell has survived, which needs ages to be
+    /// counted for `set_max_age` or `set_age_tracking`.
+    Age = 0,
+    /// Number of times each cell has changed state, which needs
+    /// `set_activity_tracking`.
+    Activity = 1,
+}
+
+impl Universe {
+    /// Count the changes made to each cell during a tick.
+    pub(crate) fn update_activity(&mut self, stats: &TickStats) {
+        let mut activity = match self.activity.take() {
+            Some(activity) => activity,
+            None => return,
+        };
+        for &(row, col, _) in stats.changes.iter() {
+            let idx = self.get_index(row, col);
+            activity[idx] = activity[idx].saturating_add(1);
+        }
+        self.activity = Some(activity);
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start or stop counting how many times each cell changes state.
+    /// Starting again resets the counts.
+    pub fn set_activity_tracking(&mut self, enabled: bool) {
+        self.activity = if enabled {
+            Some(vec![0; self.cells.len()])
+        } else {
+            None
+        };
+    }
+
+    /// Number of times a cell has changed state since activity tracking
+    /// started, or 0 if it isn't being tracked.
+    pub fn cell_activity(&self, row: u32, col: u32) -> Result<u32, UniverseError> {
+        let idx = self.checked_index("cell_activity", row, col)?;
+        Ok(self.activity.as_ref().map_or(0, |activity| activity[idx]))
+    }
+
+    /// The height of each cell in row-major order, which is 0 everywhere if
+    /// the source isn't being tracked.
+    pub fn heightfield(&self, source: HeightSource) -> Vec<u32> {
+        let mut heights = Vec::with_capacity((self.width * self.height) as usize);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                heights.push(match source {
+                    HeightSource::Age => self.age(idx),
+                    HeightSource::Activity => {
+                        self.activity.as_ref().map_or(0, |activity| activity[idx])
+                    }
+                });
+            }
+        }
+        heights
+    }
+
+    /// The universe as a Wavefront OBJ mesh, with each cell of non-zero
+    /// height extruded into a unit square column `scale` times as tall as
+    /// its height. Columns run along x and rows along z, with y up.
+    pub fn export_obj(&self, source: HeightSource, scale: f32) -> String {
+        trace_span!("serialize");
+        let mut obj = String::from("# wasm-game-of-life heightfield\n");
+        let mut vertices = 0;
+        for (i, &height) in self.heightfield(source).iter().enumerate() {
+            if height == 0 {
+                continue;
+            }
+            let (z, x) = (i as u32 / self.width, i as u32 % self.width);
+            let y = height as f32 * scale;
+            for &(dy, dz, dx) in BOX_CORNERS.iter() {
+                writeln!(obj, "v {} {} {}", x + dx, y * dy as f32, z + dz).unwrap();
+            }
+            for face in BOX_FACES.iter() {
+                let [a, b, c, d] = face.map(|corner| vertices + corner);
+                writeln!(obj, "f {} {} {} {}", a, b, c, d).unwrap();
+            }
+            vertices += BOX_CORNERS.len();
+        }
+        obj
+    }
+}
+
+/// The `(y, z, x)` corners of a column, bottom then top, each going around
+/// the square.
+const BOX_CORNERS: [(u32, u32, u32); 8] = [
+    (0, 0, 0),
+    (0, 0, 1),
+    (0, 1, 1),
+    (0, 1, 0),
+    (1, 0, 0),
+    (1, 0, 1),
+    (1, 1, 1),
+    (1, 1, 0),
+];
+
+/// The sides and top of a column as 1-based indices into `BOX_CORNERS`,
+/// wound counter-clockwise when seen from outside. The bottom is left open.
+const BOX_FACES: [[usize; 4]; 5] = [
+    [5, 8, 7, 6],
+    [1, 5, 6, 2],
+    [2, 6, 7, 3],
+    [3, 7, 8, 4],
+    [4, 8, 5, 1],
+];
@@ -0,0 +1,178 @@
+use crate::{ages, lifetimes, Boundary, CellStorage, TickStats, Universe, UniverseError};
+use std::f64::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// What the moving frame keeps in the centre of the universe.
+pub(crate) enum Follow {
+    Population,
+    /// The object last seen with its centre at `(row, col)`.
+    Object(f64, f64),
+}
+
+/// The centre of a set of positions along one axis. Positions on a wrapping
+/// axis are treated as angles around a circle, so a cluster straddling the
+/// edge has its centre at the edge rather than the middle of the universe.
+fn mean_position(positions: &[u32], size: u32, boundary: Boundary) -> f64 {
+    let count = positions.len().max(1) as f64;
+    if boundary != Boundary::Wrap {
+        return positions.iter().map(|&pos| pos as f64).sum::<f64>() / count;
+    }
+    let angle = |pos: u32| 2.0 * PI * pos as f64 / size as f64;
+    let sin = positions.iter().map(|&pos| angle(pos).sin()).sum::<f64>() / count;
+    let cos = positions.iter().map(|&pos| angle(pos).cos()).sum::<f64>() / count;
+    (sin.atan2(cos) * size as f64 / (2.0 * PI)).rem_euclid(size as f64)
+}
+
+impl Universe {
+    pub(crate) fn centroid(&self, cells: &[(u32, u32)]) -> (f64, f64) {
+        let rows: Vec<_> = cells.iter().map(|&(row, _)| row).collect();
+        let cols: Vec<_> = cells.iter().map(|&(_, col)| col).collect();
+        (
+            mean_position(&rows, self.height, self.row_boundary),
+            mean_position(&cols, self.width, self.col_boundary),
+        )
+    }
+
+    pub(crate) fn live_cells(&self) -> Vec<(u32, u32)> {
+        self.iter_live_cells().collect()
+    }
+
+    /// Where a cell ends up when every cell is moved by a number of rows and
+    /// columns, or `None` if it is moved across an edge which doesn't wrap
+    /// or onto a wall.
+    fn shifted_index(&self, row: u32, col: u32, d_row: i32, d_col: i32) -> Option<usize> {
+        let shift = |pos: u32, delta: i32, size: u32, boundary: Boundary| {
+            let pos = pos as i64 + delta as i64;
+            let pos = if boundary == Boundary::Wrap {
+                pos.rem_euclid(size as i64)
+            } else {
+                pos
+            };
+            Some(pos as u32).filter(|_| pos >= 0 && pos < size as i64)
+        };
+        let row = shift(row, d_row, self.height, self.row_boundary)?;
+        let col = shift(col, d_col, self.width, self.col_boundary)?;
+        Some(self.get_index(row, col)).filter(|&idx| !self.is_wall(idx))
+    }
+
+    /// Move values laid out in the same way as `cells` along with the cells,
+    /// leaving `empty` wherever nothing was moved to.
+    fn shifted<T: Copy>(&self, values: &[T], d_row: i32, d_col: i32, empty: T) -> Vec<T> {
+        let mut shifted = vec![empty; values.len()];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if let Some(idx) = self.shifted_index(row, col, d_row, d_col) {
+                    shifted[idx] = values[self.get_index(row, col)];
+                }
+            }
+        }
+        shifted
+    }
+
+    /// Move every cell by a number of rows and columns, recording the cells
+    /// which end up changed in `stats`. Cells moved across an edge which
+    /// doesn't wrap or onto a wall are lost. Ages and lifetimes move with
+    /// the cells, while walls, anchors and resources stay where they are.
+    pub(crate) fn shift_cells(&mut self, d_row: i32, d_col: i32, stats: &mut TickStats) {
+        let mut cells = self.dead_cells();
+        for (row, col) in self.live_cells() {
+            if let Some(idx) = self.shifted_index(row, col, d_row, d_col) {
+                cells.set(idx, self.cells.get(self.get_index(row, col)));
+            }
+        }
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let alive = cells.is_alive(idx);
+                if alive != self.cells.is_alive(idx) {
+                    stats.record_change(row, col, alive);
+                }
+            }
+        }
+        stats.population = cells.population();
+        if let Some(ages) = &self.ages {
+            self.ages = Some(self.shifted(ages, d_row, d_col, ages::LONG_DEAD));
+        }
+        if let Some(mut lifetimes) = self.lifetimes.take() {
+            lifetimes.born = self.shifted(&lifetimes.born, d_row, d_col, lifetimes::UNKNOWN);
+            self.lifetimes = Some(lifetimes);
+        }
+        self.cells = cells;
+        if let Some(period) = &mut self.period {
+            period.mark_stale();
+        }
+    }
+
+    /// Shift the universe so that whatever is being followed is back in the
+    /// centre, recording the cells moved in `stats`.
+    pub(crate) fn update_follow(&mut self, stats: &mut TickStats) {
+        let target = match self.follow {
+            Some(Follow::Population) => self.live_cells(),
+            Some(Follow::Object(row, col)) => {
+                // The object is taken to be the one containing the live cell
+                // closest to where it was last seen.
+                let nearest = self.live_cells().into_iter().min_by(|a, b| {
+                    let distance =
+                        |&(r, c): &(u32, u32)| (r as f64 - row).powi(2) + (c as f64 - col).powi(2);
+                    distance(a).partial_cmp(&distance(b)).unwrap()
+                });
+                match nearest {
+                    Some((row, col)) => self.connected_cells(row, col),
+                    None => Vec::new(),
+                }
+            }
+            None => return,
+        };
+        if target.is_empty() {
+            return;
+        }
+        let (row, col) = self.centroid(&target);
+        let d_row = (self.height / 2) as i32 - row.round() as i32;
+        let d_col = (self.width / 2) as i32 - col.round() as i32;
+        if d_row != 0 || d_col != 0 {
+            self.shift_cells(d_row, d_col, stats);
+            self.frame_offset.0 += d_row;
+            self.frame_offset.1 += d_col;
+        }
+        if let Some(Follow::Object(..)) = self.follow {
+            self.follow = Some(Follow::Object(row + d_row as f64, col + d_col as f64));
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// After every tick, shift the universe to keep the centre of the live
+    /// cells in the middle, so that spaceships can be followed indefinitely.
+    pub fn follow_population(&mut self) {
+        self.follow = Some(Follow::Population);
+        self.update_follow(&mut TickStats::default());
+    }
+
+    /// After every tick, shift the universe to keep the object containing a
+    /// cell in the middle. Returns false if the cell is dead.
+    pub fn follow_object(&mut self, row: u32, col: u32) -> Result<bool, UniverseError> {
+        let idx = self.checked_index("follow_object", row, col)?;
+        if !self.cells.is_alive(idx) {
+            return Ok(false);
+        }
+        self.follow = Some(Follow::Object(row as f64, col as f64));
+        self.update_follow(&mut TickStats::default());
+        Ok(true)
+    }
+
+    pub fn stop_following(&mut self) {
+        self.follow = None;
+    }
+
+    /// Total number of rows the universe has been shifted down by to follow
+    /// its cells, for moving a camera or background to match.
+    pub fn frame_offset_row(&self) -> i32 {
+        self.frame_offset.0
+    }
+
+    /// Total number of columns the universe has been shifted right by.
+    pub fn frame_offset_col(&self) -> i32 {
+        self.frame_offset.1
+    }
+}
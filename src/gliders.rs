@@ -0,0 +1,201 @@
+use crate::{CellStorage, Universe};
+use wasm_bindgen::prelude::*;
+
+/// The two distinct shapes of a south-east travelling glider. The other two
+/// phases are reflections of these, and the other directions are rotations.
+const GLIDER_SHAPES: [[&str; 3]; 2] = [[".O.", "..O", "OOO"], ["O.O", ".OO", ".O."]];
+
+/// The eight symmetries of a square as matrices `[a, b, c, d]` mapping
+/// `(row, col)` to `(a * row + b * col, c * row + d * col)`.
+const SYMMETRIES: [[i32; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, -1, 0],
+    [-1, 0, 0, -1],
+    [0, -1, 1, 0],
+    [1, 0, 0, -1],
+    [-1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, -1, 0],
+];
+
+#[derive(Clone, Copy)]
+pub(crate) struct Glider {
+    pub(crate) row: u32,
+    pub(crate) col: u32,
+    direction: (i32, i32),
+}
+
+/// Keeps a running count of the distinct gliders seen in a universe.
+pub(crate) struct GliderDetector {
+    interval: u32,
+    ticks: u32,
+    count: u32,
+    /// From `glider_masks`.
+    masks: Vec<(u16, (i32, i32))>,
+    gliders: Vec<Glider>,
+}
+
+/// Each 3x3 glider image as a row-major bitmask along with the direction it
+/// travels in.
+pub(crate) fn glider_masks() -> Vec<(u16, (i32, i32))> {
+    let mut masks = Vec::new();
+    for shape in GLIDER_SHAPES.iter() {
+        for [a, b, c, d] in SYMMETRIES.iter().cloned() {
+            let mut mask = 0;
+            for (row, line) in shape.iter().enumerate() {
+                for (col, ch) in line.chars().enumerate() {
+                    if ch != 'O' {
+                        continue;
+                    }
+                    let (row, col) = (row as i32 - 1, col as i32 - 1);
+                    let new_row = a * row + b * col + 1;
+                    let new_col = c * row + d * col + 1;
+                    mask |= 1 << (new_row * 3 + new_col);
+                }
+            }
+            if !masks.iter().any(|&(m, _)| m == mask) {
+                masks.push((mask, (a + b, c + d)));
+            }
+        }
+    }
+    masks
+}
+
+impl GliderDetector {
+    fn new(interval: u32) -> GliderDetector {
+        GliderDetector {
+            interval: interval.max(1),
+            ticks: 0,
+            count: 0,
+            masks: glider_masks(),
+            gliders: Vec::new(),
+        }
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.masks.capacity() * std::mem::size_of::<(u16, (i32, i32))>()
+            + self.gliders.capacity() * std::mem::size_of::<Glider>()
+    }
+
+    /// Replace the gliders from the previous scan, counting any which can't
+    /// be explained as one of the previous gliders having moved on.
+    fn record(&mut self, found: Vec<Glider>, width: u32, height: u32) {
+        let steps = (self.interval / 4) as i32;
+        let wrapped_distance = |from: u32, to: u32, size: u32| {
+            let delta = (to as i32 - from as i32).rem_euclid(size as i32);
+            delta.min(size as i32 - delta)
+        };
+        for glider in found.iter() {
+            let seen = self.gliders.iter().any(|previous| {
+                if previous.direction != glider.direction {
+                    return false;
+                }
+                let (d_row, d_col) = previous.direction;
+                let row = (previous.row as i32 + d_row * steps).rem_euclid(height as i32) as u32;
+                let col = (previous.col as i32 + d_col * steps).rem_euclid(width as i32) as u32;
+                wrapped_distance(row, glider.row, height) <= 1
+                    && wrapped_distance(col, glider.col, width) <= 1
+            });
+            if !seen {
+                self.count += 1;
+            }
+        }
+        self.gliders = found;
+    }
+}
+
+impl Universe {
+    /// Advance the glider detector by one tick, scanning the universe if the
+    /// detector's interval has elapsed.
+    pub(crate) fn update_glider_detector(&mut self) {
+        if let Some(mut detector) = self.glider_detector.take() {
+            if detector.ticks % detector.interval == 0 {
+                let found = self.find_gliders(&detector.masks);
+                detector.record(found, self.width, self.height);
+            }
+            detector.ticks += 1;
+            self.glider_detector = Some(detector);
+        }
+    }
+
+    /// Find every glider which is isolated from other live cells.
+    pub(crate) fn find_gliders(&self, masks: &[(u16, (i32, i32))]) -> Vec<Glider> {
+        let mut found = Vec::new();
+        if self.width < 5 || self.height < 5 {
+            return found;
+        }
+        let alive = |row: u32, col: u32| {
+            let idx = self.get_index(row % self.height, col % self.width);
+            self.cells.is_alive(idx)
+        };
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let mut mask: u16 = 0;
+                for i in 0..9 {
+                    if alive(row + i / 3, col + i % 3) {
+                        mask |= 1 << i;
+                    }
+                }
+                if mask.count_ones() != 5 {
+                    continue;
+                }
+                let direction = match masks.iter().find(|&&(m, _)| m == mask) {
+                    Some(&(_, direction)) => direction,
+                    None => continue,
+                };
+                // The ring of cells around the glider must be empty.
+                let ring_row = row + self.height - 1;
+                let ring_col = col + self.width - 1;
+                let isolated = (0..5).all(|i| {
+                    !alive(ring_row, ring_col + i)
+                        && !alive(ring_row + 4, ring_col + i)
+                        && !alive(ring_row + i, ring_col)
+                        && !alive(ring_row + i, ring_col + 4)
+                });
+                if isolated {
+                    found.push(Glider {
+                        row: (row + 1) % self.height,
+                        col: (col + 1) % self.width,
+                        direction,
+                    });
+                }
+            }
+        }
+        found
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start counting gliders, scanning the universe every `interval` ticks.
+    ///
+    /// Gliders already in the universe are counted by the first scan.
+    pub fn enable_glider_detection(&mut self, interval: u32) {
+        self.glider_detector = Some(GliderDetector::new(interval));
+    }
+
+    pub fn disable_glider_detection(&mut self) {
+        self.glider_detector = None;
+    }
+
+    /// Number of distinct gliders seen since detection was enabled.
+    pub fn glider_count(&self) -> u32 {
+        self.glider_detector.as_ref().map_or(0, |d| d.count)
+    }
+
+    /// Average number of new gliders per tick since detection was enabled.
+    pub fn glider_rate(&self) -> f64 {
+        match &self.glider_detector {
+            Some(detector) if detector.ticks > 0 => detector.count as f64 / detector.ticks as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Centres of the gliders found by the latest scan as
+    /// `[row, col, row, col, ...]`.
+    pub fn glider_positions(&self) -> Vec<u32> {
+        self.glider_detector.as_ref().map_or(Vec::new(), |d| {
+            d.gliders.iter().flat_map(|g| vec![g.row, g.col]).collect()
+        })
+    }
+}
@@ -0,0 +1,211 @@
+use crate::gliders::glider_masks;
+use crate::{CellStorage, LogLevel, Pattern, Universe};
+use wasm_bindgen::prelude::*;
+
+/// What has to happen for a goal to be met.
+enum Condition {
+    /// The population rises above a number of cells.
+    PopulationAbove(u32),
+    /// The centre of an isolated glider reaches a column.
+    GliderAtColumn(u32),
+    /// The cells in a rectangle with its top-left corner at `(row, col)`
+    /// are exactly the live cells of a pattern.
+    PatternAt {
+        row: u32,
+        col: u32,
+        width: u32,
+        height: u32,
+        cells: Vec<(i32, i32)>,
+    },
+}
+
+struct Goal {
+    id: u32,
+    condition: Condition,
+    /// The generation the goal was met in, after which it isn't checked
+    /// again.
+    met: Option<u64>,
+}
+
+/// Sent to the goal listener when a goal is met.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GoalEvent {
+    /// The id returned when the goal was added.
+    pub id: u32,
+    pub generation: u64,
+}
+
+/// Goals checked after every tick, so that frontends can award achievements
+/// without scanning the cells themselves.
+#[derive(Default)]
+pub(crate) struct Goals {
+    goals: Vec<Goal>,
+    next_id: u32,
+    listener: Option<js_sys::Function>,
+}
+
+impl Goals {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.goals.capacity() * std::mem::size_of::<Goal>()
+            + self
+                .goals
+                .iter()
+                .map(|goal| match &goal.condition {
+                    Condition::PatternAt { cells, .. } => {
+                        cells.capacity() * std::mem::size_of::<(i32, i32)>()
+                    }
+                    _ => 0,
+                })
+                .sum::<usize>()
+    }
+}
+
+impl Universe {
+    fn add_goal(&mut self, condition: Condition) -> u32 {
+        let id = self.goals.next_id;
+        self.goals.next_id += 1;
+        self.goals.goals.push(Goal {
+            id,
+            condition,
+            met: None,
+        });
+        id
+    }
+
+    fn pattern_matches(
+        &self,
+        (row, col): (u32, u32),
+        (width, height): (u32, u32),
+        cells: &[(i32, i32)],
+    ) -> bool {
+        let alive = |d_row: u32, d_col: u32| {
+            let row = (row + d_row) % self.height;
+            let col = (col + d_col) % self.width;
+            self.cells.is_alive(self.get_index(row, col))
+        };
+        (0..height).all(|d_row| {
+            (0..width)
+                .all(|d_col| alive(d_row, d_col) == cells.contains(&(d_row as i32, d_col as i32)))
+        })
+    }
+
+    /// Check the goals which haven't been met yet against the latest
+    /// generation, telling the listener about any which now are.
+    pub(crate) fn update_goals(&mut self, population: usize) {
+        if self.goals.goals.iter().all(|goal| goal.met.is_some()) {
+            return;
+        }
+        // Only scan for gliders if a goal needs them.
+        let gliders = if self.goals.goals.iter().any(|goal| {
+            goal.met.is_none() && matches!(goal.condition, Condition::GliderAtColumn(_))
+        }) {
+            self.find_gliders(&glider_masks())
+        } else {
+            Vec::new()
+        };
+        let mut events = Vec::new();
+        for goal in self.goals.goals.iter() {
+            if goal.met.is_some() {
+                continue;
+            }
+            let met = match &goal.condition {
+                Condition::PopulationAbove(threshold) => population > *threshold as usize,
+                Condition::GliderAtColumn(col) => gliders.iter().any(|glider| glider.col == *col),
+                Condition::PatternAt {
+                    row,
+                    col,
+                    width,
+                    height,
+                    cells,
+                } => self.pattern_matches((*row, *col), (*width, *height), cells),
+            };
+            if met {
+                events.push(GoalEvent {
+                    id: goal.id,
+                    generation: self.generation,
+                });
+            }
+        }
+        for event in events {
+            log!(LogLevel::Info, "Goal {} met", event.id);
+            if let Some(goal) = self.goals.goals.iter_mut().find(|goal| goal.id == event.id) {
+                goal.met = Some(event.generation);
+            }
+            if let Some(listener) = &self.goals.listener {
+                if let Err(err) = listener.call1(&JsValue::NULL, &event.into()) {
+                    log!(LogLevel::Error, "goal listener failed: {:?}", err);
+                }
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Add a goal which is met when the population rises above `threshold`
+    /// cells. Returns the id of the goal.
+    pub fn add_population_goal(&mut self, threshold: u32) -> u32 {
+        self.add_goal(Condition::PopulationAbove(threshold))
+    }
+
+    /// Add a goal which is met when a glider, isolated from other live
+    /// cells, is centred on a column. Gliders move a column every four
+    /// generations, so any glider crossing the column is seen. Returns the
+    /// id of the goal.
+    pub fn add_glider_goal(&mut self, col: u32) -> u32 {
+        self.add_goal(Condition::GliderAtColumn(col))
+    }
+
+    /// Add a goal which is met when the cells covered by a pattern placed
+    /// with its top-left corner at a cell are exactly the pattern's live
+    /// cells, wrapping around the edges. Returns the id of the goal.
+    pub fn add_pattern_goal(&mut self, pattern: &Pattern, row: u32, col: u32) -> u32 {
+        self.add_goal(Condition::PatternAt {
+            row,
+            col,
+            width: pattern.width(),
+            height: pattern.height(),
+            cells: pattern.offsets(),
+        })
+    }
+
+    /// Remove a goal, returning false if there is none with the id.
+    pub fn remove_goal(&mut self, id: u32) -> bool {
+        let count = self.goals.goals.len();
+        self.goals.goals.retain(|goal| goal.id != id);
+        self.goals.goals.len() != count
+    }
+
+    pub fn clear_goals(&mut self) {
+        self.goals.goals.clear();
+    }
+
+    /// The generation in which a goal was met, or `undefined` if it hasn't
+    /// been met or doesn't exist.
+    pub fn goal_met_at(&self, id: u32) -> Option<u64> {
+        self.goals
+            .goals
+            .iter()
+            .find(|goal| goal.id == id)
+            .and_then(|goal| goal.met)
+    }
+
+    /// Check a goal again from the next tick, as if it had just been added.
+    pub fn reset_goal(&mut self, id: u32) {
+        if let Some(goal) = self.goals.goals.iter_mut().find(|goal| goal.id == id) {
+            goal.met = None;
+        }
+    }
+
+    /// Call a function with a `GoalEvent` whenever a goal is met. Goals are
+    /// checked after each tick, and each is only met once until it is
+    /// reset.
+    pub fn on_goal_met(&mut self, listener: js_sys::Function) {
+        self.goals.listener = Some(listener);
+    }
+
+    pub fn clear_goal_listener(&mut self) {
+        self.goals.listener = None;
+    }
+}
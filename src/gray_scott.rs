@@ -0,0 +1,221 @@
+//! The Gray-Scott model of two chemicals reacting and diffusing across a
+//! grid, which grows spots, stripes and mazes depending on its feed and kill
+//! rates.
+//!
+//! Chemical U is fed in everywhere and is turned into V by the reaction
+//! `U + 2V → 3V`, while V is removed at the kill rate. Each step:
+//!
+//! ```text
+//! u' = u + Du ∇²u − uv² + feed (1 − u)
+//! v' = v + Dv ∇²v + uv² − (feed + kill) v
+//! ```
+
+use crate::rng::{self, Rng};
+use crate::{Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// Weights of a cell's neighbours in the Laplacian, which with the cell
+/// itself at −1 sum to zero.
+const ADJACENT_WEIGHT: f32 = 0.2;
+const DIAGONAL_WEIGHT: f32 = 0.05;
+
+#[wasm_bindgen]
+pub struct GrayScott {
+    width: u32,
+    height: u32,
+    /// Number of steps since the grid was created.
+    generation: u64,
+    /// Concentration of U in each cell, row by row.
+    u: Vec<f32>,
+    /// Concentration of V in each cell, row by row.
+    v: Vec<f32>,
+    feed: f32,
+    kill: f32,
+    diffusion_u: f32,
+    diffusion_v: f32,
+    rng: Rng,
+}
+
+impl GrayScott {
+    fn get_index(&self, row: u32, col: u32) -> usize {
+        (row * self.width + col) as usize
+    }
+
+    /// The Laplacian of a field at every cell, wrapping around the edges.
+    fn laplacian(&self, field: &[f32]) -> Vec<f32> {
+        let mut result = Vec::with_capacity(field.len());
+        for row in 0..self.height {
+            let rows = [
+                (row + self.height - 1) % self.height,
+                (row + 1) % self.height,
+            ];
+            for col in 0..self.width {
+                let cols = [(col + self.width - 1) % self.width, (col + 1) % self.width];
+                let at = |row: u32, col: u32| field[self.get_index(row, col)];
+                let adjacent =
+                    at(rows[0], col) + at(rows[1], col) + at(row, cols[0]) + at(row, cols[1]);
+                let diagonal = rows
+                    .iter()
+                    .flat_map(|&row| cols.iter().map(move |&col| (row, col)))
+                    .map(|(row, col)| at(row, col))
+                    .sum::<f32>();
+                result.push(adjacent * ADJACENT_WEIGHT + diagonal * DIAGONAL_WEIGHT - at(row, col));
+            }
+        }
+        result
+    }
+}
+
+seedable! {
+    /// Seed the random number generator used by `randomise`, so the same
+    /// seed gives the same grid.
+    GrayScott
+}
+
+#[wasm_bindgen]
+impl GrayScott {
+    /// Create a grid full of U with no V, fed and killed at rates which grow
+    /// a maze of stripes.
+    pub fn new(width: u32, height: u32) -> Result<GrayScott, UniverseError> {
+        crate::utils::set_panic_hook();
+        let size = UniverseError::checked_size("GrayScott::new", &[width, height])?;
+        Ok(GrayScott {
+            width,
+            height,
+            generation: 0,
+            u: vec![1.0; size],
+            v: vec![0.0; size],
+            feed: 0.029,
+            kill: 0.057,
+            diffusion_u: 1.0,
+            diffusion_v: 0.5,
+            rng: Rng::new(rng::random_seed()),
+        })
+    }
+
+    /// Create a grid the same size as a universe, with V added wherever a
+    /// cell is alive, so patterns drawn with the Life editing tools can seed
+    /// the reaction.
+    pub fn from_universe(universe: &Universe) -> GrayScott {
+        let mut grid = GrayScott::new(universe.width(), universe.height()).unwrap();
+        for (row, col) in universe.iter_live_cells() {
+            grid.add_v(row, col, 0);
+        }
+        grid
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn feed(&self) -> f32 {
+        self.feed
+    }
+
+    pub fn kill(&self) -> f32 {
+        self.kill
+    }
+
+    /// Set the feed and kill rates. Feed 0.029 with kill 0.057 grows a
+    /// maze, 0.035 with 0.065 grows spots and 0.014 with 0.054 sends out
+    /// waves.
+    pub fn set_feed_kill(&mut self, feed: f32, kill: f32) {
+        self.feed = feed;
+        self.kill = kill;
+    }
+
+    /// Set how quickly each chemical spreads to neighbouring cells. Steps
+    /// become unstable with rates above 1.
+    pub fn set_diffusion(&mut self, u: f32, v: f32) {
+        self.diffusion_u = u;
+        self.diffusion_v = v;
+    }
+
+    /// Concentration of U in each cell, row by row, as a `Float32Array`.
+    pub fn u_field(&self) -> Vec<f32> {
+        self.u.clone()
+    }
+
+    /// Concentration of V in each cell, row by row, as a `Float32Array`.
+    pub fn v_field(&self) -> Vec<f32> {
+        self.v.clone()
+    }
+
+    /// Turn half of the U into V in the square of cells within `radius` of
+    /// a cell, wrapping around the edges, which starts the reaction there.
+    pub fn add_v(&mut self, row: u32, col: u32, radius: u32) {
+        let radius = radius as i32;
+        for d_row in -radius..=radius {
+            for d_col in -radius..=radius {
+                let row = (row as i32 + d_row).rem_euclid(self.height as i32) as u32;
+                let col = (col as i32 + d_col).rem_euclid(self.width as i32) as u32;
+                let idx = self.get_index(row, col);
+                self.u[idx] = 0.5;
+                self.v[idx] = 0.5;
+            }
+        }
+    }
+
+    /// Reset the grid to U everywhere, then add V in a few small squares at
+    /// random.
+    pub fn randomise(&mut self) {
+        self.clear();
+        let patches = (self.width * self.height / 2000).max(1);
+        for _ in 0..patches {
+            let row = (self.rng.next_f64() * self.height as f64) as u32;
+            let col = (self.rng.next_f64() * self.width as f64) as u32;
+            self.add_v(row, col, 3);
+        }
+    }
+
+    /// Reset the grid to U everywhere with no V.
+    pub fn clear(&mut self) {
+        self.u.fill(1.0);
+        self.v.fill(0.0);
+    }
+
+    pub fn tick(&mut self) {
+        self.tick_many(1);
+    }
+
+    pub fn tick_many(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            trace_span!("tick");
+            let laplacian_u = self.laplacian(&self.u);
+            let laplacian_v = self.laplacian(&self.v);
+            for i in 0..self.u.len() {
+                let (u, v) = (self.u[i], self.v[i]);
+                let reaction = u * v * v;
+                self.u[i] = (u + self.diffusion_u * laplacian_u[i] - reaction
+                    + self.feed * (1.0 - u))
+                    .clamp(0.0, 1.0);
+                self.v[i] = (v + self.diffusion_v * laplacian_v[i] + reaction
+                    - (self.feed + self.kill) * v)
+                    .clamp(0.0, 1.0);
+            }
+            self.generation += 1;
+        }
+    }
+
+    /// The grid as RGBA bytes with one pixel per cell, ready to be put into
+    /// an `ImageData`, shaded from white where there is no V to black where
+    /// V is at its strongest.
+    pub fn image(&self) -> Vec<u8> {
+        trace_span!("render");
+        let mut pixels = Vec::with_capacity(self.v.len() * 4);
+        for &v in self.v.iter() {
+            // V rarely rises much above a half, so double it for contrast.
+            let shade = 255 - ((v * 2.0).min(1.0) * 255.0) as u8;
+            pixels.extend_from_slice(&[shade, shade, shade, 255]);
+        }
+        pixels
+    }
+}
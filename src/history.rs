@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+/// Previous generations of a universe, compressed to fit within a byte
+/// budget and optionally limited to a number of generations.
+///
+/// The most recent snapshot is stored in full and each older snapshot is
+/// stored as its difference from the next newer one. Both are run-length
+/// encoded, so a mostly-empty or mostly-unchanging universe takes very little
+/// space. When the budget or capacity is exceeded the oldest snapshots are
+/// dropped.
+pub(crate) struct History {
+    budget: usize,
+    /// The most snapshots to keep.
+    capacity: usize,
+    bytes: usize,
+    /// Encoded snapshots, newest first.
+    snapshots: VecDeque<Vec<u8>>,
+}
+
+impl History {
+    pub(crate) fn new(budget: usize) -> History {
+        History {
+            budget,
+            capacity: usize::MAX,
+            bytes: 0,
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Total size of the encoded snapshots.
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub(crate) fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.trim();
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.snapshots.clear();
+        self.bytes = 0;
+    }
+
+    /// Record a snapshot of packed cells as the most recent one.
+    pub(crate) fn push(&mut self, packed: &[u8]) {
+        if let Some(head) = self.snapshots.front_mut() {
+            let delta = xor(&decode(head), packed);
+            self.bytes -= head.len();
+            *head = encode(&delta);
+            self.bytes += head.len();
+        }
+        let head = encode(packed);
+        self.bytes += head.len();
+        self.snapshots.push_front(head);
+        self.trim();
+    }
+
+    /// Drop the oldest snapshots until the history is within its budget and
+    /// capacity.
+    fn trim(&mut self) {
+        while self.bytes > self.budget || self.snapshots.len() > self.capacity {
+            match self.snapshots.pop_back() {
+                Some(oldest) => self.bytes -= oldest.len(),
+                None => break,
+            }
+        }
+    }
+
+    /// Decode up to `count` snapshots, newest first.
+    pub(crate) fn recent(&self, count: usize) -> Vec<Vec<u8>> {
+        let mut recent: Vec<Vec<u8>> = Vec::new();
+        for encoded in self.snapshots.iter().take(count) {
+            let snapshot = match recent.last() {
+                Some(newer) => xor(newer, &decode(encoded)),
+                None => decode(encoded),
+            };
+            recent.push(snapshot);
+        }
+        recent
+    }
+
+    /// Remove and return the most recent snapshot.
+    pub(crate) fn pop(&mut self) -> Option<Vec<u8>> {
+        let head = self.snapshots.pop_front()?;
+        self.bytes -= head.len();
+        let head = decode(&head);
+        if let Some(next) = self.snapshots.front_mut() {
+            let full = xor(&decode(next), &head);
+            self.bytes -= next.len();
+            *next = encode(&full);
+            self.bytes += next.len();
+        }
+        Some(head)
+    }
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b.iter()).map(|(a, b)| a ^ b).collect()
+}
+
+/// Run-length encode bytes as pairs of a run length and the repeated byte.
+fn encode(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let value = bytes[i];
+        let mut run = 1;
+        while run < 255 && i + run < bytes.len() && bytes[i + run] == value {
+            run += 1;
+        }
+        encoded.push(run as u8);
+        encoded.push(value);
+        i += run;
+    }
+    encoded
+}
+
+fn decode(encoded: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for pair in encoded.chunks(2) {
+        bytes.extend(std::iter::repeat_n(pair[1], pair[0] as usize));
+    }
+    bytes
+}
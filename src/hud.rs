@@ -0,0 +1,115 @@
+use crate::{CellStorage, Universe};
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+/// How long the speed shown on the HUD is averaged over, in milliseconds.
+const SPEED_WINDOW_MS: f64 = 500.0;
+
+/// Options for drawing the universe onto a canvas with a `Renderer`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    /// Whether to draw the heads-up display at all.
+    pub hud: bool,
+    pub hud_generation: bool,
+    pub hud_population: bool,
+    /// Generations per second, measured between draws.
+    pub hud_speed: bool,
+    pub hud_rule: bool,
+    /// Height of the HUD text in pixels.
+    pub hud_font_size: u32,
+}
+
+#[wasm_bindgen]
+impl RenderOptions {
+    /// Options with everything on the HUD shown.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> RenderOptions {
+        RenderOptions {
+            hud: true,
+            hud_generation: true,
+            hud_population: true,
+            hud_speed: true,
+            hud_rule: true,
+            hud_font_size: 12,
+        }
+    }
+}
+
+/// Generations per second, measured over windows of `SPEED_WINDOW_MS`.
+#[derive(Default)]
+pub(crate) struct SpeedMeter {
+    /// Time and generation at the start of the current window.
+    start: Option<(f64, u64)>,
+    speed: f64,
+}
+
+impl SpeedMeter {
+    pub(crate) fn sample(&mut self, now: f64, generation: u64) -> f64 {
+        match self.start {
+            // Stepping back restarts the window.
+            Some((time, start_generation)) if generation >= start_generation => {
+                if now - time >= SPEED_WINDOW_MS {
+                    self.speed = (generation - start_generation) as f64 * 1000.0 / (now - time);
+                    self.start = Some((now, generation));
+                }
+            }
+            _ => self.start = Some((now, generation)),
+        }
+        self.speed
+    }
+}
+
+/// Draw the lines of the HUD in the top-left corner of a canvas, so a
+/// minimal page needs nothing but the canvas. Returns the width and height
+/// in pixels of the area drawn over.
+pub(crate) fn draw_hud(
+    ctx: &CanvasRenderingContext2d,
+    lines: &[String],
+    options: &RenderOptions,
+) -> Result<(f64, f64), JsValue> {
+    let size = options.hud_font_size as f64;
+    let line_height = size * 1.25;
+    ctx.set_font(&format!("{}px monospace", options.hud_font_size));
+    let width = lines
+        .iter()
+        .map(|line| ctx.measure_text(line).map(|metrics| metrics.width()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .fold(0.0, f64::max);
+    let (width, height) = (width + size, lines.len() as f64 * line_height + size / 2.0);
+    // A translucent backing keeps the text readable over live cells.
+    ctx.set_fill_style_str("rgba(0, 0, 0, 0.6)");
+    ctx.fill_rect(0.0, 0.0, width, height);
+    ctx.set_fill_style_str("#ffffff");
+    for (i, line) in lines.iter().enumerate() {
+        ctx.fill_text(
+            line,
+            size / 2.0,
+            size / 2.0 + (i as f64 + 0.8) * line_height,
+        )?;
+    }
+    Ok((width, height))
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// The lines of text shown on the HUD, given the measured speed.
+    pub fn hud_text(&self, options: &RenderOptions, speed: f64) -> Vec<String> {
+        let mut lines = Vec::new();
+        if options.hud_generation {
+            lines.push(format!("Generation {}", self.generation));
+        }
+        if options.hud_population {
+            lines.push(format!("Population {}", self.cells.population()));
+        }
+        if options.hud_speed {
+            lines.push(format!("Speed {:.1} gen/s", speed));
+        }
+        if options.hud_rule {
+            lines.push(format!("Rule {}", self.rule_string()));
+        }
+        lines
+    }
+}
@@ -0,0 +1,184 @@
+//! The JSON interchange format, a stable way for other tools to save and
+//! load universes. It looks like this:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "rule": "B3/S23",
+//!   "topology": { "top_bottom": "wrap", "left_right": "absorbing" },
+//!   "width": 64,
+//!   "height": 48,
+//!   "cells": "x = 64, y = 48, rule = B3/S23\nbo$2bo$3o!\n",
+//!   "generation": 120,
+//!   "seed": 42
+//! }
+//! ```
+//!
+//! - `version` is the version of the format, which is raised whenever the
+//!   meaning of a field changes. Fields may be added without raising it, so
+//!   readers should ignore fields they don't know.
+//! - `rule` is the rule in B/S notation.
+//! - `topology` says what lies beyond each pair of opposite edges: `wrap`,
+//!   `absorbing` or `reflecting`.
+//! - `cells` are the live cells in RLE, with the top-left corner of the
+//!   pattern at the top-left corner of the universe.
+//! - `generation` is the number of ticks since the universe was created.
+//! - `seed` is the seed of the random number generator used by features
+//!   such as `spray`.
+//!
+//! The same fields can be written as MessagePack, with `export_msgpack`,
+//! for smaller payloads.
+
+use crate::errors;
+use crate::pattern::{self, Pattern, PatternFit, RuleMismatch};
+use crate::rule::Rule;
+use crate::versioning::{self, Format};
+use crate::{Topology, Universe, UniverseError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    version: u64,
+    /// Fields which tools might reasonably leave out have defaults.
+    #[serde(default = "default_rule")]
+    rule: String,
+    #[serde(default)]
+    topology: Topology,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    cells: String,
+    #[serde(default)]
+    generation: u64,
+    #[serde(default)]
+    seed: u64,
+}
+
+fn default_rule() -> String {
+    "B3/S23".to_string()
+}
+
+impl Universe {
+    fn state(&self) -> State {
+        let rule = self.rule_string();
+        let cells = Pattern::with_size(
+            self.width,
+            self.height,
+            self.live_cells(),
+            Some(rule.clone()),
+        );
+        State {
+            version: Format::State.current(),
+            rule,
+            topology: Topology {
+                top_bottom: self.row_boundary,
+                left_right: self.col_boundary,
+            },
+            width: self.width,
+            height: self.height,
+            cells: cells.to_rle(),
+            generation: self.generation,
+            seed: self.seed,
+        }
+    }
+
+    /// Replace the universe with a decoded document, migrating it from the
+    /// version it was saved with, or leave it alone if it can't be loaded,
+    /// including if it has no cells or more than `MAX_CELLS`.
+    fn load_state(
+        &mut self,
+        method: &'static str,
+        args: String,
+        document: Value,
+    ) -> Result<(), UniverseError> {
+        let state: State = versioning::unwrap(Format::State, document)
+            .map_err(|err| self.error(method, args.clone(), err))?;
+        let rule = match Rule::parse(&state.rule).filter(|rule| rule.is_supported()) {
+            Some(rule) => rule,
+            None => {
+                let message = format!("the rule {} is not supported", state.rule);
+                return Err(self.error(method, args, message));
+            }
+        };
+        if let Err(err) = errors::grid_size(&[state.width, state.height]) {
+            return Err(self.error(method, args, err));
+        }
+        let cells = pattern::parse_pattern(&state.cells)
+            .map_err(|err| self.error(method, args, format!("invalid cells: {}", err)))?;
+
+        self.set_width(state.width);
+        self.set_height(state.height);
+        self.set_boundary(state.topology.top_bottom, state.topology.left_right);
+        self.apply_rule(rule);
+        self.place_pattern(&cells, 0, 0, PatternFit::Crop, RuleMismatch::Ignore)?;
+        self.initial_cells = self.cells.clone();
+        self.generation = state.generation;
+        self.set_seed(state.seed);
+        Ok(())
+    }
+
+    fn load_json(&mut self, method: &'static str, json: &str) -> Result<(), UniverseError> {
+        trace_span!("deserialize");
+        let args = format!("{} bytes of JSON", json.len());
+        match serde_json::from_str::<Value>(json) {
+            Ok(state) => self.load_state(method, args, state),
+            Err(err) => Err(self.error(method, args, err.to_string())),
+        }
+    }
+
+    fn load_msgpack(&mut self, method: &'static str, bytes: &[u8]) -> Result<(), UniverseError> {
+        trace_span!("deserialize");
+        let args = format!("{} bytes of MessagePack", bytes.len());
+        match rmp_serde::from_slice::<Value>(bytes) {
+            Ok(state) => self.load_state(method, args, state),
+            Err(err) => Err(self.error(method, args, err.to_string())),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// The universe in the JSON interchange format, described in the
+    /// `interchange` module.
+    pub fn export_json(&self) -> String {
+        trace_span!("serialize");
+        serde_json::to_string(&self.state()).unwrap()
+    }
+
+    /// Replace the universe with one from the JSON interchange format,
+    /// resizing it to match. Nothing changes if the JSON can't be loaded.
+    pub fn import_json(&mut self, json: &str) -> Result<(), UniverseError> {
+        self.load_json("import_json", json)
+    }
+
+    /// Create a universe from the JSON interchange format, such as one saved
+    /// to local storage with `export_json`.
+    pub fn from_json(json: &str) -> Result<Universe, UniverseError> {
+        let mut universe = Universe::empty(0, 0);
+        universe.load_json("from_json", json)?;
+        Ok(universe)
+    }
+
+    /// The universe in MessagePack, with the same fields as the JSON
+    /// interchange format but a much smaller payload, for syncing over the
+    /// network or storing in IndexedDB.
+    pub fn export_msgpack(&self) -> Vec<u8> {
+        trace_span!("serialize");
+        rmp_serde::to_vec_named(&self.state()).unwrap()
+    }
+
+    /// Replace the universe with one from `export_msgpack`, resizing it to
+    /// match. Nothing changes if the bytes can't be loaded.
+    pub fn import_msgpack(&mut self, bytes: &[u8]) -> Result<(), UniverseError> {
+        self.load_msgpack("import_msgpack", bytes)
+    }
+
+    /// Create a universe from `export_msgpack`.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Universe, UniverseError> {
+        let mut universe = Universe::empty(0, 0);
+        universe.load_msgpack("from_msgpack", bytes)?;
+        Ok(universe)
+    }
+}
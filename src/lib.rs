@@ -1,55 +1,408 @@
-mod utils;
-
-use fixedbitset::FixedBitSet;
-use js_sys;
-use wasm_bindgen::prelude::*;
-use web_sys;
+// The macros are defined before the modules so that they can use them.
 
+/// Write a message to the console at a `LogLevel`, if that level is enabled.
 macro_rules! log {
-    ( $( $t:tt )* ) => {
-        web_sys::console::log_1(&format!( $( $t )* ).into());
+    ( $level:expr, $( $t:tt )* ) => {
+        if $crate::logging::enabled($level) {
+            $crate::logging::write($level, &format!( $( $t )* ));
+        }
     }
 }
 
+/// Enter a `tracing` span lasting until the end of the enclosing block, when
+/// the `tracing` feature is enabled.
+macro_rules! trace_span {
+    ( $name:expr ) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name).entered();
+    };
+}
+
+/// Give a type with an `rng` field a `set_seed` method for JavaScript, with
+/// the doc comment written before the type, which seeds the generator so
+/// that the same seed gives the same results.
+macro_rules! seedable {
+    ( $( #[$attr:meta] )* $ty:ident ) => {
+        #[wasm_bindgen]
+        impl $ty {
+            $( #[$attr] )*
+            pub fn set_seed(&mut self, seed: u64) {
+                self.rng = $crate::rng::Rng::new(seed);
+            }
+        }
+    };
+}
+
+mod ages;
+mod analysis;
+mod benchmark;
+mod boundary;
+mod budget;
+mod catalogue;
+mod changed;
+mod components;
+mod config;
+mod cursor;
+mod density_field;
+mod drift;
+mod duel;
+mod errors;
+mod extrusion;
+mod follow;
+mod gliders;
+mod goals;
+mod gray_scott;
+mod history;
+mod hud;
+mod interchange;
+mod life3d;
+mod lifetimes;
+mod light_cone;
+mod logging;
+mod markers;
+mod memory;
+mod minimap;
+mod names;
+mod neighbors;
+mod onion_skin;
+mod oplog;
+mod paint;
+mod pathfinding;
+mod pattern;
+mod period;
+mod probability;
+mod puzzle;
+mod records;
+mod renderer;
+mod resources;
+mod rng;
+mod rule;
+mod rule_callback;
+mod rule_change;
+mod sand;
+mod scenario;
+mod selection;
+mod shared_frame;
+mod sources;
+mod sparks;
+mod sparse;
+mod stamps;
+mod statistics;
+mod storage;
+mod summary;
+mod temperature;
+mod time_zones;
+mod timing;
+mod unbounded;
+mod utils;
+mod validate;
+mod versioning;
+mod walls;
+
+pub use analysis::{ObjectAnalysis, ObjectKind, SweepReport, SymmetryReport};
+pub use benchmark::{run_benchmark_suite, BenchmarkReport, BenchmarkResult};
+pub use boundary::{Boundary, Topology};
+pub use budget::BudgetWarning;
+pub use catalogue::{catalogue_names, catalogue_pattern};
+pub use config::UniverseConfig;
+pub use duel::DuelPhase;
+pub use errors::UniverseError;
+pub use extrusion::HeightSource;
+pub use goals::GoalEvent;
+pub use gray_scott::GrayScott;
+pub use hud::RenderOptions;
+pub use life3d::Universe3D;
+#[cfg(feature = "tracing")]
+pub use logging::init_tracing;
+pub use logging::{log_level, set_log_level, LogLevel};
+pub use markers::{Marker, MarkerKind};
+pub use memory::MemoryReport;
+pub use names::{
+    boundary_from_string, boundary_names, boundary_to_string, brush_shape_from_string,
+    brush_shape_names, brush_shape_to_string, height_source_from_string, height_source_names,
+    height_source_to_string, log_level_from_string, log_level_names, log_level_to_string,
+    material_from_string, material_names, material_to_string, mirror_mode_from_string,
+    mirror_mode_names, mirror_mode_to_string, pattern_fit_from_string, pattern_fit_names,
+    pattern_fit_to_string, rule_mismatch_from_string, rule_mismatch_names, rule_mismatch_to_string,
+    topology_from_string, topology_to_string,
+};
+pub use paint::{BrushShape, MirrorMode};
+pub use pattern::{parse_pattern, pattern_from_msgpack, Pattern, PatternFit, RuleMismatch};
+pub use probability::ProbabilityUniverse;
+pub use puzzle::{Puzzle, PuzzleStatus};
+pub use records::RunRecords;
+pub use renderer::Renderer;
+pub use rule::{normalize_rule, rule_names};
+pub use sand::{FallingSand, Material};
+pub use scenario::{Scenario, ScenarioEvent, ScenarioStatus};
+pub use sources::Anchor;
+pub use stamps::PlacementGuide;
+pub use storage::{BitCells, CellStorage, Cells};
+pub use timing::TimingStats;
+pub use unbounded::SparseUniverse;
+
+use fixedbitset::FixedBitSet;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+/// Which part of the universe stays in place when it is resized.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeAnchor {
+    /// Rows and columns are added or removed at the bottom and right.
+    TopLeft,
+    /// Rows and columns are added or removed evenly around the edges, with
+    /// any odd one at the bottom or right.
+    Centre,
+}
+
+/// The state of a single cell.
+///
+/// Two state rules only use `DEAD` and `ALIVE`, while multi-state rules can
+/// use up to 256 states with any state other than `DEAD` counting as alive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cell(pub u8);
+
+impl Cell {
+    pub const DEAD: Cell = Cell(0);
+    pub const ALIVE: Cell = Cell(1);
+
+    pub fn state(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_alive(self) -> bool {
+        self != Cell::DEAD
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(alive: bool) -> Cell {
+        if alive {
+            Cell::ALIVE
+        } else {
+            Cell::DEAD
+        }
+    }
+}
+
+/// What changed in the universe during a tick.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TickStats {
+    pub(crate) population: usize,
+    pub(crate) births: usize,
+    pub(crate) deaths: usize,
+    /// Bounding box of the cells which changed as
+    /// `(min_row, min_col, max_row, max_col)`.
+    pub(crate) dirty: Option<(u32, u32, u32, u32)>,
+    /// The `(row, col, born)` of each change in the order they were made. A
+    /// cell can change more than once, such as when it is born and then
+    /// killed by its age limit.
+    pub(crate) changes: Vec<(u32, u32, bool)>,
+}
+
+impl TickStats {
+    /// Reset to no changes, keeping the buffer of changes.
+    fn clear(&mut self) {
+        self.population = 0;
+        self.births = 0;
+        self.deaths = 0;
+        self.dirty = None;
+        self.changes.clear();
+    }
+
+    pub(crate) fn record_change(&mut self, row: u32, col: u32, born: bool) {
+        if born {
+            self.births += 1;
+        } else {
+            self.deaths += 1;
+        }
+        self.changes.push((row, col, born));
+        self.dirty = Some(match self.dirty {
+            Some((min_row, min_col, max_row, max_col)) => (
+                min_row.min(row),
+                min_col.min(col),
+                max_row.max(row),
+                max_col.max(col),
+            ),
+            None => (row, col, row, col),
+        });
+    }
+}
+
 #[wasm_bindgen]
 pub struct Universe {
     width: u32,
     height: u32,
-    cells: FixedBitSet,
-    initial_cells: FixedBitSet,
+    /// Which neighbour counts give births and survivals.
+    rule: rule::Rule,
+    /// Number of ticks since the universe was created.
+    generation: u64,
+    /// The cells of the universe surrounded by a one cell border, or halo.
+    ///
+    /// The halo is filled according to the boundaries while ticking so that
+    /// neighbours can be counted without wrapping indices, and is dead at all
+    /// other times.
+    cells: Cells,
+    /// Cells which never change, if any have been placed.
+    walls: Option<walls::Walls>,
+    /// An `Anchor` for each cell, laid out in the same way as `cells`, if
+    /// any sources or sinks have been placed.
+    anchors: Option<Vec<u8>>,
+    /// What lies beyond the top and bottom edges.
+    row_boundary: Boundary,
+    /// What lies beyond the left and right edges.
+    col_boundary: Boundary,
+    initial_cells: Cells,
+    glider_detector: Option<gliders::GliderDetector>,
+    /// Goals checked after each tick.
+    goals: goals::Goals,
+    light_cone: Option<light_cone::LightCone>,
+    history: Option<history::History>,
+    summary: Option<summary::Summary>,
+    brush: paint::Brush,
+    mirror: MirrorMode,
+    /// The `(x, y, w, h)` of the cells being shown by the frontend.
+    viewport: Option<(u32, u32, u32, u32)>,
+    /// The `(row, col)` of the cell under the keyboard editing cursor.
+    cursor: (u32, u32),
+    /// Cells lifted out of the universe by `grab_object`.
+    selection: Option<selection::Selection>,
+    /// Patterns saved by the user, by name.
+    patterns: BTreeMap<String, Pattern>,
+    /// Cells inside the polygon chosen by `select_polygon`.
+    region: Option<Vec<(u32, u32)>>,
+    /// Cells of the path found by `find_path`.
+    path: Option<Vec<(u32, u32)>>,
+    /// Cells changed by the last call to `tick_many`, as `row * width +
+    /// col`.
+    changed: Vec<u32>,
+    /// The `cells_version` before the call to `tick_many` which made
+    /// `changed`.
+    changed_from: u64,
+    /// Counts the changes to the cells: each call to `tick_many` adds one, as
+    /// does each edit or move through history, so that renderers can tell
+    /// whether what they drew is still current.
+    cells_version: u64,
+    /// Number of births and deaths during the latest tick.
+    last_births: u32,
+    last_deaths: u32,
+    /// Annotations placed by the user.
+    markers: Vec<markers::Marker>,
+    /// Number of ticks a cell can survive for, if limited.
+    max_age: Option<u32>,
+    /// Number of ticks each cell has been alive, or dead, for, laid out in
+    /// the same way as `cells`, while ages are needed for `max_age` or are
+    /// being tracked.
+    ages: Option<Vec<u32>>,
+    age_tracking: bool,
+    /// The most ages given by `ages_as_bytes` go up to.
+    age_cap: u8,
+    resources: Option<resources::Resources>,
+    /// Number of times each cell has changed, laid out in the same way as
+    /// `cells`, while activity is being tracked.
+    activity: Option<Vec<u32>>,
+    /// How long cells have lived for, while lifetimes are being tracked.
+    lifetimes: Option<lifetimes::Lifetimes>,
+    /// Records such as the peak population, while they are being kept.
+    records: Option<records::Records>,
+    /// Hashes of recent generations, while periods are being detected.
+    period: Option<period::PeriodDetector>,
+    /// Buffer for `neighbor_counts`, kept to avoid reallocating it.
+    neighbor_counts: Option<Vec<u8>>,
+    /// A JavaScript function which replaces the rule while ticking.
+    rule_callback: Option<rule_callback::RuleCallback>,
+    /// Spare cell buffer which each dense tick writes the next generation
+    /// into before swapping it with `cells`, so ticking doesn't allocate.
+    back_cells: Cells,
+    /// Buffers reused from tick to tick by `step_dense`.
+    column_sums: Vec<u8>,
+    spare_stats: TickStats,
+    /// Object ids from the last call to `component_ids`.
+    components: Option<components::Components>,
+    /// Regions which tick more slowly than the rest of the universe.
+    time_zones: Vec<time_zones::TimeZone>,
+    /// Probability of each cell flipping after every tick.
+    temperature: f64,
+    /// Random number generator for features which need to be repeatable.
+    rng: rng::Rng,
+    /// The seed last given to `rng`.
+    seed: u64,
+    drift: drift::Drift,
+    duel: Option<duel::Duel>,
+    /// Number of generations advanced by each call to `tick_frame`.
+    frame_generations: u32,
+    /// The fraction of a generation carried over between calls to
+    /// `advance`.
+    pending_generations: f64,
+    budget: budget::BudgetGuard,
+    /// Durations of recent ticks.
+    tick_timer: timing::TickTimer,
+    /// Births and deaths from the latest tick while spark mode is on.
+    sparks: Option<sparks::Sparks>,
+    /// What the moving frame keeps centred, if anything.
+    follow: Option<follow::Follow>,
+    /// Total `(rows, cols)` the moving frame has shifted the cells by.
+    frame_offset: (i32, i32),
+    /// Called when wasm memory grows, if set.
+    memory_listener: Option<js_sys::Function>,
+    /// Size of wasm memory when growth was last checked for.
+    memory_size: usize,
+    /// Frame written after ticking for renderers in other threads.
+    shared_frame: Option<shared_frame::SharedFrame>,
+    /// Edits shared with other clients editing the same board.
+    op_log: Option<oplog::OpLog>,
+    /// Whether the universe is sparse enough to be ticked by only visiting
+    /// live cells and their neighbours.
+    sparse: bool,
 }
 
 #[wasm_bindgen]
 impl Universe {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Universe {
         utils::set_panic_hook();
-        log!("Universe::new()");
-        let width = 100;
-        let height = 100;
-        let cells = Self::random_symmetric(height, width);
-        Self {
-            width,
-            height,
-            initial_cells: cells.clone(),
-            cells,
-        }
+        log!(LogLevel::Debug, "Universe::new()");
+        let mut universe = Self::empty(100, 100);
+        universe.cells =
+            universe.cells_from_bits(&Self::random_symmetric(universe.height, universe.width));
+        universe.initial_cells = universe.cells.clone();
+        universe
     }
 
     pub fn randomise(&mut self) {
-        self.cells = Self::random_symmetric(self.height, self.width);
+        self.cells = self.cells_from_bits(&Self::random_symmetric(self.height, self.width));
+        self.initial_cells = self.cells.clone();
+        self.cells_edited();
+    }
+
+    /// Make each cell alive with a probability of `density`, killing the
+    /// rest, choosing with a random number generator seeded with `seed` so
+    /// the same seed always gives the same cells. Also reseeds the
+    /// generator as `set_seed` does.
+    pub fn randomise_with_seed(&mut self, seed: u64, density: f64) {
+        self.set_seed(seed);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let alive = self.rng.next_f64() < density;
+                self.cells.set(idx, alive.into());
+            }
+        }
         self.initial_cells = self.cells.clone();
+        self.cells_edited();
     }
 
     pub fn clear(&mut self) {
-        let size = (self.width * self.height) as usize;
-        self.cells = FixedBitSet::with_capacity(size);
+        self.cells = self.dead_cells();
         self.initial_cells = self.cells.clone();
+        self.cells_edited();
     }
 
     #[allow(dead_code)]
@@ -125,61 +478,364 @@ impl Universe {
         self.height
     }
 
+    /// Number of ticks since the universe was created or loaded, less any
+    /// stepped back.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Set the width of the universe.
     ///
-    /// Resets all cells to the dead state.
+    /// Resets all cells to the dead state. Use `set_width_keeping_cells` to
+    /// keep them instead.
     pub fn set_width(&mut self, width: u32) {
-        self.width = width;
-        self.cells = FixedBitSet::with_capacity((width * self.height) as usize);
+        self.reset_size(width, self.height);
     }
 
     /// Set the height of the universe.
     ///
-    /// Resets all cells to the dead state.
+    /// Resets all cells to the dead state. Use `set_height_keeping_cells` to
+    /// keep them instead.
     pub fn set_height(&mut self, height: u32) {
-        self.height = height;
-        self.cells = FixedBitSet::with_capacity((self.width * height) as usize);
+        self.reset_size(self.width, height);
     }
 
-    pub fn cells(&self) -> *const u32 {
-        self.cells.as_slice().as_ptr()
+    /// Set the width of the universe, keeping the cells which still fit.
+    /// Cells beyond the new right edge are cropped, and growing adds dead
+    /// cells on the right. Fails if the universe would have no cells or more
+    /// than `MAX_CELLS`.
+    pub fn set_width_keeping_cells(&mut self, width: u32) -> Result<(), UniverseError> {
+        if let Err(err) = errors::grid_size(&[width, self.height]) {
+            let args = format!("width={}", width);
+            return Err(self.error("set_width_keeping_cells", args, err));
+        }
+        let kept = self.cells_within(width, self.height);
+        self.set_width(width);
+        self.restore_cells(&kept);
+        Ok(())
     }
 
-    pub fn initial_cells(&self) -> *const u32 {
-        self.initial_cells.as_slice().as_ptr()
+    /// Set the height of the universe, keeping the cells which still fit.
+    /// Cells beyond the new bottom edge are cropped, and growing adds dead
+    /// cells at the bottom. Fails if the universe would have no cells or
+    /// more than `MAX_CELLS`.
+    pub fn set_height_keeping_cells(&mut self, height: u32) -> Result<(), UniverseError> {
+        if let Err(err) = errors::grid_size(&[self.width, height]) {
+            let args = format!("height={}", height);
+            return Err(self.error("set_height_keeping_cells", args, err));
+        }
+        let kept = self.cells_within(self.width, height);
+        self.set_height(height);
+        self.restore_cells(&kept);
+        Ok(())
     }
 
-    pub fn set_state(&mut self, ptr: *const u8, buf_length: usize) {
-        let slice = unsafe { std::slice::from_raw_parts(ptr, buf_length) };
-        let size = (self.width * self.height) as usize;
-        let mut cells = FixedBitSet::with_capacity(size);
-        for i in 0..size {
-            let byte = i / 8;
-            let mask = 1 << (i % 8);
-            cells.set(i, (slice[byte] & mask) == mask);
+    /// Resize the universe, keeping the cells in the part of the old and new
+    /// universes which overlap when lined up by `anchor`, in both the
+    /// current and initial cells. Growing adds dead cells and shrinking
+    /// crops. Anything else tied to the size, such as walls and history, is
+    /// reset as with `set_width`. Fails if the universe would have no cells
+    /// or more than `MAX_CELLS`.
+    pub fn resize(
+        &mut self,
+        width: u32,
+        height: u32,
+        anchor: ResizeAnchor,
+    ) -> Result<(), UniverseError> {
+        if let Err(err) = errors::grid_size(&[width, height]) {
+            let args = format!("width={}, height={}, anchor={:?}", width, height, anchor);
+            return Err(self.error("resize", args, err));
+        }
+        // How far each cell moves.
+        let shift = match anchor {
+            ResizeAnchor::TopLeft => (0, 0),
+            ResizeAnchor::Centre => (
+                (height as i64 - self.height as i64) / 2,
+                (width as i64 - self.width as i64) / 2,
+            ),
+        };
+        let kept = self.cells_moved(&self.cells, width, height, shift);
+        let kept_initial = self.cells_moved(&self.initial_cells, width, height, shift);
+        self.reset_size(width, height);
+        self.restore_cells(&kept);
+        for &(row, col, cell) in kept_initial.iter() {
+            let idx = self.get_index(row, col);
+            self.initial_cells.set(idx, cell);
         }
-        self.cells = cells;
+        Ok(())
     }
 
-    fn get_index(&self, row: u32, column: u32) -> usize {
-        (row * self.width + column) as usize
+    /// Resize the universe, stretching or squeezing the cells to fit so
+    /// that small sketches can be blown up for longer runs. Each new cell
+    /// copies the old cell nearest its centre, or with `majority` is alive
+    /// when at least half of the old cells it covers are alive, which keeps
+    /// more of the shape when shrinking. Fails if the universe would have no
+    /// cells or more than `MAX_CELLS`.
+    pub fn rescale(
+        &mut self,
+        width: u32,
+        height: u32,
+        majority: bool,
+    ) -> Result<(), UniverseError> {
+        if let Err(err) = errors::grid_size(&[width, height]) {
+            let args = format!("width={}, height={}", width, height);
+            return Err(self.error("rescale", args, err));
+        }
+        // The old cells covered by each new row or column.
+        let spans = |new: u32, old: u32| -> Vec<(u32, u32)> {
+            (0..new)
+                .map(|i| {
+                    let start = (i as u64 * old as u64 / new as u64) as u32;
+                    let end = ((i as u64 + 1) * old as u64 / new as u64) as u32;
+                    (start, end.max(start + 1))
+                })
+                .collect()
+        };
+        let (rows, cols) = (spans(height, self.height), spans(width, self.width));
+        let mut kept = Vec::new();
+        for (row, &(top, bottom)) in rows.iter().enumerate() {
+            for (col, &(left, right)) in cols.iter().enumerate() {
+                let cell = if majority {
+                    let covered = (bottom - top) * (right - left);
+                    let alive = self.population_in(left, top, right - left, bottom - top);
+                    Cell::from(2 * alive >= covered)
+                } else {
+                    let centre = self.get_index((top + bottom - 1) / 2, (left + right - 1) / 2);
+                    self.cells.get(centre)
+                };
+                if cell.is_alive() {
+                    kept.push((row as u32, col as u32, cell));
+                }
+            }
+        }
+        self.reset_size(width, height);
+        self.restore_cells(&kept);
+        Ok(())
     }
 
-    fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
-        let mut count = 0;
-        for delta_row in [self.height - 1, 0, 1].iter().cloned() {
-            for delta_col in [self.width - 1, 0, 1].iter().cloned() {
-                if delta_row == 0 && delta_col == 0 {
-                    continue;
+    /// Pointer to the cells in wasm memory, laid out as described by
+    /// `row_stride_bits` and `cells_offset_bits`.
+    #[deprecated(note = "use `cells_as_bytes`, which copies the cells without pointer arithmetic")]
+    // For the binding generated by `wasm_bindgen`, which calls this.
+    #[allow(deprecated)]
+    pub fn cells(&self) -> *const u8 {
+        self.cells.as_bytes().as_ptr()
+    }
+
+    #[deprecated(note = "use `initial_cells_as_bytes`")]
+    #[allow(deprecated)]
+    pub fn initial_cells(&self) -> *const u8 {
+        self.initial_cells.as_bytes().as_ptr()
+    }
+
+    /// Number of states a cell can be in under the active rule.
+    pub fn state_count(&self) -> u32 {
+        self.rule.states()
+    }
+
+    /// Number of live cells in the `w` by `h` rectangle with its top-left
+    /// corner at column `x` and row `y`, clipped to the universe. Each row of
+    /// the rectangle is counted a word of cells at a time.
+    pub fn population_in(&self, x: u32, y: u32, w: u32, h: u32) -> u32 {
+        let right = x.saturating_add(w).min(self.width);
+        let bottom = y.saturating_add(h).min(self.height);
+        if x >= right {
+            return 0;
+        }
+        (y..bottom)
+            .map(|row| {
+                let start = self.get_index(row, x);
+                self.cells
+                    .population_between(start, start + (right - x) as usize) as u32
+            })
+            .sum()
+    }
+
+    /// Get the state of a cell, where 0 is dead.
+    pub fn get_cell_state(&self, row: u32, column: u32) -> Result<u8, UniverseError> {
+        let idx = self.checked_index("get_cell_state", row, column)?;
+        Ok(self.cells.get(idx).state())
+    }
+
+    /// Set the state of a cell, where 0 is dead.
+    ///
+    /// Under two state rules any state other than 0 is stored as alive.
+    pub fn set_cell_state(
+        &mut self,
+        row: u32,
+        column: u32,
+        state: u8,
+    ) -> Result<(), UniverseError> {
+        let idx = self.checked_index("set_cell_state", row, column)?;
+        self.cells.set(idx, Cell(state));
+        self.cells_edited();
+        Ok(())
+    }
+
+    /// Number of bits each cell occupies in the `cells` buffer: 1 for two
+    /// state rules, or 8 for multi-state rules.
+    pub fn bits_per_cell(&self) -> u32 {
+        self.cells.bits_per_cell()
+    }
+
+    /// The cells packed row by row with no halo, using one bit per cell for
+    /// two state rules or one byte per cell for multi-state rules.
+    ///
+    /// Bits are packed starting from the least significant bit of each byte.
+    pub fn cells_as_bytes(&self) -> Vec<u8> {
+        self.pack_cells(&self.cells)
+    }
+
+    /// The cells in the `w` by `h` rectangle with its top-left corner at
+    /// column `x` and row `y`, packed row by row in the same way as
+    /// `cells_as_bytes`. The rectangle must lie within the universe.
+    pub fn get_region(&self, x: u32, y: u32, w: u32, h: u32) -> Result<Vec<u8>, UniverseError> {
+        self.check_rect("get_region", x, y, w, h)?;
+        let bits_per_cell = self.cells.bits_per_cell() as usize;
+        let mut bytes = vec![0; (w as usize * h as usize * bits_per_cell).div_ceil(8)];
+        for row in 0..h {
+            for col in 0..w {
+                let i = (row * w + col) as usize;
+                let state = self.cells.get(self.get_index(y + row, x + col)).state();
+                if bits_per_cell == 8 {
+                    bytes[i] = state;
+                } else if state != 0 {
+                    bytes[i / 8] |= 1 << (i % 8);
                 }
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Set the cells in the `w` by `h` rectangle with its top-left corner at
+    /// column `x` and row `y` from bytes packed like those from
+    /// `get_region`. The rectangle must lie within the universe and the
+    /// bytes must cover it exactly. With a byte per cell, each must be one of
+    /// the rule's states.
+    pub fn set_region(
+        &mut self,
+        x: u32,
+        y: u32,
+        w: u32,
+        h: u32,
+        bytes: &[u8],
+    ) -> Result<(), UniverseError> {
+        self.unpack_region("set_region", (x, y, w, h), bytes)
+    }
 
-                let neighbor_row = (row + delta_row) % self.height;
-                let neighbor_col = (column + delta_col) % self.width;
-                let idx = self.get_index(neighbor_row, neighbor_col);
-                count += self.cells[idx] as u8;
+    /// Replace every cell from bytes packed like those from
+    /// `cells_as_bytes`, such as a `Uint8Array`. The bytes must cover the
+    /// universe exactly and, with a byte per cell, each must be one of the
+    /// rule's states. The universe is left alone if they aren't.
+    pub fn set_cells_from_bytes(&mut self, bytes: &[u8]) -> Result<(), UniverseError> {
+        self.unpack_region(
+            "set_cells_from_bytes",
+            (0, 0, self.width, self.height),
+            bytes,
+        )
+    }
+
+    /// The initial cells packed in the same way as `cells_as_bytes`.
+    pub fn initial_cells_as_bytes(&self) -> Vec<u8> {
+        self.pack_cells(&self.initial_cells)
+    }
+
+    /// Length in bytes of the `cells` and `initial_cells` buffers.
+    pub fn cells_byte_len(&self) -> usize {
+        self.cells.as_bytes().len()
+    }
+
+    /// Size in bytes of the words making up the `cells` buffer. The buffer
+    /// is always a whole number of words long.
+    pub fn cells_word_size(&self) -> u32 {
+        self.cells.word_size()
+    }
+
+    /// Number of bits from the start of one row of cells to the start of the
+    /// next in the `cells` buffer.
+    ///
+    /// Each row is preceded and followed by a dead halo cell.
+    pub fn row_stride_bits(&self) -> u32 {
+        (self.width + 2) * self.cells.bits_per_cell()
+    }
+
+    /// Number of bits from the start of the `cells` buffer to the cell in
+    /// the first row and column, skipping over the halo.
+    pub fn cells_offset_bits(&self) -> u32 {
+        self.get_index(0, 0) as u32 * self.cells.bits_per_cell()
+    }
+
+    /// Replace every cell with a saved state, one bit per cell packed row by
+    /// row starting from the least significant bit of each byte, which is
+    /// how `cells_as_bytes` packs two state rules.
+    ///
+    /// Giving a `width` and `height` resizes the universe to fit the state
+    /// first. Either way the bytes must cover the universe exactly, and the
+    /// universe is left alone if they don't.
+    pub fn set_state(
+        &mut self,
+        bytes: &[u8],
+        width: Option<u32>,
+        height: Option<u32>,
+    ) -> Result<(), UniverseError> {
+        let args = || {
+            format!(
+                "{} bytes, width={:?}, height={:?}",
+                bytes.len(),
+                width,
+                height
+            )
+        };
+        let (width, height) = match (width, height) {
+            (None, None) => (self.width, self.height),
+            (Some(width), Some(height)) if width > 0 && height > 0 => (width, height),
+            (Some(_), Some(_)) => {
+                return Err(self.error(
+                    "set_state",
+                    args(),
+                    "the universe must have at least one cell",
+                ))
+            }
+            _ => {
+                return Err(self.error(
+                    "set_state",
+                    args(),
+                    "the width and height must be given together",
+                ))
             }
+        };
+        let expected = (width as usize * height as usize).div_ceil(8);
+        if bytes.len() != expected {
+            return Err(self.error(
+                "set_state",
+                args(),
+                format!("a {}x{} universe needs {} bytes", width, height, expected),
+            ));
         }
-        count
+        if width != self.width {
+            self.set_width(width);
+        }
+        if height != self.height {
+            self.set_height(height);
+        }
+        let size = (self.width * self.height) as usize;
+        let mut cells = FixedBitSet::with_capacity(size);
+        for i in 0..size {
+            cells.set(i, bytes[i / 8] & 1 << (i % 8) != 0);
+        }
+        self.cells = self.cells_from_bits(&cells);
+        self.cells_edited();
+        Ok(())
+    }
+
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        ((row + 1) * (self.width + 2) + column + 1) as usize
+    }
+
+    fn toggle_index(&mut self, idx: usize) {
+        let cell = (!self.cells.is_alive(idx)).into();
+        self.cells.set(idx, cell);
+        self.cells_edited();
     }
 
     pub fn tick(&mut self) {
@@ -187,42 +843,124 @@ impl Universe {
     }
 
     pub fn tick_many(&mut self, ticks: usize) {
+        self.resync_period();
+        self.changed.clear();
+        self.changed_from = self.cells_version;
         for _ in 0..ticks {
-            let mut next = self.cells.clone();
-            for row in 0..self.height {
-                for col in 0..self.width {
-                    let idx = self.get_index(row, col);
-                    let cell = self.cells[idx];
-                    let live_neighbors = self.live_neighbor_count(row, col);
-
-                    next.set(
-                        idx,
-                        match (cell, live_neighbors) {
-                            // Rule 1: Any live cell with fewer than two live neighbours
-                            // dies, as if caused by underpopulation.
-                            (true, x) if x < 2 => false,
-                            // Rule 2: Any live cell with two or three live neighbours
-                            // lives on to the next generation.
-                            (true, 2) | (true, 3) => true,
-                            // Rule 3: Any live cell with more than three live
-                            // neighbours dies, as if by overpopulation.
-                            (true, x) if x > 3 => false,
-                            // Rule 4: Any dead cell with exactly three live neighbours
-                            // becomes a live cell, as if by reproduction.
-                            (false, 3) => true,
-                            // All other cells remain in the same state.
-                            (otherwise, _) => otherwise,
-                        },
-                    );
+            trace_span!("tick");
+            let start = timing::now();
+            if self.history.is_some() {
+                let packed = self.cells_as_bytes();
+                if let Some(history) = &mut self.history {
+                    history.push(&packed);
                 }
             }
-            self.cells = next;
+            let walls = self.mark_walls();
+            let mut stats = if self.rule_callback.is_some() {
+                self.step_callback()
+            } else if self.sparse {
+                self.step_sparse()
+            } else {
+                self.step_dense()
+            };
+            self.unmark_walls();
+            stats.population -= walls;
+            self.update_ages(&mut stats);
+            self.update_resources();
+            self.apply_temperature(&mut stats);
+            self.apply_anchors(&mut stats);
+            self.update_sparks(&stats);
+            self.update_activity(&stats);
+            self.update_lifetimes(&stats, self.generation + 1);
+            self.generation += 1;
+            self.update_records();
+            self.update_backend(stats.population);
+            self.update_summary(&stats);
+            self.update_glider_detector();
+            self.update_goals(stats.population);
+            self.update_light_cone();
+            self.update_drift(&mut stats);
+            self.update_follow(&mut stats);
+            self.update_period(&stats);
+            self.update_shared_frame(&stats);
+            self.record_changed(&stats);
+            self.update_last_tick(&stats);
+            self.tick_timer.record(timing::now() - start);
+            self.spare_stats = stats;
+        }
+        self.settle_changed();
+        self.cells_version += 1;
+        self.write_shared_frame();
+        self.check_memory_growth();
+    }
+
+    /// Reseed the random number generator used by features such as
+    /// `spray`, so they behave the same way each time.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = rng::Rng::new(seed);
+        self.seed = seed;
+    }
+
+    /// Keep previous generations so they can be returned to with
+    /// `step_back`, using at most `bytes` bytes. A budget of 0 turns history
+    /// off.
+    pub fn set_history_budget(&mut self, bytes: usize) {
+        if bytes == 0 {
+            self.history = None;
+            return;
+        }
+        self.history
+            .get_or_insert_with(|| history::History::new(bytes))
+            .set_budget(bytes);
+    }
+
+    /// Keep up to `generations` previous generations so they can be
+    /// returned to with `step_back` and `rewind`, dropping the oldest once
+    /// there are more. This works alongside any byte budget from
+    /// `set_history_budget`. A capacity of 0 turns history off.
+    pub fn set_history_capacity(&mut self, generations: usize) {
+        if generations == 0 {
+            self.history = None;
+            return;
+        }
+        self.history
+            .get_or_insert_with(|| history::History::new(usize::MAX))
+            .set_capacity(generations);
+    }
+
+    /// Number of previous generations available to step back through.
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, |history| history.len())
+    }
+
+    /// Return to the generation before the latest tick, undoing any changes
+    /// made since then. Returns false if there is no history to go back to.
+    pub fn step_back(&mut self) -> bool {
+        match self.history.as_mut().and_then(|history| history.pop()) {
+            Some(packed) => {
+                self.cells = self.unpack_cells(&packed);
+                self.generation -= 1;
+                self.cells_edited();
+                true
+            }
+            None => false,
         }
     }
 
-    pub fn toggle_cell(&mut self, row: u32, column: u32) {
-        let idx = self.get_index(row, column);
-        self.cells.set(idx, !self.cells[idx]);
+    /// Step back up to `generations` generations, stopping early if the
+    /// history runs out. Returns the number of generations stepped back.
+    pub fn rewind(&mut self, generations: u32) -> u32 {
+        let mut stepped = 0;
+        while stepped < generations && self.step_back() {
+            stepped += 1;
+        }
+        stepped
+    }
+
+    pub fn toggle_cell(&mut self, row: u32, column: u32) -> Result<(), UniverseError> {
+        let idx = self.checked_index("toggle_cell", row, column)?;
+        self.toggle_index(idx);
+        Ok(())
     }
 
     pub fn add_glider(&mut self, row: u32, col: u32) {
@@ -235,6 +973,7 @@ impl Universe {
         ]);
     }
 
+    #[allow(clippy::identity_op)]
     pub fn add_pulsar(&mut self, row: u32, col: u32) {
         for (q_row, q_col) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
             self.set_cells(&[
@@ -256,20 +995,496 @@ impl Universe {
 }
 
 impl Universe {
+    /// Create a universe with every cell dead.
+    pub(crate) fn empty(width: u32, height: u32) -> Universe {
+        let states = 2;
+        let size = ((width + 2) * (height + 2)) as usize;
+        Self {
+            width,
+            height,
+            rule: rule::Rule::CONWAY,
+            generation: 0,
+            cells: Cells::with_states(states, size),
+            walls: None,
+            anchors: None,
+            row_boundary: Boundary::Wrap,
+            col_boundary: Boundary::Wrap,
+            initial_cells: Cells::with_states(states, size),
+            glider_detector: None,
+            goals: goals::Goals::default(),
+            light_cone: None,
+            history: None,
+            summary: None,
+            brush: paint::Brush::default(),
+            mirror: MirrorMode::Off,
+            viewport: None,
+            cursor: (0, 0),
+            selection: None,
+            region: None,
+            path: None,
+            changed: Vec::new(),
+            changed_from: 0,
+            cells_version: 0,
+            last_births: 0,
+            last_deaths: 0,
+            patterns: BTreeMap::new(),
+            markers: Vec::new(),
+            max_age: None,
+            ages: None,
+            age_tracking: false,
+            age_cap: u8::MAX,
+            resources: None,
+            activity: None,
+            lifetimes: None,
+            records: None,
+            period: None,
+            neighbor_counts: None,
+            rule_callback: None,
+            back_cells: Cells::with_states(states, size),
+            column_sums: Vec::new(),
+            spare_stats: TickStats::default(),
+            components: None,
+            time_zones: Vec::new(),
+            temperature: 0.0,
+            rng: rng::Rng::new(0),
+            seed: 0,
+            drift: drift::Drift::default(),
+            duel: None,
+            frame_generations: 1,
+            pending_generations: 0.0,
+            budget: budget::BudgetGuard::default(),
+            tick_timer: timing::TickTimer::default(),
+            sparks: None,
+            follow: None,
+            frame_offset: (0, 0),
+            memory_listener: None,
+            memory_size: 0,
+            shared_frame: None,
+            op_log: None,
+            sparse: false,
+        }
+    }
+
+    /// Set each cell to be alive with probability `density`.
+    pub(crate) fn fill_random(&mut self, rng: &mut rng::Rng, density: f64) {
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                self.cells.set(idx, (rng.next_f64() < density).into());
+            }
+        }
+    }
+
     /// Get the dead and alive values of the entire universe.
-    pub fn get_cells(&self) -> &fixedbitset::FixedBitSet {
+    pub fn get_cells(&self) -> &Cells {
         &self.cells
     }
 
+    /// Iterate over the `(row, col)` of each live cell in reading order,
+    /// skipping over whole words of dead cells at a time for two state rules.
+    pub fn iter_live_cells(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let stride = (self.width + 2) as usize;
+        self.cells
+            .iter_live_indices()
+            .map(move |idx| ((idx / stride) as u32, (idx % stride) as u32))
+            // Leave out the halo, which only holds cells during a tick.
+            .filter(move |&(row, col)| {
+                row >= 1 && row <= self.height && col >= 1 && col <= self.width
+            })
+            .map(|(row, col)| (row - 1, col - 1))
+    }
+
     /// Set cells to be alive in a universe by passing the row and column
-    /// of each cell as an array.
+    /// of each cell as an array. Cells beyond an edge wrap around to the
+    /// opposite edge if the edges are joined, and are left out otherwise.
     pub fn set_cells(&mut self, cells: &[(i32, i32)]) {
         for (row, col) in cells.iter().cloned() {
-            let idx = self.get_index(
-                row.rem_euclid(self.height as i32) as u32,
-                col.rem_euclid(self.width as i32) as u32,
-            );
-            self.cells.set(idx, true);
+            let row = self.row_boundary.place(row, self.height);
+            let col = self.col_boundary.place(col, self.width);
+            if let (Some(row), Some(col)) = (row, col) {
+                let idx = self.get_index(row, col);
+                self.cells.set(idx, Cell::ALIVE);
+            }
+        }
+        self.cells_edited();
+    }
+
+    /// The live cells in the top-left `width` by `height` cells.
+    fn cells_within(&self, width: u32, height: u32) -> Vec<(u32, u32, Cell)> {
+        let mut cells = Vec::new();
+        for row in 0..self.height.min(height) {
+            for col in 0..self.width.min(width) {
+                let cell = self.cells.get(self.get_index(row, col));
+                if cell.is_alive() {
+                    cells.push((row, col, cell));
+                }
+            }
+        }
+        cells
+    }
+
+    /// The live cells which are still within a `width` by `height` universe
+    /// after moving by `(rows, cols)`, at their new positions.
+    fn cells_moved(
+        &self,
+        cells: &Cells,
+        width: u32,
+        height: u32,
+        (d_row, d_col): (i64, i64),
+    ) -> Vec<(u32, u32, Cell)> {
+        let mut moved = Vec::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let cell = cells.get(self.get_index(row, col));
+                let (new_row, new_col) = (row as i64 + d_row, col as i64 + d_col);
+                if cell.is_alive()
+                    && (0..height as i64).contains(&new_row)
+                    && (0..width as i64).contains(&new_col)
+                {
+                    moved.push((new_row as u32, new_col as u32, cell));
+                }
+            }
+        }
+        moved
+    }
+
+    fn restore_cells(&mut self, cells: &[(u32, u32, Cell)]) {
+        for &(row, col, cell) in cells {
+            let idx = self.get_index(row, col);
+            self.cells.set(idx, cell);
+        }
+        self.cells_edited();
+    }
+
+    /// Check that a rectangle given to a method lies within the universe.
+    fn check_rect(
+        &self,
+        method: &'static str,
+        x: u32,
+        y: u32,
+        w: u32,
+        h: u32,
+    ) -> Result<(), UniverseError> {
+        if x.saturating_add(w) <= self.width && y.saturating_add(h) <= self.height {
+            Ok(())
+        } else {
+            Err(self.error(
+                method,
+                format!("x={}, y={}, w={}, h={}", x, y, w, h),
+                "the rectangle is outside the universe",
+            ))
+        }
+    }
+
+    /// Set the cells in a rectangle `(x, y, w, h)` from bytes packed like
+    /// those from `get_region`, for a method which takes them. Nothing is
+    /// set if the bytes don't fit the rectangle or hold a state the rule
+    /// doesn't have.
+    fn unpack_region(
+        &mut self,
+        method: &'static str,
+        (x, y, w, h): (u32, u32, u32, u32),
+        bytes: &[u8],
+    ) -> Result<(), UniverseError> {
+        self.check_rect(method, x, y, w, h)?;
+        let bits_per_cell = self.cells.bits_per_cell() as usize;
+        let expected = (w as usize * h as usize * bits_per_cell).div_ceil(8);
+        let args = || format!("x={}, y={}, w={}, h={}, {} bytes", x, y, w, h, bytes.len());
+        if bytes.len() != expected {
+            let message = format!("the rectangle needs {} bytes", expected);
+            return Err(self.error(method, args(), message));
+        }
+        if bits_per_cell == 8 {
+            if let Some(&state) = bytes
+                .iter()
+                .find(|&&state| state as u32 >= self.rule.states())
+            {
+                let message = format!(
+                    "state {} is beyond the {} states of the rule",
+                    state,
+                    self.rule.states()
+                );
+                return Err(self.error(method, args(), message));
+            }
+        }
+        for row in 0..h {
+            for col in 0..w {
+                let i = (row * w + col) as usize;
+                let state = if bits_per_cell == 8 {
+                    bytes[i]
+                } else {
+                    (bytes[i / 8] >> (i % 8)) & 1
+                };
+                let idx = self.get_index(y + row, x + col);
+                self.cells.set(idx, Cell(state));
+            }
+        }
+        self.cells_edited();
+        Ok(())
+    }
+
+    /// Set the size of the universe, resetting all cells to the dead state
+    /// along with everything else tied to the size, such as walls, overlays
+    /// and history.
+    fn reset_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.cells = self.dead_cells();
+        self.initial_cells = self.dead_cells();
+        self.cells_edited();
+        self.reset_walls();
+        self.anchors = None;
+        self.reset_ages();
+        self.resources = None;
+        if self.activity.is_some() {
+            self.activity = Some(vec![0; self.cells.len()]);
+        }
+        if self.lifetimes.is_some() {
+            self.lifetimes = Some(lifetimes::Lifetimes::new(self.cells.len()));
+        }
+        if let Some(records) = &mut self.records {
+            records.resize(self.cells.len());
+        }
+        self.light_cone = None;
+        self.selection = None;
+        self.region = None;
+        self.path = None;
+        self.changed.clear();
+        self.clear_sparks();
+        self.cursor = (0, 0);
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+        self.check_memory_growth();
+    }
+
+    /// A value for each cell of the universe, row by row without the halo,
+    /// from the cell's index.
+    pub(crate) fn each_cell<T>(&self, value: impl Fn(usize) -> T) -> Vec<T> {
+        let mut values = Vec::with_capacity(self.width as usize * self.height as usize);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                values.push(value(self.get_index(row, col)));
+            }
+        }
+        values
+    }
+
+    /// A buffer of dead cells sized for the universe, its halo and its rule.
+    fn dead_cells(&self) -> Cells {
+        let size = (self.width as usize + 2) * (self.height as usize + 2);
+        Cells::with_states(self.rule.states(), size)
+    }
+
+    /// Convert cells packed row by row with no halo into the universe's
+    /// layout.
+    fn cells_from_bits(&self, bits: &FixedBitSet) -> Cells {
+        let mut cells = self.dead_cells();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let alive = bits[(row * self.width + col) as usize];
+                cells.set(self.get_index(row, col), alive.into());
+            }
+        }
+        cells
+    }
+
+    fn pack_cells(&self, cells: &Cells) -> Vec<u8> {
+        let bits_per_cell = cells.bits_per_cell() as usize;
+        let size = (self.width * self.height) as usize;
+        let mut bytes = vec![0; (size * bits_per_cell).div_ceil(8)];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let i = (row * self.width + col) as usize;
+                let state = cells.get(self.get_index(row, col)).state();
+                if bits_per_cell == 8 {
+                    bytes[i] = state;
+                } else if state != 0 {
+                    bytes[i / 8] |= 1 << (i % 8);
+                }
+            }
+        }
+        bytes
+    }
+
+    /// The spare buffer, filled with a copy of the cells for the next
+    /// generation to be written into. Hand it back with `swap_cells`.
+    pub(crate) fn next_cells(&mut self) -> Cells {
+        let mut next = std::mem::replace(&mut self.back_cells, Cells::Bytes(Vec::new()));
+        next.copy_from(&self.cells);
+        next
+    }
+
+    /// Make the next generation current, keeping the old cells as the spare
+    /// buffer for the next tick.
+    pub(crate) fn swap_cells(&mut self, next: Cells) {
+        self.back_cells = std::mem::replace(&mut self.cells, next);
+    }
+
+    /// Empty statistics for a tick, reusing the buffers of the last tick's.
+    pub(crate) fn fresh_stats(&mut self) -> TickStats {
+        let mut stats = std::mem::take(&mut self.spare_stats);
+        stats.clear();
+        stats
+    }
+
+    /// Advance every cell by one generation.
+    fn step_dense(&mut self) -> TickStats {
+        let stride = (self.width + 2) as usize;
+        self.refresh_halo();
+        let mut next = self.next_cells();
+        let mut stats = self.fresh_stats();
+        let mut column_sums = std::mem::take(&mut self.column_sums);
+        let alive = |idx: usize| self.cells.is_alive(idx) as u8;
+
+        // Keep a running count of the live cells in each column of the three
+        // rows centred on the current row, so each cell's neighbours can be
+        // counted from three column sums rather than eight separate reads.
+        column_sums.clear();
+        column_sums.extend(
+            (0..stride).map(|col| alive(col) + alive(stride + col) + alive(2 * stride + col)),
+        );
+        for row in 1..=self.height as usize {
+            if row > 1 {
+                for (col, sum) in column_sums.iter_mut().enumerate() {
+                    *sum = *sum - alive((row - 2) * stride + col) + alive((row + 1) * stride + col);
+                }
+            }
+            for col in 1..=self.width as usize {
+                let idx = row * stride + col;
+                if self.is_wall(idx) {
+                    continue;
+                }
+                let cell = self.cells.is_alive(idx);
+                let live_neighbors =
+                    column_sums[col - 1] + column_sums[col] + column_sums[col + 1] - cell as u8;
+                let next_cell =
+                    self.rule.next(cell, live_neighbors) && (cell || self.birth_allowed(idx));
+                let (row, col) = (row as u32 - 1, col as u32 - 1);
+                if next_cell != cell && !self.is_frozen(row, col) {
+                    let next_state = if next_cell {
+                        self.born_cell(row, col)
+                    } else {
+                        Cell::DEAD
+                    };
+                    next.set(idx, next_state);
+                    stats.record_change(row, col, next_cell);
+                }
+            }
+        }
+        self.column_sums = column_sums;
+        self.swap_cells(next);
+        self.clear_halo();
+        stats.population = self.cells.population();
+        stats
+    }
+
+    /// The state of a cell being born. Under two state rules this is always
+    /// alive, while with more states it takes the most common state among
+    /// its live neighbours, so that colours spread.
+    fn born_cell(&self, row: u32, col: u32) -> Cell {
+        if self.rule.states() <= 2 {
+            return Cell::ALIVE;
+        }
+        let mut counts = vec![0; self.rule.states() as usize];
+        for d_row in -1..=1 {
+            for d_col in -1..=1 {
+                if d_row == 0 && d_col == 0 {
+                    continue;
+                }
+                let neighbor_row = self.row_boundary.resolve(row as i32 + d_row, self.height);
+                let neighbor_col = self.col_boundary.resolve(col as i32 + d_col, self.width);
+                if let (Some(neighbor_row), Some(neighbor_col)) = (neighbor_row, neighbor_col) {
+                    let idx = self.get_index(neighbor_row, neighbor_col);
+                    if !self.is_wall(idx) {
+                        counts[self.cells.get(idx).state() as usize] += 1;
+                    }
+                }
+            }
+        }
+        // Ties go to the lowest state.
+        let state = (1..counts.len()).rev().max_by_key(|&state| counts[state]);
+        Cell(state.unwrap_or(1) as u8)
+    }
+
+    /// The active rule in B/S notation.
+    pub(crate) fn rule_string(&self) -> String {
+        self.rule.to_string()
+    }
+
+    /// Switch to a rule, killing every cell so that the storage can suit the
+    /// rule's states.
+    pub(crate) fn reset_rule(&mut self, rule: rule::Rule) {
+        self.rule = rule;
+        self.cells = self.dead_cells();
+        self.initial_cells = self.dead_cells();
+        self.cells_edited();
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+    }
+
+    /// The inverse of `pack_cells`.
+    fn unpack_cells(&self, bytes: &[u8]) -> Cells {
+        let mut cells = self.dead_cells();
+        let bits_per_cell = cells.bits_per_cell() as usize;
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let i = (row * self.width + col) as usize;
+                let state = if bits_per_cell == 8 {
+                    bytes[i]
+                } else {
+                    (bytes[i / 8] >> (i % 8)) & 1
+                };
+                cells.set(self.get_index(row, col), Cell(state));
+            }
+        }
+        cells
+    }
+
+    /// Fill the halo with the cells which lie beyond each edge: copies of
+    /// the cells along the opposite edge for wrapping boundaries, dead cells
+    /// for absorbing boundaries, or copies of the cells along the same edge
+    /// for reflecting boundaries.
+    fn refresh_halo(&mut self) {
+        let stride = (self.width + 2) as usize;
+        let (width, height) = (self.width as usize, self.height as usize);
+        for row in 1..=height {
+            let start = row * stride;
+            let (first, last) = (self.cells.get(start + 1), self.cells.get(start + width));
+            let (left, right) = match self.col_boundary {
+                Boundary::Wrap => (last, first),
+                Boundary::Absorbing => (Cell::DEAD, Cell::DEAD),
+                Boundary::Reflecting => (first, last),
+            };
+            self.cells.set(start, left);
+            self.cells.set(start + width + 1, right);
+        }
+        // The rows are copied including their halo cells so the corners
+        // match the cells diagonally beyond them.
+        for col in 0..stride {
+            let first = self.cells.get(stride + col);
+            let last = self.cells.get(height * stride + col);
+            let (top, bottom) = match self.row_boundary {
+                Boundary::Wrap => (last, first),
+                Boundary::Absorbing => (Cell::DEAD, Cell::DEAD),
+                Boundary::Reflecting => (first, last),
+            };
+            self.cells.set(col, top);
+            self.cells.set((height + 1) * stride + col, bottom);
+        }
+    }
+
+    fn clear_halo(&mut self) {
+        let stride = (self.width + 2) as usize;
+        let (width, height) = (self.width as usize, self.height as usize);
+        for row in 1..=height {
+            self.cells.set(row * stride, Cell::DEAD);
+            self.cells.set(row * stride + width + 1, Cell::DEAD);
+        }
+        for col in 0..stride {
+            self.cells.set(col, Cell::DEAD);
+            self.cells.set((height + 1) * stride + col, Cell::DEAD);
         }
     }
 }
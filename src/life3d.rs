@@ -0,0 +1,298 @@
+//! Life in three dimensions, on a grid of cubes which wraps around in every
+//! direction, where each cell has 26 neighbours.
+//!
+//! Rules are written in Carter Bays' notation as four numbers: the fewest
+//! and most live neighbours for a live cell to survive, then the fewest and
+//! most for a dead cell to be born. `5766` is the rule with Bays' glider and
+//! `4555` is the other rule he studied.
+
+use crate::rng::{self, Rng};
+use crate::UniverseError;
+use fixedbitset::FixedBitSet;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// A rule for three dimensional Life.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rule3D {
+    /// Fewest and most live neighbours for a live cell to survive.
+    survive: (u8, u8),
+    /// Fewest and most live neighbours for a dead cell to be born.
+    birth: (u8, u8),
+}
+
+impl Rule3D {
+    const BAYS_5766: Rule3D = Rule3D {
+        survive: (5, 7),
+        birth: (6, 6),
+    };
+
+    /// Parse a rule written as four digits, such as `5766`, or as four
+    /// numbers separated by commas, such as `5,7,6,6`, which allows counts
+    /// above 9.
+    fn parse(rule: &str) -> Result<Rule3D, String> {
+        let rule = rule.trim();
+        let numbers: Vec<u8> = if rule.contains(',') {
+            rule.split(',')
+                .map(|number| number.trim().parse().ok())
+                .collect::<Option<_>>()
+        } else {
+            rule.chars()
+                .map(|ch| ch.to_digit(10).map(|digit| digit as u8))
+                .collect::<Option<_>>()
+        }
+        .ok_or_else(|| format!("the rule {} is not four numbers", rule))?;
+        let (survive, birth) = match numbers[..] {
+            [low, high, birth_low, birth_high] => ((low, high), (birth_low, birth_high)),
+            _ => return Err(format!("the rule {} is not four numbers", rule)),
+        };
+        if survive.0 > survive.1 || birth.0 > birth.1 || survive.1 > 26 || birth.1 > 26 {
+            return Err(format!(
+                "the rule {} needs each range to be in order and within 0 to 26",
+                rule
+            ));
+        }
+        if birth.0 == 0 {
+            return Err(format!(
+                "the rule {} brings cells with no live neighbours to life",
+                rule
+            ));
+        }
+        Ok(Rule3D { survive, birth })
+    }
+
+    fn next(self, alive: bool, live_neighbors: u8) -> bool {
+        let (low, high) = if alive { self.survive } else { self.birth };
+        (low..=high).contains(&live_neighbors)
+    }
+}
+
+impl fmt::Display for Rule3D {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let numbers = [self.survive.0, self.survive.1, self.birth.0, self.birth.1];
+        if numbers.iter().all(|&number| number < 10) {
+            numbers
+                .iter()
+                .try_for_each(|number| write!(f, "{}", number))
+        } else {
+            write!(
+                f,
+                "{},{},{},{}",
+                numbers[0], numbers[1], numbers[2], numbers[3]
+            )
+        }
+    }
+}
+
+/// Cells of the built-in stamps as `(x, y, z)` offsets.
+fn stamp_cells(name: &str) -> Option<Vec<(u32, u32, u32)>> {
+    match name {
+        // Bays' glider for 5766: two layers of the two dimensional glider,
+        // moving one cell along x and y every four generations.
+        "glider" => Some(
+            [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+                .iter()
+                .flat_map(|&(x, y)| (0..2).map(move |z| (x, y, z)))
+                .collect(),
+        ),
+        // A still life under 5766, where every cell has seven neighbours.
+        "cube" => Some(
+            (0..8)
+                .map(|corner| (corner & 1, corner >> 1 & 1, corner >> 2))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+#[wasm_bindgen]
+pub struct Universe3D {
+    width: u32,
+    height: u32,
+    depth: u32,
+    rule: Rule3D,
+    /// Number of ticks since the universe was created.
+    generation: u64,
+    /// A bit per cell, ordered by layer, then row, then column.
+    cells: FixedBitSet,
+    rng: Rng,
+}
+
+impl Universe3D {
+    fn get_index(&self, x: u32, y: u32, z: u32) -> usize {
+        ((z * self.height + y) * self.width + x) as usize
+    }
+
+    /// Number of live cells in the 3×3×3 cube centred on each cell,
+    /// including the cell itself, summed one axis at a time.
+    fn cube_sums(&self) -> Vec<u8> {
+        let (width, height, depth) = (
+            self.width as usize,
+            self.height as usize,
+            self.depth as usize,
+        );
+        let mut sums: Vec<u8> = (0..self.cells.len()).map(|i| self.cells[i] as u8).collect();
+        // The distance between neighbouring cells along each axis, and the
+        // length of the axis.
+        for &(step, len) in [(1, width), (width, height), (width * height, depth)].iter() {
+            let line_sums = sums.clone();
+            for (i, sum) in sums.iter_mut().enumerate() {
+                let pos = i / step % len;
+                let start = i - pos * step;
+                let before = start + (pos + len - 1) % len * step;
+                let after = start + (pos + 1) % len * step;
+                *sum = line_sums[before] + line_sums[i] + line_sums[after];
+            }
+        }
+        sums
+    }
+}
+
+seedable! {
+    /// Seed the random number generator used by `randomise`, so the same
+    /// seed gives the same cells.
+    Universe3D
+}
+
+#[wasm_bindgen]
+impl Universe3D {
+    /// Create an empty universe, running Bays' 5766 rule.
+    pub fn new(width: u32, height: u32, depth: u32) -> Result<Universe3D, UniverseError> {
+        crate::utils::set_panic_hook();
+        let size = UniverseError::checked_size("Universe3D::new", &[width, height, depth])?;
+        Ok(Universe3D {
+            width,
+            height,
+            depth,
+            rule: Rule3D::BAYS_5766,
+            generation: 0,
+            cells: FixedBitSet::with_capacity(size),
+            rng: Rng::new(rng::random_seed()),
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u32 {
+        self.cells.count_ones(..) as u32
+    }
+
+    /// The rule in Bays' notation, such as `5766`.
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    /// Switch to a rule in Bays' notation, described in the `life3d`
+    /// module, keeping the cells as they are.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), String> {
+        self.rule = Rule3D::parse(rule)?;
+        Ok(())
+    }
+
+    /// Make each cell alive with a probability of `density`, killing the
+    /// rest.
+    pub fn randomise(&mut self, density: f64) {
+        for i in 0..self.cells.len() {
+            let alive = self.rng.next_f64() < density;
+            self.cells.set(i, alive);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Whether the cell at column `x`, row `y` and layer `z` is alive, or
+    /// false if it is outside the universe.
+    pub fn get_cell(&self, x: u32, y: u32, z: u32) -> bool {
+        x < self.width && y < self.height && z < self.depth && self.cells[self.get_index(x, y, z)]
+    }
+
+    /// Set a cell, wrapping around the edges.
+    pub fn set_cell(&mut self, x: u32, y: u32, z: u32, alive: bool) {
+        let idx = self.get_index(x % self.width, y % self.height, z % self.depth);
+        self.cells.set(idx, alive);
+    }
+
+    pub fn toggle_cell(&mut self, x: u32, y: u32, z: u32) {
+        let idx = self.get_index(x % self.width, y % self.height, z % self.depth);
+        self.cells.set(idx, !self.cells[idx]);
+    }
+
+    /// Make the cells of a built-in stamp alive with the corner of its
+    /// bounding box at a cell, wrapping around the edges. The stamps are
+    /// `glider`, Bays' glider for 5766, and `cube`, a still life under 5766.
+    /// Returns false for an unknown stamp.
+    pub fn stamp(&mut self, name: &str, x: u32, y: u32, z: u32) -> bool {
+        match stamp_cells(name) {
+            Some(cells) => {
+                for (dx, dy, dz) in cells {
+                    self.set_cell(x + dx, y + dy, z + dz, true);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.tick_many(1);
+    }
+
+    pub fn tick_many(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            trace_span!("tick");
+            let sums = self.cube_sums();
+            let mut next = FixedBitSet::with_capacity(self.cells.len());
+            for (i, &sum) in sums.iter().enumerate() {
+                let alive = self.cells[i];
+                next.set(i, self.rule.next(alive, sum - alive as u8));
+            }
+            self.cells = next;
+            self.generation += 1;
+        }
+    }
+
+    /// Number of bytes in each layer from `slice`.
+    pub fn slice_byte_len(&self) -> usize {
+        (self.width as usize * self.height as usize).div_ceil(8)
+    }
+
+    /// The cells of layer `z`, packed one bit per cell row by row starting
+    /// from the least significant bit of each byte, like the two
+    /// dimensional universe's `cells_as_bytes`. Empty if the layer is
+    /// outside the universe.
+    pub fn slice(&self, z: u32) -> Vec<u8> {
+        if z >= self.depth {
+            return Vec::new();
+        }
+        let mut bytes = vec![0; self.slice_byte_len()];
+        let start = self.get_index(0, 0, z);
+        for i in 0..(self.width * self.height) as usize {
+            if self.cells[start + i] {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bytes
+    }
+
+    /// Every layer from `slice`, one after another, so layer `z` starts at
+    /// byte `z * slice_byte_len()`.
+    pub fn slices(&self) -> Vec<u8> {
+        (0..self.depth).flat_map(|z| self.slice(z)).collect()
+    }
+}
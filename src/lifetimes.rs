@@ -0,0 +1,89 @@
+use crate::{CellStorage, TickStats, Universe};
+use wasm_bindgen::prelude::*;
+
+/// Lifetimes this long or longer share the last bucket of the histogram.
+const MAX_LIFETIME: usize = 1023;
+
+/// Marks cells whose birth wasn't seen, such as those alive when tracking
+/// started or drawn in by the user.
+pub(crate) const UNKNOWN: u64 = u64::MAX;
+
+/// When each live cell was born, and how long the cells which have died
+/// lived for.
+pub(crate) struct Lifetimes {
+    /// The generation each cell was born in, laid out in the same way as
+    /// `cells`.
+    pub(crate) born: Vec<u64>,
+    /// Number of cells which lived for each number of generations.
+    histogram: Vec<u32>,
+}
+
+impl Lifetimes {
+    /// Number of cells covered, which matches the universe's cells.
+    pub(crate) fn len(&self) -> usize {
+        self.born.len()
+    }
+
+    pub(crate) fn new(size: usize) -> Lifetimes {
+        Lifetimes {
+            born: vec![UNKNOWN; size],
+            histogram: Vec::new(),
+        }
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.born.capacity() * std::mem::size_of::<u64>()
+            + self.histogram.capacity() * std::mem::size_of::<u32>()
+    }
+}
+
+impl Universe {
+    /// Record births and deaths from a tick, which leaves the universe at
+    /// `generation`.
+    pub(crate) fn update_lifetimes(&mut self, stats: &TickStats, generation: u64) {
+        let mut lifetimes = match self.lifetimes.take() {
+            Some(lifetimes) => lifetimes,
+            None => return,
+        };
+        for &(row, col, born) in stats.changes.iter() {
+            let idx = self.get_index(row, col);
+            if born {
+                lifetimes.born[idx] = generation;
+                continue;
+            }
+            if lifetimes.born[idx] != UNKNOWN {
+                let lifetime = ((generation - lifetimes.born[idx]) as usize).min(MAX_LIFETIME);
+                if lifetimes.histogram.len() <= lifetime {
+                    lifetimes.histogram.resize(lifetime + 1, 0);
+                }
+                lifetimes.histogram[lifetime] += 1;
+            }
+            lifetimes.born[idx] = UNKNOWN;
+        }
+        self.lifetimes = Some(lifetimes);
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start or stop recording how many generations cells live for, from
+    /// their birth to their death. Starting again clears the histogram.
+    pub fn set_lifetime_tracking(&mut self, enabled: bool) {
+        self.lifetimes = if enabled {
+            Some(Lifetimes::new(self.cells.len()))
+        } else {
+            None
+        };
+    }
+
+    /// The number of cells which have died after living for each number of
+    /// generations, indexed by lifetime. Cells which are still alive aren't
+    /// included, nor are cells which were alive when tracking started, and
+    /// lifetimes of 1023 generations or more are all counted in the last
+    /// bucket.
+    pub fn lifetime_histogram(&self) -> Vec<u32> {
+        self.lifetimes
+            .as_ref()
+            .map_or(Vec::new(), |lifetimes| lifetimes.histogram.clone())
+    }
+}
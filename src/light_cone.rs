@@ -0,0 +1,85 @@
+use crate::{CellStorage, Universe};
+use fixedbitset::FixedBitSet;
+use wasm_bindgen::prelude::*;
+
+/// The cells which could have been influenced by a region of the universe.
+///
+/// Nothing travels faster than one cell per generation, so after `n`
+/// generations the cone is the region grown by `n` cells in each direction.
+pub(crate) struct LightCone {
+    row: u32,
+    col: u32,
+    height: u32,
+    width: u32,
+    generations: u32,
+    cells: FixedBitSet,
+}
+
+impl LightCone {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        std::mem::size_of_val(self.cells.as_slice())
+    }
+}
+
+impl Universe {
+    pub(crate) fn update_light_cone(&mut self) {
+        if let Some(mut cone) = self.light_cone.take() {
+            cone.generations += 1;
+            self.mark_light_cone(&mut cone);
+            self.light_cone = Some(cone);
+        }
+    }
+
+    fn mark_light_cone(&self, cone: &mut LightCone) {
+        cone.cells = FixedBitSet::with_capacity(self.cells.len());
+        // Rows and columns beyond the size of the universe would wrap back
+        // onto cells that are already marked.
+        let rows = (cone.height + 2 * cone.generations).min(self.height);
+        let cols = (cone.width + 2 * cone.generations).min(self.width);
+        let first_row = cone.row + self.height - cone.generations % self.height;
+        let first_col = cone.col + self.width - cone.generations % self.width;
+        for row in first_row..first_row + rows {
+            for col in first_col..first_col + cols {
+                let idx = self.get_index(row % self.height, col % self.width);
+                cone.cells.insert(idx);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start tracking the cells which could be influenced by the `w` by `h`
+    /// region with its top-left corner at column `x` and row `y`.
+    ///
+    /// The cone grows each tick and can be read with `light_cone`.
+    pub fn start_light_cone(&mut self, x: u32, y: u32, w: u32, h: u32) {
+        let mut cone = LightCone {
+            row: y % self.height,
+            col: x % self.width,
+            height: h.max(1),
+            width: w.max(1),
+            generations: 0,
+            cells: FixedBitSet::with_capacity(0),
+        };
+        self.mark_light_cone(&mut cone);
+        self.light_cone = Some(cone);
+    }
+
+    pub fn stop_light_cone(&mut self) {
+        self.light_cone = None;
+    }
+
+    /// Number of generations the light cone has been growing for.
+    pub fn light_cone_generations(&self) -> u32 {
+        self.light_cone.as_ref().map_or(0, |cone| cone.generations)
+    }
+
+    /// Pointer to the light cone overlay, laid out in the same way as
+    /// `cells`, or null if no light cone is being tracked.
+    pub fn light_cone(&self) -> *const u32 {
+        self.light_cone
+            .as_ref()
+            .map_or(std::ptr::null(), |cone| cone.cells.as_slice().as_ptr())
+    }
+}
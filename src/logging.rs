@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use wasm_bindgen::prelude::*;
+
+/// How much is written to the browser console, from nothing to everything.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+/// The most detailed level compiled in. Debug and trace messages are only
+/// built with the `verbose_logging` feature, so they cost nothing otherwise.
+const MAX_LEVEL: LogLevel = if cfg!(feature = "verbose_logging") {
+    LogLevel::Trace
+} else {
+    LogLevel::Info
+};
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Warn as u8);
+
+/// Whether messages at a level are currently written.
+pub(crate) fn enabled(level: LogLevel) -> bool {
+    level as u8 <= MAX_LEVEL as u8 && level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn write(level: LogLevel, message: &str) {
+    let message = JsValue::from_str(message);
+    match level {
+        LogLevel::Off => {}
+        LogLevel::Error => web_sys::console::error_1(&message),
+        LogLevel::Warn => web_sys::console::warn_1(&message),
+        LogLevel::Info => web_sys::console::info_1(&message),
+        LogLevel::Debug => web_sys::console::debug_1(&message),
+        LogLevel::Trace => web_sys::console::log_1(&message),
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn write(level: LogLevel, message: &str) {
+    match level {
+        LogLevel::Off => {}
+        LogLevel::Error => tracing::error!("{}", message),
+        LogLevel::Warn => tracing::warn!("{}", message),
+        LogLevel::Info => tracing::info!("{}", message),
+        LogLevel::Debug => tracing::debug!("{}", message),
+        LogLevel::Trace => tracing::trace!("{}", message),
+    }
+}
+
+/// Send `tracing` spans and events to the browser's performance timeline
+/// and console. Call this once, before creating a universe.
+#[cfg(feature = "tracing")]
+#[wasm_bindgen]
+pub fn init_tracing() {
+    tracing_wasm::set_as_global_default();
+}
+
+/// Set the most detailed level of messages written to the console, which is
+/// `Warn` to begin with. Levels beyond `Info` are only available in builds
+/// with the `verbose_logging` feature.
+#[wasm_bindgen]
+pub fn set_log_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+#[wasm_bindgen]
+pub fn log_level() -> LogLevel {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => LogLevel::Off,
+        1 => LogLevel::Error,
+        2 => LogLevel::Warn,
+        3 => LogLevel::Info,
+        4 => LogLevel::Debug,
+        _ => LogLevel::Trace,
+    }
+}
@@ -0,0 +1,153 @@
+use crate::versioning::{self, Format};
+use crate::{Universe, UniverseError};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerKind {
+    /// A single highlighted cell.
+    Highlight = 0,
+    /// A labelled point on a cell.
+    Point = 1,
+    /// A rectangle of cells, optionally labelled.
+    Rect = 2,
+}
+
+/// An annotation placed on the universe by the user. Markers are drawn by the
+/// frontend and don't affect the cells.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    id: u32,
+    kind: MarkerKind,
+    row: u32,
+    col: u32,
+    /// Size of the marked area, which is a single cell for highlights and
+    /// points.
+    width: u32,
+    height: u32,
+    label: String,
+}
+
+#[wasm_bindgen]
+impl Marker {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> MarkerKind {
+        self.kind
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn col(&self) -> u32 {
+        self.col
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> String {
+        self.label.clone()
+    }
+}
+
+impl Universe {
+    fn add_marker(
+        &mut self,
+        kind: MarkerKind,
+        row: u32,
+        col: u32,
+        width: u32,
+        height: u32,
+        label: &str,
+    ) -> u32 {
+        let id = self
+            .markers
+            .iter()
+            .map(|marker| marker.id + 1)
+            .max()
+            .unwrap_or(0);
+        self.markers.push(Marker {
+            id,
+            kind,
+            row,
+            col,
+            width,
+            height,
+            label: label.to_string(),
+        });
+        id
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Highlight a cell. Returns the id of the new marker.
+    pub fn highlight_cell(&mut self, row: u32, col: u32) -> u32 {
+        self.add_marker(MarkerKind::Highlight, row, col, 1, 1, "")
+    }
+
+    /// Place a labelled point on a cell. Returns the id of the new marker.
+    pub fn add_label(&mut self, row: u32, col: u32, label: &str) -> u32 {
+        self.add_marker(MarkerKind::Point, row, col, 1, 1, label)
+    }
+
+    /// Mark the `w` by `h` rectangle with its top-left corner at column `x`
+    /// and row `y`, with an optional label which can be empty. Returns the
+    /// id of the new marker.
+    pub fn add_rect(&mut self, x: u32, y: u32, w: u32, h: u32, label: &str) -> u32 {
+        self.add_marker(MarkerKind::Rect, y, x, w, h, label)
+    }
+
+    /// Remove a marker, returning false if there is none with the id.
+    pub fn remove_marker(&mut self, id: u32) -> bool {
+        let count = self.markers.len();
+        self.markers.retain(|marker| marker.id != id);
+        self.markers.len() != count
+    }
+
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+    }
+
+    /// The markers in the order they were added, for drawing.
+    pub fn markers(&self) -> Vec<Marker> {
+        self.markers.clone()
+    }
+
+    /// The markers as JSON, so they can be saved and shared along with the
+    /// cells.
+    pub fn export_markers(&self) -> String {
+        trace_span!("serialize");
+        serde_json::to_string(&versioning::wrap(Format::Markers, &self.markers)).unwrap()
+    }
+
+    /// Replace the markers with ones from JSON made by `export_markers`,
+    /// including by older builds. Returns the number of markers.
+    pub fn import_markers(&mut self, json: &str) -> Result<u32, UniverseError> {
+        trace_span!("deserialize");
+        let context = format!("{} bytes of JSON", json.len());
+        self.markers = serde_json::from_str(json)
+            .map_err(|err| err.to_string())
+            .and_then(|document| versioning::unwrap(Format::Markers, document))
+            .map_err(|err| self.error("import_markers", context, err))?;
+        Ok(self.markers.len() as u32)
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{markers, CellStorage, LogLevel, Universe};
+use wasm_bindgen::prelude::*;
+
+/// Approximate number of bytes used by each part of a universe.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoryReport {
+    /// The current and initial cell buffers.
+    pub cells: usize,
+    /// Snapshots kept for stepping back through previous generations.
+    pub history: usize,
+    /// Per-cell data such as ages, activity counts, birth times, resource
+    /// levels, neighbour counts and object ids.
+    pub heatmaps: usize,
+    /// Statistics and events recorded while the universe runs, the cells
+    /// changed by the last tick, and the log of shared edits.
+    pub recorders: usize,
+    /// Overlay buffers such as the light cone, walls, sources and sinks,
+    /// selections, markers, paths and sparks, and the shared frame.
+    pub overlays: usize,
+    /// State kept by detectors such as the glider and period detectors, and
+    /// goals.
+    pub detectors: usize,
+    /// The sum of all of the above.
+    pub total: usize,
+    /// Current size of the wasm linear memory, or 0 when not running in
+    /// wasm.
+    pub wasm_memory: usize,
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Report how much memory the universe is using, so frontends can warn
+    /// before a resize or setting would use too much.
+    pub fn memory_stats(&self) -> MemoryReport {
+        let mut report = MemoryReport {
+            cells: self.cells.as_bytes().len()
+                + self.back_cells.as_bytes().len()
+                + self.initial_cells.as_bytes().len(),
+            history: self.history.as_ref().map_or(0, |history| history.bytes()),
+            heatmaps: self
+                .ages
+                .as_ref()
+                .map_or(0, |ages| ages.len() * std::mem::size_of::<u32>())
+                + self
+                    .resources
+                    .as_ref()
+                    .map_or(0, |resources| resources.memory_bytes())
+                + self
+                    .activity
+                    .as_ref()
+                    .map_or(0, |activity| activity.len() * std::mem::size_of::<u32>())
+                + self
+                    .lifetimes
+                    .as_ref()
+                    .map_or(0, |lifetimes| lifetimes.memory_bytes())
+                + self
+                    .neighbor_counts
+                    .as_ref()
+                    .map_or(0, |counts| counts.len())
+                + self
+                    .components
+                    .as_ref()
+                    .map_or(0, |components| components.memory_bytes()),
+            recorders: self
+                .summary
+                .as_ref()
+                .map_or(0, |summary| summary.memory_bytes())
+                + self
+                    .records
+                    .as_ref()
+                    .map_or(0, |records| records.memory_bytes())
+                + self.op_log.as_ref().map_or(0, |log| log.memory_bytes())
+                + self.changed.capacity() * std::mem::size_of::<u32>(),
+            overlays: self
+                .light_cone
+                .as_ref()
+                .map_or(0, |cone| cone.memory_bytes())
+                + self
+                    .selection
+                    .as_ref()
+                    .map_or(0, |selection| selection.memory_bytes())
+                + self.walls.as_ref().map_or(0, |walls| walls.memory_bytes())
+                + self.anchors.as_ref().map_or(0, |anchors| anchors.len())
+                + self.region.as_ref().map_or(0, |region| {
+                    region.capacity() * std::mem::size_of::<(u32, u32)>()
+                })
+                + self.markers.capacity() * std::mem::size_of::<markers::Marker>()
+                + self.path.as_ref().map_or(0, |path| {
+                    path.capacity() * std::mem::size_of::<(u32, u32)>()
+                })
+                + self
+                    .sparks
+                    .as_ref()
+                    .map_or(0, |sparks| sparks.memory_bytes())
+                + self
+                    .shared_frame
+                    .as_ref()
+                    .map_or(0, |frame| frame.memory_bytes()),
+            detectors: self
+                .glider_detector
+                .as_ref()
+                .map_or(0, |detector| detector.memory_bytes())
+                + self
+                    .period
+                    .as_ref()
+                    .map_or(0, |period| period.memory_bytes())
+                + self.goals.memory_bytes(),
+            wasm_memory: wasm_memory_bytes(),
+            ..MemoryReport::default()
+        };
+        report.total = report.cells
+            + report.history
+            + report.heatmaps
+            + report.recorders
+            + report.overlays
+            + report.detectors;
+        report
+    }
+}
+
+impl Universe {
+    /// Tell the memory growth listener, if there is one, when wasm memory
+    /// has grown since this was last checked. This is checked after the
+    /// operations which make large allocations, such as ticking and
+    /// resizing.
+    pub(crate) fn check_memory_growth(&mut self) {
+        let size = wasm_memory_bytes();
+        if size <= self.memory_size {
+            return;
+        }
+        self.memory_size = size;
+        if let Some(listener) = &self.memory_listener {
+            if let Err(err) = listener.call1(&JsValue::NULL, &(size as f64).into()) {
+                log!(LogLevel::Error, "memory growth listener failed: {:?}", err);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Call a function with the new size in bytes whenever wasm memory
+    /// grows. Growing memory detaches the buffer behind any typed arrays
+    /// viewing it, such as views of `cells`, so they need to be recreated.
+    pub fn on_memory_growth(&mut self, listener: js_sys::Function) {
+        self.memory_size = wasm_memory_bytes();
+        self.memory_listener = Some(listener);
+    }
+
+    pub fn clear_memory_growth_listener(&mut self) {
+        self.memory_listener = None;
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn wasm_memory_bytes() -> usize {
+    const PAGE_SIZE: usize = 65536;
+    core::arch::wasm32::memory_size::<0>() * PAGE_SIZE
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn wasm_memory_bytes() -> usize {
+    0
+}
@@ -0,0 +1,66 @@
+use crate::{CellStorage, Universe};
+use wasm_bindgen::prelude::*;
+
+/// Colour of the outline marking the viewport on the minimap.
+const VIEWPORT_COLOR: [u8; 4] = [255, 0, 0, 255];
+
+#[wasm_bindgen]
+impl Universe {
+    /// Record which part of the universe the frontend is showing, as the
+    /// `w` by `h` rectangle of cells with its top-left corner at column `x`
+    /// and row `y`, so that it can be marked on the minimap.
+    pub fn set_viewport(&mut self, x: u32, y: u32, w: u32, h: u32) {
+        self.viewport = Some((x, y, w, h));
+    }
+
+    /// A `w` by `h` pixel overview of the whole universe as RGBA bytes,
+    /// ready to be put into an `ImageData`.
+    ///
+    /// Each pixel is shaded by the fraction of live cells in the part of the
+    /// universe it covers, and the viewport is outlined in red.
+    pub fn minimap(&self, w: u32, h: u32) -> Vec<u8> {
+        trace_span!("render");
+        let (w, h) = (w.max(1) as usize, h.max(1) as usize);
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut live = vec![0u32; w * h];
+        let stride = width + 2;
+        for idx in self.cells.live_indices() {
+            let (row, col) = (idx / stride - 1, idx % stride - 1);
+            live[row * h / height * w + col * w / width] += 1;
+        }
+
+        let mut pixels = vec![0; w * h * 4];
+        for (i, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+            let (y, x) = (i / w, i % w);
+            // The number of cells covered by each pixel differs by one from
+            // pixel to pixel when the sizes don't divide evenly.
+            let rows = ((y + 1) * height).div_ceil(h) - (y * height).div_ceil(h);
+            let cols = ((x + 1) * width).div_ceil(w) - (x * width).div_ceil(w);
+            let density = live[i] as f64 / (rows * cols).max(1) as f64;
+            // Square rooting makes sparse areas stand out from empty ones.
+            let shade = 255 - (density.min(1.0).sqrt() * 255.0) as u8;
+            pixel.copy_from_slice(&[shade, shade, shade, 255]);
+        }
+
+        if let Some((x, y, view_w, view_h)) = self.viewport {
+            let to_x = |col: u32| (col as usize * w / width).min(w - 1);
+            let to_y = |row: u32| (row as usize * h / height).min(h - 1);
+            let (left, top) = (to_x(x), to_y(y));
+            let right = to_x(x + view_w.max(1) - 1);
+            let bottom = to_y(y + view_h.max(1) - 1);
+            let mut mark = |px: usize, py: usize| {
+                let i = (py * w + px) * 4;
+                pixels[i..i + 4].copy_from_slice(&VIEWPORT_COLOR);
+            };
+            for px in left..=right {
+                mark(px, top);
+                mark(px, bottom);
+            }
+            for py in top..=bottom {
+                mark(left, py);
+                mark(right, py);
+            }
+        }
+        pixels
+    }
+}
@@ -0,0 +1,199 @@
+//! Names for the settings enums, so that frontends can fill settings menus
+//! and save preferences without keeping their own tables of the values.
+//!
+//! Each enum gets `Display` and `FromStr` in Rust, and three functions in
+//! JavaScript: `boundary_names()` lists the names in order,
+//! `boundary_to_string(Boundary.Wrap)` gives `"wrap"` and
+//! `boundary_from_string("wrap")` gives `Boundary.Wrap`. Names are in
+//! camelCase, matching the names used by `UniverseConfig`.
+
+use crate::{
+    Boundary, BrushShape, HeightSource, LogLevel, Material, MirrorMode, PatternFit, RuleMismatch,
+    Topology,
+};
+use std::fmt;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+macro_rules! names {
+    (
+        $ty:ident,
+        $what:literal,
+        [$names:ident, $to_string:ident, $from_string:ident],
+        { $( $variant:ident => $name:literal ),* $(,)? }
+    ) => {
+        impl $ty {
+            const NAMES: &'static [($ty, &'static str)] = &[ $( ($ty::$variant, $name) ),* ];
+        }
+
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let &(_, name) = $ty::NAMES.iter().find(|(value, _)| value == self).unwrap();
+                f.write_str(name)
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = String;
+
+            fn from_str(name: &str) -> Result<$ty, String> {
+                $ty::NAMES
+                    .iter()
+                    .find(|&&(_, known)| known == name)
+                    .map(|&(value, _)| value)
+                    .ok_or_else(|| unknown($what, name, $ty::NAMES.iter().map(|&(_, name)| name)))
+            }
+        }
+
+        #[doc = concat!("Names of every `", stringify!($ty), "`, in order.")]
+        #[wasm_bindgen]
+        pub fn $names() -> Vec<String> {
+            $ty::NAMES.iter().map(|&(_, name)| name.to_string()).collect()
+        }
+
+        #[wasm_bindgen]
+        pub fn $to_string(value: $ty) -> String {
+            value.to_string()
+        }
+
+        #[wasm_bindgen]
+        pub fn $from_string(name: &str) -> Result<$ty, String> {
+            name.parse()
+        }
+    };
+}
+
+/// The error for a name which isn't one of the known names.
+fn unknown<'a>(what: &str, name: &str, known: impl Iterator<Item = &'a str>) -> String {
+    let known: Vec<&str> = known.collect();
+    format!(
+        "unknown {} `{}`, expected one of {}",
+        what,
+        name,
+        known.join(", ")
+    )
+}
+
+names!(
+    Boundary,
+    "boundary",
+    [boundary_names, boundary_to_string, boundary_from_string],
+    {
+        Wrap => "wrap",
+        Absorbing => "absorbing",
+        Reflecting => "reflecting",
+    }
+);
+
+names!(
+    BrushShape,
+    "brush shape",
+    [brush_shape_names, brush_shape_to_string, brush_shape_from_string],
+    {
+        Square => "square",
+        Circle => "circle",
+    }
+);
+
+names!(
+    MirrorMode,
+    "mirror mode",
+    [mirror_mode_names, mirror_mode_to_string, mirror_mode_from_string],
+    {
+        Off => "off",
+        Vertical => "vertical",
+        Horizontal => "horizontal",
+        FourFold => "fourFold",
+        EightFold => "eightFold",
+    }
+);
+
+names!(
+    PatternFit,
+    "pattern fit",
+    [pattern_fit_names, pattern_fit_to_string, pattern_fit_from_string],
+    {
+        Wrap => "wrap",
+        Crop => "crop",
+        Centre => "centre",
+        Reject => "reject",
+    }
+);
+
+names!(
+    RuleMismatch,
+    "rule mismatch",
+    [rule_mismatch_names, rule_mismatch_to_string, rule_mismatch_from_string],
+    {
+        Ignore => "ignore",
+        Warn => "warn",
+        Adopt => "adopt",
+    }
+);
+
+names!(
+    HeightSource,
+    "height source",
+    [height_source_names, height_source_to_string, height_source_from_string],
+    {
+        Age => "age",
+        Activity => "activity",
+    }
+);
+
+names!(
+    LogLevel,
+    "log level",
+    [log_level_names, log_level_to_string, log_level_from_string],
+    {
+        Off => "off",
+        Error => "error",
+        Warn => "warn",
+        Info => "info",
+        Debug => "debug",
+        Trace => "trace",
+    }
+);
+
+names!(
+    Material,
+    "material",
+    [material_names, material_to_string, material_from_string],
+    {
+        Empty => "empty",
+        Sand => "sand",
+        Water => "water",
+        Wall => "wall",
+    }
+);
+
+/// Written as the top and bottom boundary then the left and right
+/// boundary, such as `wrap/absorbing`. A single boundary such as `wrap`
+/// is read as the same boundary on every edge.
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.top_bottom, self.left_right)
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Topology, String> {
+        let (top_bottom, left_right) = name.split_once('/').unwrap_or((name, name));
+        Ok(Topology {
+            top_bottom: top_bottom.parse()?,
+            left_right: left_right.parse()?,
+        })
+    }
+}
+
+#[wasm_bindgen]
+pub fn topology_to_string(topology: &Topology) -> String {
+    topology.to_string()
+}
+
+#[wasm_bindgen]
+pub fn topology_from_string(name: &str) -> Result<Topology, String> {
+    name.parse()
+}
@@ -0,0 +1,55 @@
+use crate::{CellStorage, Universe};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+impl Universe {
+    /// Pointer to a byte per cell holding the number of live neighbours of
+    /// each live cell, and 0 for dead cells, laid out in the same way as the
+    /// multi-state `cells` buffer. Renderers can use it to colour crowded
+    /// regions differently from stable ones.
+    ///
+    /// The counts are worked out when this is called, and the buffer is
+    /// kept until `free_neighbor_counts`.
+    pub fn neighbor_counts(&mut self) -> *const u8 {
+        let mut counts = self
+            .neighbor_counts
+            .take()
+            .unwrap_or_else(|| vec![0; self.cells.len()]);
+        counts.resize(self.cells.len(), 0);
+        let stride = (self.width + 2) as usize;
+        // Count walls and wrapped cells in the same way as ticking does.
+        self.mark_walls();
+        self.refresh_halo();
+        for row in 1..=self.height as usize {
+            for col in 1..=self.width as usize {
+                let idx = row * stride + col;
+                counts[idx] = if self.cells.is_alive(idx) && !self.is_wall(idx) {
+                    [
+                        idx - stride - 1,
+                        idx - stride,
+                        idx - stride + 1,
+                        idx - 1,
+                        idx + 1,
+                        idx + stride - 1,
+                        idx + stride,
+                        idx + stride + 1,
+                    ]
+                    .iter()
+                    .filter(|&&neighbor| self.cells.is_alive(neighbor))
+                    .count() as u8
+                } else {
+                    0
+                };
+            }
+        }
+        self.clear_halo();
+        self.unmark_walls();
+        self.neighbor_counts = Some(counts);
+        self.check_memory_growth();
+        self.neighbor_counts.as_ref().unwrap().as_ptr()
+    }
+
+    pub fn free_neighbor_counts(&mut self) {
+        self.neighbor_counts = None;
+    }
+}
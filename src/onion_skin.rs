@@ -0,0 +1,64 @@
+use crate::{CellStorage, Cells, Universe};
+use wasm_bindgen::prelude::*;
+
+impl Universe {
+    /// Whether each cell is alive, one byte per cell in row-major order.
+    fn alive_bytes(&self, cells: &Cells) -> Vec<u8> {
+        let mut alive = Vec::with_capacity((self.width * self.height) as usize);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                alive.push(cells.is_alive(self.get_index(row, col)) as u8);
+            }
+        }
+        alive
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// The cells as they were `k` generations ago, with one byte per cell in
+    /// row-major order which is 1 for live cells and 0 for dead ones. Layer 0
+    /// is the current generation.
+    ///
+    /// Older layers come from the history kept by `set_history_budget`, and
+    /// the result is empty if the history doesn't go back that far.
+    pub fn history_layer(&self, k: u32) -> Vec<u8> {
+        if k == 0 {
+            return self.alive_bytes(&self.cells);
+        }
+        let recent = self
+            .history
+            .as_ref()
+            .map_or(Vec::new(), |history| history.recent(k as usize));
+        match recent.get(k as usize - 1) {
+            Some(packed) => self.alive_bytes(&self.unpack_cells(packed)),
+            None => Vec::new(),
+        }
+    }
+
+    /// Stack up to `layers` previous generations into one alpha value per
+    /// cell in row-major order, for drawing fading trails behind moving
+    /// objects. A cell which was alive one generation ago has the highest
+    /// alpha, fading to 0 for cells which haven't been alive in any of the
+    /// layers.
+    pub fn onion_skin(&self, layers: u32) -> Vec<u8> {
+        let mut alpha = vec![0; (self.width * self.height) as usize];
+        let recent = self
+            .history
+            .as_ref()
+            .map_or(Vec::new(), |history| history.recent(layers as usize));
+        // Older layers are drawn first so newer ones cover them.
+        for (k, packed) in recent.iter().enumerate().rev() {
+            let value = (255 * (layers as usize - k) / (layers as usize + 1)) as u8;
+            for (alpha, alive) in alpha
+                .iter_mut()
+                .zip(self.alive_bytes(&self.unpack_cells(packed)))
+            {
+                if alive == 1 {
+                    *alpha = value;
+                }
+            }
+        }
+        alpha
+    }
+}
@@ -0,0 +1,236 @@
+use crate::rule::Rule;
+use crate::{Cell, CellStorage, Universe, UniverseError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use wasm_bindgen::prelude::*;
+
+/// Number of operations seen from each client, counting only those with
+/// none missing before them.
+type Clock = BTreeMap<u32, u64>;
+
+/// An edit to a shared board, tagged with the clock of the client which
+/// made it so that every client orders concurrent edits the same way.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Op {
+    client: u32,
+    /// The client's clock when making the edit, counting the edit itself.
+    clock: Clock,
+    #[serde(flatten)]
+    edit: Edit,
+}
+
+/// What an op changes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Edit {
+    /// Set the state of a cell.
+    Cell { row: u32, col: u32, state: u8 },
+    /// Switch to another rule, in B/S notation.
+    Rule { rule: String },
+}
+
+impl Op {
+    fn counter(&self) -> u64 {
+        self.clock.get(&self.client).copied().unwrap_or(0)
+    }
+
+    /// A key which puts every op after the ops it has seen, and breaks ties
+    /// between concurrent ops by client.
+    fn order(&self) -> (u64, u32, u64) {
+        (self.clock.values().sum(), self.client, self.counter())
+    }
+}
+
+/// The log of edits made by this client and the others editing the same
+/// board.
+pub(crate) struct OpLog {
+    client: u32,
+    clock: Clock,
+    /// Every op seen, in order.
+    ops: Vec<Op>,
+    seen: BTreeSet<(u32, u64)>,
+}
+
+impl OpLog {
+    /// Add an edit made by this client to the log.
+    fn record(&mut self, edit: Edit) -> Op {
+        *self.clock.entry(self.client).or_insert(0) += 1;
+        let op = Op {
+            client: self.client,
+            clock: self.clock.clone(),
+            edit,
+        };
+        self.seen.insert((op.client, op.counter()));
+        self.ops.push(op.clone());
+        op
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.ops.capacity() * std::mem::size_of::<Op>()
+            + self
+                .ops
+                .iter()
+                .map(|op| op.clock.len() * std::mem::size_of::<(u32, u64)>())
+                .sum::<usize>()
+    }
+}
+
+impl Universe {
+    /// Log a change of rule made by this client, if there is an op log.
+    pub(crate) fn log_rule(&mut self, rule: Rule) {
+        if let Some(log) = &mut self.op_log {
+            log.record(Edit::Rule {
+                rule: rule.to_string(),
+            });
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start logging edits for a board shared with other clients, as the
+    /// client with an id which is unique among them.
+    pub fn start_op_log(&mut self, client: u32) {
+        self.op_log = Some(OpLog {
+            client,
+            clock: Clock::new(),
+            ops: Vec::new(),
+            seen: BTreeSet::new(),
+        });
+    }
+
+    pub fn stop_op_log(&mut self) {
+        self.op_log = None;
+    }
+
+    /// Set the state of a cell as an edit to send to the other clients,
+    /// returning the edit as JSON to pass to their `merge_ops`.
+    pub fn log_cell_state(
+        &mut self,
+        row: u32,
+        col: u32,
+        state: u8,
+    ) -> Result<String, UniverseError> {
+        let idx = self.checked_index("log_cell_state", row, col)?;
+        let args = || format!("row={}, col={}, state={}", row, col, state);
+        if state as u32 >= self.rule.states() {
+            let message = format!(
+                "state {} is beyond the {} states of the rule",
+                state,
+                self.rule.states()
+            );
+            return Err(self.error("log_cell_state", args(), message));
+        }
+        let log = match &mut self.op_log {
+            Some(log) => log,
+            None => return Err(self.error("log_cell_state", args(), "there is no op log")),
+        };
+        let op = log.record(Edit::Cell { row, col, state });
+        self.cells.set(idx, Cell(state));
+        self.cells_edited();
+        Ok(serde_json::to_string(&[op]).unwrap())
+    }
+
+    /// Merge edits from other clients, given as JSON from `log_cell_state`
+    /// or `ops_since`. Edits can arrive more than once and in any order:
+    /// once every client has seen the same edits, their boards and rules
+    /// match. Returns the number of new edits.
+    pub fn merge_ops(&mut self, json: &str) -> Result<u32, UniverseError> {
+        trace_span!("deserialize");
+        let args = || format!("{} bytes of JSON", json.len());
+        let ops: Vec<Op> = serde_json::from_str(json)
+            .map_err(|err| self.error("merge_ops", args(), err.to_string()))?;
+        for op in ops.iter() {
+            let message = match &op.edit {
+                &Edit::Cell { row, col, .. } if row >= self.height || col >= self.width => {
+                    format!(
+                        "an edit at row={}, col={} is outside the universe",
+                        row, col
+                    )
+                }
+                &Edit::Cell { state, .. } if state as u32 >= self.rule.states() => {
+                    format!(
+                        "an edit to state {} is beyond the {} states of the rule",
+                        state,
+                        self.rule.states()
+                    )
+                }
+                Edit::Rule { rule } if !Rule::parse(rule).is_some_and(Rule::is_supported) => {
+                    format!("the rule {} is not supported", rule)
+                }
+                _ => continue,
+            };
+            return Err(self.error("merge_ops", args(), message));
+        }
+        let mut log = match self.op_log.take() {
+            Some(log) => log,
+            None => return Err(self.error("merge_ops", args(), "there is no op log")),
+        };
+        let mut touched = BTreeSet::new();
+        let mut rule_changed = false;
+        let mut added = 0;
+        for op in ops {
+            if log.seen.insert((op.client, op.counter())) {
+                let count = log.clock.entry(op.client).or_insert(0);
+                while log.seen.contains(&(op.client, *count + 1)) {
+                    *count += 1;
+                }
+                match op.edit {
+                    Edit::Cell { row, col, .. } => {
+                        touched.insert(self.get_index(row, col));
+                    }
+                    Edit::Rule { .. } => rule_changed = true,
+                }
+                log.ops.push(op);
+                added += 1;
+            }
+        }
+        // The rule and each cell the new ops touch take their values from
+        // their last edit in the shared order.
+        log.ops.sort_by_key(Op::order);
+        let mut rule = None;
+        for op in log.ops.iter() {
+            match &op.edit {
+                &Edit::Cell { row, col, state } => {
+                    let idx = self.get_index(row, col);
+                    if touched.contains(&idx) {
+                        self.cells.set(idx, Cell(state));
+                    }
+                }
+                Edit::Rule { rule: edit } => rule = Rule::parse(edit),
+            }
+        }
+        if let (true, Some(rule)) = (rule_changed, rule) {
+            self.apply_rule(rule);
+        }
+        self.cells_edited();
+        self.op_log = Some(log);
+        Ok(added)
+    }
+
+    /// This client's clock as JSON, for other clients to pass to
+    /// `ops_since` when catching up after a dropped connection.
+    pub fn op_clock(&self) -> String {
+        let clock = self.op_log.as_ref().map(|log| log.clock.clone());
+        serde_json::to_string(&clock.unwrap_or_default()).unwrap()
+    }
+
+    /// The edits which a client with a clock from `op_clock` hasn't seen,
+    /// as JSON for its `merge_ops`.
+    pub fn ops_since(&self, clock: &str) -> Result<String, UniverseError> {
+        let clock: Clock = serde_json::from_str(clock).map_err(|err| {
+            self.error(
+                "ops_since",
+                format!("{} bytes of JSON", clock.len()),
+                err.to_string(),
+            )
+        })?;
+        let ops: Vec<&Op> = self.op_log.as_ref().map_or(Vec::new(), |log| {
+            log.ops
+                .iter()
+                .filter(|op| op.counter() > clock.get(&op.client).copied().unwrap_or(0))
+                .collect()
+        });
+        Ok(serde_json::to_string(&ops).unwrap())
+    }
+}
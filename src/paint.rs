@@ -0,0 +1,280 @@
+use crate::{Cell, CellStorage, Universe, UniverseError};
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BrushShape {
+    Square,
+    Circle,
+}
+
+/// How painting is mirrored about the centre of the universe.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MirrorMode {
+    Off,
+    /// Reflection in the vertical axis (left to right).
+    Vertical,
+    /// Reflection in the horizontal axis (top to bottom).
+    Horizontal,
+    /// Reflection in both axes.
+    FourFold,
+    /// Reflection in both axes and both diagonals.
+    EightFold,
+}
+
+/// The area affected by painting around the cell under the cursor.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Brush {
+    radius: u32,
+    shape: BrushShape,
+}
+
+impl Default for Brush {
+    fn default() -> Brush {
+        Brush {
+            radius: 0,
+            shape: BrushShape::Square,
+        }
+    }
+}
+
+impl Brush {
+    /// Offsets of the cells covered by the brush from its centre.
+    pub(crate) fn offsets(self) -> Vec<(i32, i32)> {
+        let radius = self.radius as i32;
+        let mut offsets = Vec::new();
+        for d_row in -radius..=radius {
+            for d_col in -radius..=radius {
+                // Allowing an extra `radius` gives rounder looking circles
+                // than a strict distance check.
+                if self.shape == BrushShape::Circle
+                    && d_row * d_row + d_col * d_col > radius * radius + radius
+                {
+                    continue;
+                }
+                offsets.push((d_row, d_col));
+            }
+        }
+        offsets
+    }
+}
+
+impl Universe {
+    /// The cells which are images of a cell under a mirror mode, including
+    /// the cell itself, wrapping around the edges.
+    pub(crate) fn mirror_images(&self, row: i32, col: i32, mode: MirrorMode) -> Vec<(u32, u32)> {
+        // Work in doubled coordinates relative to the centre of the universe
+        // so that the centre is always a whole number.
+        let (row_sum, col_sum) = (self.height as i32 - 1, self.width as i32 - 1);
+        let (r, c) = (2 * row - row_sum, 2 * col - col_sum);
+        let mut images = vec![(r, c)];
+        match mode {
+            MirrorMode::Off => {}
+            MirrorMode::Vertical => images.push((r, -c)),
+            MirrorMode::Horizontal => images.push((-r, c)),
+            MirrorMode::FourFold => images.extend(&[(r, -c), (-r, c), (-r, -c)]),
+            MirrorMode::EightFold => images.extend(&[
+                (r, -c),
+                (-r, c),
+                (-r, -c),
+                (c, r),
+                (c, -r),
+                (-c, r),
+                (-c, -r),
+            ]),
+        }
+        images
+            .into_iter()
+            .map(|(r, c)| (r + row_sum, c + col_sum))
+            // Reflecting in a diagonal of a universe whose width and height
+            // differ in parity lands between cells, so those are skipped.
+            .filter(|&(r, c)| r % 2 == 0 && c % 2 == 0)
+            .map(|(r, c)| {
+                (
+                    (r / 2).rem_euclid(self.height as i32) as u32,
+                    (c / 2).rem_euclid(self.width as i32) as u32,
+                )
+            })
+            .collect()
+    }
+
+    /// Set a cell, wrapping around the edges, along with its images under
+    /// the mirror mode.
+    fn paint_cell(&mut self, row: i32, col: i32, cell: Cell) {
+        for (row, col) in self.mirror_images(row, col, self.mirror) {
+            let idx = self.get_index(row, col);
+            if !self.is_wall(idx) {
+                self.cells.set(idx, cell);
+            }
+        }
+        self.cells_edited();
+    }
+
+    /// Set every cell under the brush centred on a cell.
+    pub(crate) fn apply_brush(&mut self, row: i32, col: i32, cell: Cell) {
+        for (d_row, d_col) in self.brush.offsets() {
+            self.paint_cell(row + d_row, col + d_col, cell);
+        }
+    }
+
+    /// Set every cell in a rectangle, clipped to the edges of the universe.
+    pub(crate) fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, cell: Cell) {
+        for row in y.min(self.height)..y.saturating_add(h).min(self.height) {
+            for col in x.min(self.width)..x.saturating_add(w).min(self.width) {
+                self.paint_cell(row as i32, col as i32, cell);
+            }
+        }
+    }
+
+    /// Apply the brush at every cell on the line between two cells.
+    pub(crate) fn apply_brush_line(&mut self, from: (i32, i32), to: (i32, i32), cell: Cell) {
+        for (row, col) in line_cells(from, to) {
+            self.apply_brush(row, col, cell);
+        }
+    }
+}
+
+/// The cells on a straight line between two cells, using Bresenham's
+/// algorithm so there are no gaps.
+pub(crate) fn line_cells(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let (mut row, mut col) = from;
+    let d_row = (to.0 - row).abs();
+    let d_col = -(to.1 - col).abs();
+    let step_row = if row < to.0 { 1 } else { -1 };
+    let step_col = if col < to.1 { 1 } else { -1 };
+    let mut error = d_row + d_col;
+    let mut cells = Vec::new();
+    loop {
+        cells.push((row, col));
+        if (row, col) == to {
+            return cells;
+        }
+        let doubled = 2 * error;
+        if doubled >= d_col {
+            error += d_col;
+            row += step_row;
+        }
+        if doubled <= d_row {
+            error += d_row;
+            col += step_col;
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Set the brush used for painting and erasing. A radius of 0 affects a
+    /// single cell.
+    pub fn set_brush(&mut self, radius: u32, shape: BrushShape) {
+        self.brush = Brush { radius, shape };
+    }
+
+    pub fn brush_radius(&self) -> u32 {
+        self.brush.radius
+    }
+
+    pub fn brush_shape(&self) -> BrushShape {
+        self.brush.shape
+    }
+
+    /// Mirror every painting and erasing operation about the centre of the
+    /// universe, for drawing symmetric patterns.
+    pub fn set_mirror_mode(&mut self, mode: MirrorMode) {
+        self.mirror = mode;
+    }
+
+    pub fn mirror_mode(&self) -> MirrorMode {
+        self.mirror
+    }
+
+    /// Make the cells under the brush centred on a cell alive.
+    pub fn paint_at(&mut self, row: u32, col: u32) {
+        self.apply_brush(row as i32, col as i32, Cell::ALIVE);
+    }
+
+    /// Make the cells under the brush alive at every point along a line, for
+    /// painting while the pointer is dragged between two cells.
+    pub fn paint_line(&mut self, from_row: u32, from_col: u32, to_row: u32, to_col: u32) {
+        self.apply_brush_line(
+            (from_row as i32, from_col as i32),
+            (to_row as i32, to_col as i32),
+            Cell::ALIVE,
+        );
+    }
+
+    /// Kill the cells under the brush centred on a cell.
+    pub fn erase_at(&mut self, row: u32, col: u32) {
+        self.apply_brush(row as i32, col as i32, Cell::DEAD);
+    }
+
+    /// Kill the cells under the brush at every point along a line. Unlike
+    /// toggling, this reliably clears cells when dragging over a mixture of
+    /// live and dead cells.
+    pub fn erase_line(&mut self, from_row: u32, from_col: u32, to_row: u32, to_col: u32) {
+        self.apply_brush_line(
+            (from_row as i32, from_col as i32),
+            (to_row as i32, to_col as i32),
+            Cell::DEAD,
+        );
+    }
+
+    /// Kill every cell in the `w` by `h` rectangle with its top-left corner
+    /// at column `x` and row `y`.
+    pub fn erase_region(&mut self, x: u32, y: u32, w: u32, h: u32) {
+        self.fill_rect(x, y, w, h, Cell::DEAD);
+    }
+
+    /// Make a random fraction `density` of the cells within `radius` of a
+    /// cell alive, like an airbrush. Uses the universe's seeded random
+    /// number generator, so sprays are repeatable after `set_seed`.
+    pub fn spray(&mut self, row: u32, col: u32, radius: u32, density: f64) {
+        let brush = Brush {
+            radius,
+            shape: BrushShape::Circle,
+        };
+        for (d_row, d_col) in brush.offsets() {
+            if self.rng.next_f64() < density {
+                self.paint_cell(row as i32 + d_row, col as i32 + d_col, Cell::ALIVE);
+            }
+        }
+    }
+
+    /// Set a row from an array of numbers, such as an audio waveform or
+    /// sensor readings, stretched or squeezed to the width of the universe.
+    /// Each cell takes the largest of the samples falling within it, and is
+    /// alive if that is at least `threshold`.
+    pub fn seed_row_from_samples(
+        &mut self,
+        row: u32,
+        samples: &[f32],
+        threshold: f32,
+    ) -> Result<(), UniverseError> {
+        let args = || format!("row={}, {} samples", row, samples.len());
+        if row >= self.height {
+            return Err(self.error(
+                "seed_row_from_samples",
+                args(),
+                "the row is outside the universe",
+            ));
+        }
+        if samples.is_empty() {
+            return Err(self.error("seed_row_from_samples", args(), "there are no samples"));
+        }
+        let (count, width) = (samples.len(), self.width as usize);
+        for col in 0..width {
+            let start = col * count / width;
+            let end = ((col + 1) * count / width).max(start + 1);
+            let peak = samples[start..end]
+                .iter()
+                .cloned()
+                .fold(f32::NEG_INFINITY, f32::max);
+            let idx = self.get_index(row, col as u32);
+            self.cells.set(idx, (peak >= threshold).into());
+        }
+        self.cells_edited();
+        Ok(())
+    }
+}
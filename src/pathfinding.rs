@@ -0,0 +1,134 @@
+//! Finding a way between two cells through the dead cells of the universe,
+//! which turns the corridors grown by the maze rules into a puzzle.
+//!
+//! The search is A* over the dead cells which aren't walls, stepping to the
+//! four cells which share an edge. Paths follow the boundaries, so with
+//! wrapping edges a path can leave one side and come back on the other.
+
+use crate::{Boundary, CellStorage, Universe, UniverseError};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use wasm_bindgen::prelude::*;
+
+/// Distance between two positions along an axis of `size` cells, taking the
+/// short way round if the axis wraps.
+fn axis_distance(from: u32, to: u32, size: u32, boundary: Boundary) -> u32 {
+    let distance = from.abs_diff(to);
+    if boundary == Boundary::Wrap {
+        distance.min(size - distance)
+    } else {
+        distance
+    }
+}
+
+impl Universe {
+    /// Whether a path can pass through a cell.
+    fn is_open(&self, row: u32, col: u32) -> bool {
+        let idx = self.get_index(row, col);
+        !self.cells.is_alive(idx) && !self.is_wall(idx)
+    }
+
+    /// The cells a path can step to from a cell.
+    fn open_neighbors(&self, row: u32, col: u32) -> Vec<(u32, u32)> {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .filter_map(|&(d_row, d_col)| {
+                let row = self.row_boundary.resolve(row as i32 + d_row, self.height)?;
+                let col = self.col_boundary.resolve(col as i32 + d_col, self.width)?;
+                Some((row, col))
+            })
+            .filter(|&(neighbor_row, neighbor_col)| {
+                (neighbor_row, neighbor_col) != (row, col)
+                    && self.is_open(neighbor_row, neighbor_col)
+            })
+            .collect()
+    }
+
+    /// The fewest steps from one cell to another, which A* uses to decide
+    /// which cells to try first.
+    fn steps_between(&self, from: (u32, u32), to: (u32, u32)) -> u32 {
+        axis_distance(from.0, to.0, self.height, self.row_boundary)
+            + axis_distance(from.1, to.1, self.width, self.col_boundary)
+    }
+
+    /// A shortest path between two open cells, including both ends, or
+    /// `None` if they aren't connected.
+    fn shortest_path(&self, from: (u32, u32), to: (u32, u32)) -> Option<Vec<(u32, u32)>> {
+        let index = |(row, col): (u32, u32)| (row * self.width + col) as usize;
+        let size = (self.width * self.height) as usize;
+        let mut steps = vec![u32::MAX; size];
+        let mut came_from = vec![None; size];
+        let mut open = BinaryHeap::new();
+        steps[index(from)] = 0;
+        open.push(Reverse((self.steps_between(from, to), 0, from)));
+        while let Some(Reverse((_, steps_so_far, cell))) = open.pop() {
+            if cell == to {
+                let mut path = vec![to];
+                while let Some(previous) = came_from[index(path[path.len() - 1])] {
+                    path.push(previous);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            // A cell can be queued again after a shorter way to it is found,
+            // leaving the longer entry to be skipped here.
+            if steps_so_far > steps[index(cell)] {
+                continue;
+            }
+            for neighbor in self.open_neighbors(cell.0, cell.1) {
+                let neighbor_steps = steps_so_far + 1;
+                if neighbor_steps < steps[index(neighbor)] {
+                    steps[index(neighbor)] = neighbor_steps;
+                    came_from[index(neighbor)] = Some(cell);
+                    let estimate = neighbor_steps + self.steps_between(neighbor, to);
+                    open.push(Reverse((estimate, neighbor_steps, neighbor)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Find a shortest path from one dead cell to another, moving between
+    /// cells which share an edge and never through live cells or walls. The
+    /// path is kept as an overlay for drawing, read with `path`.
+    ///
+    /// Returns the number of steps in the path, or `None` if there is no way
+    /// through, which also clears the overlay. Both ends must lie within the
+    /// universe and be dead. The path isn't updated as the universe ticks,
+    /// so find it again after changing the cells.
+    pub fn find_path(
+        &mut self,
+        from_row: u32,
+        from_col: u32,
+        to_row: u32,
+        to_col: u32,
+    ) -> Result<Option<u32>, UniverseError> {
+        for &(row, col) in [(from_row, from_col), (to_row, to_col)].iter() {
+            self.checked_index("find_path", row, col)?;
+            if !self.is_open(row, col) {
+                return Err(self.error(
+                    "find_path",
+                    format!("row={}, col={}", row, col),
+                    "the path can't start or end on a live cell or wall",
+                ));
+            }
+        }
+        self.path = self.shortest_path((from_row, from_col), (to_row, to_col));
+        Ok(self.path.as_ref().map(|path| path.len() as u32 - 1))
+    }
+
+    /// The cells of the path from `find_path` as `[row, col, row, col,
+    /// ...]`, from start to end, or empty if there is none.
+    pub fn path(&self) -> Vec<u32> {
+        self.path.as_ref().map_or(Vec::new(), |path| {
+            path.iter().flat_map(|&(row, col)| vec![row, col]).collect()
+        })
+    }
+
+    pub fn clear_path(&mut self) {
+        self.path = None;
+    }
+}
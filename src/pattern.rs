@@ -0,0 +1,875 @@
+use crate::errors::MAX_CELLS;
+use crate::rule::Rule;
+use crate::versioning::{self, Format};
+use crate::{LogLevel, Universe, UniverseError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+/// A pattern parsed from text, which can be inspected before being inserted
+/// into a universe.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pattern {
+    width: u32,
+    height: u32,
+    /// The rule given by the pattern's file, such as `B3/S23`.
+    rule: Option<String>,
+    /// The `(row, col)` of each live cell from the top-left corner.
+    cells: Vec<(u32, u32)>,
+    /// Where the pattern came from, read from the file's comment lines.
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    comments: Vec<String>,
+}
+
+/// What to do with the parts of a pattern which don't fit in the universe
+/// when placing it.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternFit {
+    /// Cells beyond an edge wrap around to the opposite edge if the edges
+    /// are joined, and are left out otherwise.
+    Wrap,
+    /// Cells beyond an edge are left out.
+    Crop,
+    /// The pattern is centred on the universe, ignoring the position it was
+    /// placed at, and cells beyond the edges are left out.
+    Centre,
+    /// Placing the pattern fails unless it fits within the edges.
+    Reject,
+}
+
+/// What to do when a pattern names a different rule to the universe's.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleMismatch {
+    /// Place the pattern under the universe's rule without saying anything.
+    Ignore,
+    /// Place the pattern under the universe's rule and log a warning.
+    Warn,
+    /// Switch the universe to the pattern's rule, failing if the universe
+    /// can't run it.
+    Adopt,
+}
+
+impl Pattern {
+    /// Build a pattern from live cells at any positions, shifted so the
+    /// bounding box starts at the top-left corner, or fail if the bounding
+    /// box covers more than `MAX_CELLS`.
+    pub(crate) fn from_cells(
+        cells: &[(i32, i32)],
+        rule: Option<String>,
+    ) -> Result<Pattern, String> {
+        let min_row = cells.iter().map(|&(row, _)| row as i64).min().unwrap_or(0);
+        let max_row = cells.iter().map(|&(row, _)| row as i64).max().unwrap_or(-1);
+        let min_col = cells.iter().map(|&(_, col)| col as i64).min().unwrap_or(0);
+        let max_col = cells.iter().map(|&(_, col)| col as i64).max().unwrap_or(-1);
+        let (width, height) = (max_col - min_col + 1, max_row - min_row + 1);
+        if width
+            .checked_mul(height)
+            .is_none_or(|size| size > MAX_CELLS as i64)
+        {
+            return Err(format!("the pattern covers more than {} cells", MAX_CELLS));
+        }
+        let mut cells: Vec<_> = cells
+            .iter()
+            .map(|&(row, col)| ((row as i64 - min_row) as u32, (col as i64 - min_col) as u32))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        Ok(Pattern {
+            width: width as u32,
+            height: height as u32,
+            rule,
+            cells,
+            ..Pattern::default()
+        })
+    }
+
+    /// A pattern covering an area of a given size, which can be larger than
+    /// the live cells' bounding box.
+    pub(crate) fn with_size(
+        width: u32,
+        height: u32,
+        mut cells: Vec<(u32, u32)>,
+        rule: Option<String>,
+    ) -> Pattern {
+        cells.sort_unstable();
+        Pattern {
+            width,
+            height,
+            rule,
+            cells,
+            ..Pattern::default()
+        }
+    }
+
+    /// The pattern with the name, author and comments of another.
+    fn with_metadata_of(self, other: &Pattern) -> Pattern {
+        Pattern {
+            name: other.name.clone(),
+            author: other.author.clone(),
+            comments: other.comments.clone(),
+            ..self
+        }
+    }
+
+    /// Check that every cell of a pattern read from outside lies within its
+    /// size, which flipping and rotating rely on.
+    pub(crate) fn check_cells(&self) -> Result<(), String> {
+        match self
+            .cells
+            .iter()
+            .find(|&&(row, col)| row >= self.height || col >= self.width)
+        {
+            Some((row, col)) => Err(format!(
+                "the cell at row={}, col={} is outside the {}x{} pattern",
+                row, col, self.width, self.height
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Offsets of the live cells from the top-left corner, which is used as
+    /// the anchor when stamping.
+    pub(crate) fn offsets(&self) -> Vec<(i32, i32)> {
+        self.cells
+            .iter()
+            .map(|&(row, col)| (row as i32, col as i32))
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Pattern {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The rule named by the pattern, if any.
+    #[wasm_bindgen(getter)]
+    pub fn rule(&self) -> Option<String> {
+        self.rule.clone()
+    }
+
+    /// The live cells as `[row, col, row, col, ...]` from the top-left
+    /// corner.
+    #[wasm_bindgen(getter)]
+    pub fn cells(&self) -> Vec<u32> {
+        self.cells
+            .iter()
+            .flat_map(|&(row, col)| vec![row, col])
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> u32 {
+        self.cells.len() as u32
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn author(&self) -> Option<String> {
+        self.author.clone()
+    }
+
+    /// The lines of description and other comments from the file.
+    #[wasm_bindgen(getter)]
+    pub fn comments(&self) -> Vec<String> {
+        self.comments.clone()
+    }
+
+    /// The pattern turned clockwise by a number of quarter turns.
+    pub fn rotated(&self, quarter_turns: u8) -> Pattern {
+        let mut pattern = self.clone();
+        for _ in 0..quarter_turns % 4 {
+            pattern = Pattern::with_size(
+                pattern.height,
+                pattern.width,
+                pattern
+                    .cells
+                    .iter()
+                    .map(|&(row, col)| (col, pattern.height - 1 - row))
+                    .collect(),
+                pattern.rule.clone(),
+            )
+            .with_metadata_of(&pattern);
+        }
+        pattern
+    }
+
+    /// The pattern mirrored from left to right.
+    pub fn flipped(&self) -> Pattern {
+        Pattern::with_size(
+            self.width,
+            self.height,
+            self.cells
+                .iter()
+                .map(|&(row, col)| (row, self.width - 1 - col))
+                .collect(),
+            self.rule.clone(),
+        )
+        .with_metadata_of(self)
+    }
+
+    /// The pattern in RLE format, with its name, author and comments as
+    /// `#N`, `#O` and `#C` lines.
+    pub fn to_rle(&self) -> String {
+        let mut rle = String::new();
+        if let Some(name) = &self.name {
+            rle.push_str(&format!("#N {}\n", name));
+        }
+        if let Some(author) = &self.author {
+            rle.push_str(&format!("#O {}\n", author));
+        }
+        for comment in self.comments.iter() {
+            rle.push_str(&format!("#C {}\n", comment));
+        }
+        rle.push_str(&format!("x = {}, y = {}", self.width, self.height));
+        if let Some(rule) = &self.rule {
+            rle.push_str(&format!(", rule = {}", rule));
+        }
+        rle.push('\n');
+
+        // Runs of dead cells at the ends of rows are left out.
+        let mut items = Vec::new();
+        let (mut row, mut col) = (0, 0);
+        let mut push = |count: u32, tag: char| match items.last_mut() {
+            Some((run, last)) if *last == tag => *run += count,
+            _ => items.push((count, tag)),
+        };
+        for &(cell_row, cell_col) in self.cells.iter() {
+            if cell_row > row {
+                push(cell_row - row, '$');
+                row = cell_row;
+                col = 0;
+            }
+            if cell_col > col {
+                push(cell_col - col, 'b');
+            }
+            push(1, 'o');
+            col = cell_col + 1;
+        }
+        push(1, '!');
+
+        // Lines are kept to at most 70 characters.
+        let mut line = String::new();
+        for (count, tag) in items {
+            let item = if count == 1 {
+                tag.to_string()
+            } else {
+                format!("{}{}", count, tag)
+            };
+            if line.len() + item.len() > 70 {
+                rle.push_str(&line);
+                rle.push('\n');
+                line.clear();
+            }
+            line.push_str(&item);
+        }
+        rle.push_str(&line);
+        rle.push('\n');
+        rle
+    }
+
+    /// The pattern in MessagePack, with the same fields as the JSON made by
+    /// `export_patterns`, for a smaller payload.
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        trace_span!("serialize");
+        rmp_serde::to_vec_named(&versioning::wrap(Format::Pattern, self)).unwrap()
+    }
+}
+
+/// Read a pattern from MessagePack made by `Pattern::to_msgpack`, failing
+/// if it has cells outside its size.
+#[wasm_bindgen]
+pub fn pattern_from_msgpack(bytes: &[u8]) -> Result<Pattern, String> {
+    trace_span!("deserialize");
+    let document = rmp_serde::from_slice(bytes).map_err(|err| err.to_string())?;
+    let pattern: Pattern = versioning::unwrap(Format::Pattern, document)?;
+    pattern.check_cells()?;
+    Ok(pattern)
+}
+
+/// Read the name, author and comments from a pattern's comment lines:
+/// `#N`, `#O` and `#C` or `#D` lines, and for plaintext files `!Name:` and
+/// `!Author:` lines with any other `!` lines being comments.
+fn read_metadata(pattern: &mut Pattern, lines: &[&str], plaintext: bool) {
+    for line in lines {
+        let text = |prefix: &str| line[prefix.len()..].trim().to_string();
+        if plaintext && line.starts_with('!') {
+            if let Some(name) = line.strip_prefix("!Name:") {
+                pattern.name = Some(name.trim().to_string());
+            } else if let Some(author) = line.strip_prefix("!Author:") {
+                pattern.author = Some(author.trim().to_string());
+            } else {
+                pattern.comments.push(text("!"));
+            }
+        } else if line.starts_with("#N") && !text("#N").is_empty() {
+            // A bare `#N` in Life 1.05 marks the normal rule instead.
+            pattern.name = Some(text("#N"));
+        } else if line.starts_with("#O") {
+            pattern.author = Some(text("#O"));
+        } else if line.starts_with("#C") || line.starts_with("#c") || line.starts_with("#D") {
+            pattern.comments.push(text("#C"));
+        }
+    }
+}
+
+/// Parse a pattern in any of the common plain text formats, working out the
+/// format from the text itself:
+///
+/// - RLE, recognised by its `x = ...` header line.
+/// - Life 1.05, recognised by its `#Life 1.05` header line, with blocks of
+///   `.` and `*` rows placed by `#P x y` lines.
+/// - Life 1.06, recognised by its `#Life 1.06` header line.
+/// - Plaintext (`.cells`), with `!` comment lines, `.` for dead cells and `O`
+///   for live cells.
+/// - Grids of `0` and `1` or `.` and `O`/`*`, with `#` or `!` comment lines.
+///
+/// The name, author and comments are read from the comment lines of any of
+/// the formats. Lines can end with `\n`, `\r\n` or `\r`.
+#[wasm_bindgen]
+pub fn parse_pattern(text: &str) -> Result<Pattern, String> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let header = lines.iter().find(|line| !line.is_empty());
+    let (mut pattern, plaintext) = if header.is_some_and(|line| line.starts_with("#Life 1.05")) {
+        (parse_life_105(&lines)?, false)
+    } else if header.is_some_and(|line| line.starts_with("#Life 1.06")) {
+        (parse_life_106(&lines)?, false)
+    } else if lines.iter().any(|line| is_rle_header(line)) {
+        (parse_rle(&lines)?, false)
+    } else {
+        (parse_grid(&lines)?, true)
+    };
+    read_metadata(&mut pattern, &lines, plaintext);
+    Ok(pattern)
+}
+
+fn is_rle_header(line: &str) -> bool {
+    (line.starts_with('x') || line.starts_with('X')) && line[1..].trim_start().starts_with('=')
+}
+
+fn parse_life_105(lines: &[&str]) -> Result<Pattern, String> {
+    let mut rule = None;
+    let mut cells = Vec::new();
+    // The position of the top-left corner of the current block and the row
+    // within it.
+    let mut block = None;
+    for (number, line) in lines.iter().enumerate() {
+        if let Some(position) = line.strip_prefix("#P") {
+            let coordinates: Vec<i32> = position
+                .split_whitespace()
+                .map(|word| word.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| format!("invalid block position on line {}", number + 1))?;
+            match coordinates[..] {
+                [x, y] => block = Some((y, x, 0)),
+                _ => return Err(format!("expected two coordinates on line {}", number + 1)),
+            }
+            continue;
+        }
+        if line.starts_with("#N") {
+            rule = Some("B3/S23".to_string());
+            continue;
+        }
+        // Life 1.05 gives rules as survival counts then birth counts.
+        if let Some(counts) = line.strip_prefix("#R") {
+            let mut parts = counts.trim().splitn(2, '/');
+            let survive = parts.next().unwrap_or("");
+            let birth = parts.next().unwrap_or("");
+            rule = Some(format!("B{}/S{}", birth, survive));
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (top, left, row) = match &mut block {
+            Some((top, left, row)) => {
+                *row += 1;
+                (*top, *left, *row - 1)
+            }
+            None => return Err(format!("cells before any #P line on line {}", number + 1)),
+        };
+        for (col, ch) in line.chars().enumerate() {
+            match ch {
+                '.' => {}
+                '*' => match (top.checked_add(row), left.checked_add(col as i32)) {
+                    (Some(row), Some(col)) => cells.push((row, col)),
+                    _ => return Err(format!("the block is too far out on line {}", number + 1)),
+                },
+                ch => return Err(format!("unexpected `{}` on line {}", ch, number + 1)),
+            }
+        }
+    }
+    Pattern::from_cells(&cells, rule)
+}
+
+fn parse_life_106(lines: &[&str]) -> Result<Pattern, String> {
+    let mut cells = Vec::new();
+    for (number, line) in lines.iter().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let coordinates: Vec<i32> = line
+            .split_whitespace()
+            .map(|word| word.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("invalid coordinates on line {}", number + 1))?;
+        match coordinates[..] {
+            [x, y] => cells.push((y, x)),
+            _ => return Err(format!("expected two coordinates on line {}", number + 1)),
+        }
+    }
+    Pattern::from_cells(&cells, None)
+}
+
+/// The largest width or height of a pattern read from RLE, and so of any
+/// run in it, which keeps huge run counts from overflowing. The whole
+/// pattern is also kept within `MAX_CELLS` so that it fits in memory.
+const MAX_RLE_SIZE: u32 = 1 << 16;
+
+fn parse_rle(lines: &[&str]) -> Result<Pattern, String> {
+    let mut width = 0;
+    let mut height = 0;
+    let mut rule = None;
+    let mut cells = Vec::new();
+    let (mut row, mut col) = (0, 0);
+    let mut run: u32 = 0;
+    // One past the rightmost live cell so far.
+    let mut right = 0;
+    'lines: for (number, line) in lines.iter().enumerate() {
+        let too_large = || {
+            format!(
+                "the pattern is larger than {0}x{0} on line {1}",
+                MAX_RLE_SIZE,
+                number + 1
+            )
+        };
+        let too_many = || {
+            format!(
+                "the pattern covers more than {} cells on line {}",
+                MAX_CELLS,
+                number + 1
+            )
+        };
+        if let Some(comment) = line.strip_prefix("#r") {
+            rule = Some(comment.trim().to_string());
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        if is_rle_header(line) {
+            // The rule can itself contain commas, as in `B3/S23:T10,10`, so
+            // it takes the rest of the line.
+            let (size, rule_field) = match line.find("rule") {
+                Some(start) => line.split_at(start),
+                None => (*line, ""),
+            };
+            for field in size.split(',') {
+                let mut parts = field.splitn(2, '=').map(str::trim);
+                let (key, value) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+                let invalid = || format!("invalid header field `{}`", field.trim());
+                match key.to_ascii_lowercase().as_str() {
+                    "x" => width = value.parse().map_err(|_| invalid())?,
+                    "y" => height = value.parse().map_err(|_| invalid())?,
+                    _ => {}
+                }
+            }
+            if width > MAX_RLE_SIZE || height > MAX_RLE_SIZE {
+                return Err(too_large());
+            }
+            if width as u64 * height as u64 > MAX_CELLS {
+                return Err(too_many());
+            }
+            if let Some(value) = rule_field.split_once('=').map(|(_, value)| value) {
+                rule = Some(value.trim().to_string());
+            }
+            continue;
+        }
+        // Moves along a row or down the rows by a run, keeping within the
+        // largest pattern size.
+        let advance = |position: u32, count: u32| {
+            Some(position + count)
+                .filter(|&position| position <= MAX_RLE_SIZE)
+                .ok_or_else(too_large)
+        };
+        for ch in line.chars() {
+            let count = run.max(1);
+            match ch {
+                '0'..='9' => {
+                    run = run
+                        .checked_mul(10)
+                        .and_then(|run| run.checked_add(ch.to_digit(10).unwrap()))
+                        .filter(|&run| run <= MAX_RLE_SIZE)
+                        .ok_or_else(too_large)?;
+                    continue;
+                }
+                'b' | '.' => col = advance(col, count)?,
+                '$' => {
+                    row = advance(row, count)?;
+                    col = 0;
+                }
+                '!' => break 'lines,
+                'o' | 'A'..='X' => {
+                    let end = advance(col, count)?;
+                    if row >= MAX_RLE_SIZE {
+                        return Err(too_large());
+                    }
+                    // Rows up to this one, as wide as the widest so far.
+                    right = right.max(end);
+                    if (row as u64 + 1) * right.max(width) as u64 > MAX_CELLS {
+                        return Err(too_many());
+                    }
+                    cells.extend((col..end).map(|col| (row, col)));
+                    col = end;
+                }
+                ch if ch.is_whitespace() => {}
+                ch => return Err(format!("unexpected `{}` on line {}", ch, number + 1)),
+            }
+            run = 0;
+        }
+    }
+    // The header's size can include empty space around the cells.
+    let width = width.max(right);
+    let height = cells.last().map_or(0, |&(row, _)| row + 1).max(height);
+    if width as u64 * height as u64 > MAX_CELLS {
+        return Err(format!("the pattern covers more than {} cells", MAX_CELLS));
+    }
+    Ok(Pattern {
+        width,
+        height,
+        rule,
+        cells,
+        ..Pattern::default()
+    })
+}
+
+fn parse_grid(lines: &[&str]) -> Result<Pattern, String> {
+    let mut rows = Vec::new();
+    for (number, line) in lines.iter().enumerate() {
+        if line.starts_with('!') || line.starts_with('#') {
+            continue;
+        }
+        let mut row = Vec::new();
+        for ch in line.chars() {
+            match ch {
+                '.' | '0' => row.push(false),
+                'O' | 'o' | '*' | '1' => row.push(true),
+                ch if ch.is_whitespace() => {}
+                ch => return Err(format!("unexpected `{}` on line {}", ch, number + 1)),
+            }
+        }
+        rows.push(row);
+    }
+    while rows.first().is_some_and(Vec::is_empty) {
+        rows.remove(0);
+    }
+    while rows.last().is_some_and(Vec::is_empty) {
+        rows.pop();
+    }
+    let mut cells = Vec::new();
+    for (row, line) in rows.iter().enumerate() {
+        for (col, &alive) in line.iter().enumerate() {
+            if alive {
+                cells.push((row as u32, col as u32));
+            }
+        }
+    }
+    Ok(Pattern {
+        width: rows.iter().map(Vec::len).max().unwrap_or(0) as u32,
+        height: rows.len() as u32,
+        rule: None,
+        cells,
+        ..Pattern::default()
+    })
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Make the live cells of a pattern alive with the pattern's top-left
+    /// corner at a cell, wrapping around joined edges and leaving out cells
+    /// beyond the others.
+    pub fn insert_pattern(&mut self, pattern: &Pattern, row: u32, col: u32) {
+        let cells: Vec<_> = pattern
+            .offsets()
+            .iter()
+            .map(|&(d_row, d_col)| (row as i32 + d_row, col as i32 + d_col))
+            .collect();
+        self.set_cells(&cells);
+    }
+
+    /// Make the live cells of a pattern alive with the pattern's top-left
+    /// corner at a cell, like `insert_pattern` but with a choice of what to
+    /// do when the pattern is too big for where it is placed or names a
+    /// different rule. Returns the number of cells placed.
+    ///
+    /// Nothing is placed if this fails.
+    pub fn place_pattern(
+        &mut self,
+        pattern: &Pattern,
+        row: u32,
+        col: u32,
+        fit: PatternFit,
+        on_mismatch: RuleMismatch,
+    ) -> Result<u32, UniverseError> {
+        let args = || {
+            format!(
+                "{}x{} pattern, row={}, col={}",
+                pattern.width, pattern.height, row, col
+            )
+        };
+        let mut adopted = None;
+        if let Some(rule) = &pattern.rule {
+            let parsed = Rule::parse(rule);
+            match on_mismatch {
+                _ if parsed == Some(self.rule) => {}
+                RuleMismatch::Ignore => {}
+                RuleMismatch::Warn => log!(
+                    LogLevel::Warn,
+                    "pattern uses rule {} but the universe runs {}",
+                    rule,
+                    self.rule
+                ),
+                RuleMismatch::Adopt => match parsed {
+                    Some(parsed) if parsed.is_supported() => adopted = Some(parsed),
+                    _ => {
+                        return Err(self.error(
+                            "place_pattern",
+                            args(),
+                            format!("the pattern's rule {} is not supported", rule),
+                        ))
+                    }
+                },
+            }
+        }
+
+        let (top, left) = match fit {
+            PatternFit::Centre => (
+                (self.height as i32 - pattern.height as i32) / 2,
+                (self.width as i32 - pattern.width as i32) / 2,
+            ),
+            _ => (row as i32, col as i32),
+        };
+        let fits = |row: i32, col: i32| {
+            row >= 0 && col >= 0 && row < self.height as i32 && col < self.width as i32
+        };
+        let cells: Vec<_> = pattern
+            .offsets()
+            .iter()
+            .map(|&(d_row, d_col)| (top + d_row, left + d_col))
+            .filter(|&(row, col)| fit == PatternFit::Wrap || fits(row, col))
+            .collect();
+        if fit == PatternFit::Reject && cells.len() != pattern.cells.len() {
+            return Err(self.error(
+                "place_pattern",
+                args(),
+                "the pattern doesn't fit in the universe",
+            ));
+        }
+        if let Some(rule) = adopted {
+            self.apply_rule(rule);
+            self.log_rule(rule);
+        }
+        self.set_cells(&cells);
+        Ok(cells.len() as u32)
+    }
+
+    /// Place a pattern in RLE format, such as one copied from the LifeWiki,
+    /// with its top-left corner at a cell. `fit` chooses whether cells
+    /// beyond the edges wrap around or are cropped, or whether the pattern
+    /// is centred or rejected. A different rule in the pattern's header is
+    /// logged as a warning, leaving the universe's rule as it is. Returns
+    /// the number of cells placed.
+    pub fn import_rle(
+        &mut self,
+        rle: &str,
+        row: u32,
+        col: u32,
+        fit: PatternFit,
+    ) -> Result<u32, UniverseError> {
+        let args = || format!("{} bytes of RLE, row={}, col={}", rle.len(), row, col);
+        if !rle.lines().any(|line| is_rle_header(line.trim())) {
+            return Err(self.error(
+                "import_rle",
+                args(),
+                "there is no `x = .., y = ..` header line",
+            ));
+        }
+        let pattern = parse_pattern(rle).map_err(|err| self.error("import_rle", args(), err))?;
+        self.place_pattern(&pattern, row, col, fit, RuleMismatch::Warn)
+    }
+
+    /// The whole universe in RLE format, with its size and rule in the
+    /// header, so it can be shared and loaded back with `import_rle` at
+    /// row 0, column 0.
+    pub fn export_rle(&self) -> String {
+        trace_span!("serialize");
+        Pattern::with_size(
+            self.width,
+            self.height,
+            self.iter_live_cells().collect(),
+            Some(self.rule_string()),
+        )
+        .to_rle()
+    }
+
+    /// Add the current selection to the pattern library under a name,
+    /// replacing any pattern already saved with that name. Returns false if
+    /// there is no selection.
+    ///
+    /// Saved patterns can be placed with `stamp` like the built-in ones.
+    pub fn save_selection_as_pattern(&mut self, name: &str) -> bool {
+        match self.selection_pattern() {
+            Some(pattern) => {
+                self.patterns.insert(name.to_string(), pattern);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// A pattern saved in the library.
+    pub fn saved_pattern(&self, name: &str) -> Option<Pattern> {
+        self.patterns.get(name).cloned()
+    }
+
+    /// Names of the patterns in the library, in alphabetical order.
+    pub fn saved_pattern_names(&self) -> Vec<String> {
+        self.patterns.keys().cloned().collect()
+    }
+
+    pub fn remove_saved_pattern(&mut self, name: &str) -> bool {
+        self.patterns.remove(name).is_some()
+    }
+
+    /// The pattern library as JSON, with a `version` and a `patterns` object
+    /// mapping names to patterns.
+    pub fn export_patterns(&self) -> String {
+        trace_span!("serialize");
+        serde_json::to_string(&versioning::wrap(Format::Patterns, &self.patterns)).unwrap()
+    }
+
+    /// Add the patterns from JSON made by `export_patterns`, including by
+    /// older builds, to the library, replacing any with the same names.
+    /// Returns the number of patterns added, or fails without adding any if
+    /// a pattern has cells outside its size.
+    pub fn import_patterns(&mut self, json: &str) -> Result<u32, UniverseError> {
+        trace_span!("deserialize");
+        let context = format!("{} bytes of JSON", json.len());
+        let patterns: BTreeMap<String, Pattern> = serde_json::from_str(json)
+            .map_err(|err| err.to_string())
+            .and_then(|document| versioning::unwrap(Format::Patterns, document))
+            .and_then(|patterns: BTreeMap<String, Pattern>| {
+                for (name, pattern) in patterns.iter() {
+                    pattern
+                        .check_cells()
+                        .map_err(|err| format!("in the pattern {:?}, {}", name, err))?;
+                }
+                Ok(patterns)
+            })
+            .map_err(|err| self.error("import_patterns", context, err))?;
+        let count = patterns.len() as u32;
+        self.patterns.extend(patterns);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn life_106() {
+        let pattern = parse_pattern("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (3, 3));
+        assert_eq!(pattern.cells(), vec![0, 1, 1, 2, 2, 0, 2, 1, 2, 2]);
+    }
+
+    #[test]
+    fn life_106_rejects_huge_bounding_boxes() {
+        assert!(parse_pattern("#Life 1.06\n-2147483648 0\n2147483647 0\n").is_err());
+        assert!(parse_pattern("#Life 1.06\n0 0\n100000 100000\n").is_err());
+        assert!(parse_pattern("#Life 1.06\n0 0\n1 x\n").is_err());
+    }
+
+    #[test]
+    fn life_105() {
+        let text = "#Life 1.05\n#D A glider\n#R 23/36\n#P -1 -1\n.*.\n..*\n***\n#P 3 0\n*\n";
+        let pattern = parse_pattern(text).unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (5, 3));
+        assert_eq!(pattern.cells(), vec![0, 1, 1, 2, 1, 4, 2, 0, 2, 1, 2, 2]);
+        assert_eq!(pattern.rule(), Some("B36/S23".to_string()));
+        assert_eq!(pattern.comments(), vec!["A glider".to_string()]);
+    }
+
+    #[test]
+    fn life_105_rejects_blocks_that_overflow() {
+        assert_eq!(
+            parse_pattern("#Life 1.05\n#P 2147483647 0\n.*\n"),
+            Err("the block is too far out on line 3".to_string())
+        );
+        assert!(parse_pattern("#Life 1.05\n*\n").is_err());
+        assert!(parse_pattern("#Life 1.05\n#P 0\n*\n").is_err());
+    }
+
+    #[test]
+    fn rle() {
+        let text = "#N Glider\n#O Richard K. Guy\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+        let pattern = parse_pattern(text).unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (3, 3));
+        assert_eq!(pattern.cells(), vec![0, 1, 1, 2, 2, 0, 2, 1, 2, 2]);
+        assert_eq!(pattern.rule(), Some("B3/S23".to_string()));
+        assert_eq!(pattern.name(), Some("Glider".to_string()));
+        assert_eq!(pattern.author(), Some("Richard K. Guy".to_string()));
+        assert_eq!(
+            parse_pattern(&pattern.to_rle()).unwrap().cells,
+            pattern.cells
+        );
+    }
+
+    #[test]
+    fn rle_rejects_oversized_patterns() {
+        assert!(parse_pattern("x = 65537, y = 1\no!\n").is_err());
+        assert!(parse_pattern("x = 65536, y = 65536\no!\n").is_err());
+        assert!(parse_pattern("x = 0, y = 0\n99999999999o!\n").is_err());
+        let rows = "65536o$".repeat(1025);
+        assert!(parse_pattern(&format!("x = 0, y = 0\n{}!\n", rows)).is_err());
+    }
+
+    #[test]
+    fn plaintext() {
+        let text = "!Name: Blinker\n!Author: John Conway\n!Oscillates\n.O.\n.O.\n.O.\n";
+        let pattern = parse_pattern(text).unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (3, 3));
+        assert_eq!(pattern.cells(), vec![0, 1, 1, 1, 2, 1]);
+        assert_eq!(pattern.name(), Some("Blinker".to_string()));
+        assert_eq!(pattern.author(), Some("John Conway".to_string()));
+        assert_eq!(pattern.comments(), vec!["Oscillates".to_string()]);
+    }
+
+    #[test]
+    fn grid() {
+        let pattern = parse_pattern("#C A block\r\n11\r\n11\r\n").unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (2, 2));
+        assert_eq!(pattern.population(), 4);
+        assert_eq!(pattern.comments(), vec!["A block".to_string()]);
+        assert!(parse_pattern("1x\n").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::rng::Rng;
+use crate::{CellStorage, TickStats, Universe};
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// Hashes of the most recent generations, for spotting when the universe
+/// returns to an earlier state.
+///
+/// The hash is the XOR of a random key for each live cell, so it can be
+/// updated from the cells which changed during a tick rather than by hashing
+/// the whole universe again.
+pub(crate) struct PeriodDetector {
+    capacity: usize,
+    /// Hashes of recent generations, newest first.
+    hashes: VecDeque<u64>,
+    /// Hash of the current generation.
+    hash: u64,
+    /// Set when the cells have been moved around wholesale, such as by the
+    /// moving frame, and the hash needs to be worked out from scratch.
+    stale: bool,
+}
+
+impl PeriodDetector {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.hashes.capacity() * std::mem::size_of::<u64>()
+    }
+
+    pub(crate) fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+}
+
+/// The key for a live cell at an index into the cells.
+fn cell_key(idx: usize) -> u64 {
+    Rng::new(idx as u64).next_u64()
+}
+
+impl Universe {
+    fn full_hash(&self) -> u64 {
+        self.cells
+            .live_indices()
+            .into_iter()
+            .fold(0, |hash, idx| hash ^ cell_key(idx))
+    }
+
+    /// Check whether the cells have been edited since the last tick, which
+    /// breaks the run of generations, before ticking again.
+    pub(crate) fn resync_period(&mut self) {
+        let hash = match &self.period {
+            Some(period) => period.hash,
+            None => return,
+        };
+        let actual = self.full_hash();
+        if let Some(period) = &mut self.period {
+            if actual != hash {
+                period.hashes.clear();
+                period.hashes.push_front(actual);
+                period.hash = actual;
+            }
+        }
+    }
+
+    pub(crate) fn update_period(&mut self, stats: &TickStats) {
+        let mut period = match self.period.take() {
+            Some(period) => period,
+            None => return,
+        };
+        if period.stale {
+            period.hash = self.full_hash();
+            period.stale = false;
+        } else {
+            for &(row, col, _) in stats.changes.iter() {
+                period.hash ^= cell_key(self.get_index(row, col));
+            }
+        }
+        if period.hashes.len() == period.capacity {
+            period.hashes.pop_back();
+        }
+        period.hashes.push_front(period.hash);
+        self.period = Some(period);
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Remember the hashes of the last `capacity` generations so that
+    /// `detected_period` can spot repeats. A capacity of 0 turns detection
+    /// off.
+    pub fn set_period_detection(&mut self, capacity: usize) {
+        self.period = None;
+        if capacity > 0 {
+            let hash = self.full_hash();
+            let mut hashes = VecDeque::with_capacity(capacity + 1);
+            hashes.push_front(hash);
+            self.period = Some(PeriodDetector {
+                capacity: capacity + 1,
+                hashes,
+                hash,
+                stale: false,
+            });
+        }
+    }
+
+    /// The smallest number of generations after which the live cells have
+    /// repeated, if the current generation matches one of the remembered
+    /// ones. A still life has period 1.
+    ///
+    /// The hashes only cover which cells are alive, and are reset whenever
+    /// the cells are edited between ticks.
+    pub fn detected_period(&self) -> Option<u32> {
+        let hashes = &self.period.as_ref()?.hashes;
+        let current = *hashes.front()?;
+        hashes
+            .iter()
+            .skip(1)
+            .position(|&hash| hash == current)
+            .map(|i| i as u32 + 1)
+    }
+}
@@ -0,0 +1,190 @@
+//! A continuous variant of Life where each cell holds the probability that
+//! it is alive rather than a single state.
+//!
+//! Each tick a cell's neighbours are summed to give its expected number of
+//! live neighbours, which is fed through smooth versions of Conway's
+//! survival and birth windows. The new probability is the chance of
+//! surviving if the cell was alive plus the chance of being born if it was
+//! dead. The steeper the windows, the closer cells which are all 0 or 1
+//! follow the discrete rule.
+
+use crate::rng::{self, Rng};
+use crate::{Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// Neighbour counts at the edges of the survival and birth windows, placed
+/// halfway between whole counts so that steep windows give Conway's rule.
+const SURVIVE: (f32, f32) = (1.5, 3.5);
+const BIRTH: (f32, f32) = (2.5, 3.5);
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Close to 1 for counts inside a window and 0 outside, with the edges
+/// smoothed more the lower the steepness.
+fn window(count: f32, (low, high): (f32, f32), steepness: f32) -> f32 {
+    sigmoid((count - low) * steepness) * sigmoid((high - count) * steepness)
+}
+
+#[wasm_bindgen]
+pub struct ProbabilityUniverse {
+    width: u32,
+    height: u32,
+    /// Number of ticks since the universe was created.
+    generation: u64,
+    /// The probability of each cell being alive, row by row.
+    cells: Vec<f32>,
+    /// How sharply the windows change from 0 to 1 at their edges.
+    steepness: f32,
+    rng: Rng,
+}
+
+impl ProbabilityUniverse {
+    fn get_index(&self, row: u32, col: u32) -> usize {
+        (row * self.width + col) as usize
+    }
+
+    /// Expected number of live neighbours of a cell, wrapping around the
+    /// edges.
+    fn expected_neighbors(&self, row: u32, col: u32) -> f32 {
+        let mut count = 0.0;
+        for d_row in [self.height - 1, 0, 1].iter().cloned() {
+            for d_col in [self.width - 1, 0, 1].iter().cloned() {
+                if d_row == 0 && d_col == 0 {
+                    continue;
+                }
+                let neighbor_row = (row + d_row) % self.height;
+                let neighbor_col = (col + d_col) % self.width;
+                count += self.cells[self.get_index(neighbor_row, neighbor_col)];
+            }
+        }
+        count
+    }
+}
+
+seedable! {
+    /// Seed the random number generator used by `randomise`, so the same
+    /// seed gives the same cells.
+    ProbabilityUniverse
+}
+
+#[wasm_bindgen]
+impl ProbabilityUniverse {
+    /// Create a universe with every cell certainly dead.
+    pub fn new(width: u32, height: u32) -> Result<ProbabilityUniverse, UniverseError> {
+        crate::utils::set_panic_hook();
+        let size = UniverseError::checked_size("ProbabilityUniverse::new", &[width, height])?;
+        Ok(ProbabilityUniverse {
+            width,
+            height,
+            generation: 0,
+            cells: vec![0.0; size],
+            steepness: 8.0,
+            rng: Rng::new(rng::random_seed()),
+        })
+    }
+
+    /// Create a universe the same size as a discrete one, with its live
+    /// cells certainly alive and the rest certainly dead, to watch the two
+    /// evolve side by side.
+    pub fn from_universe(universe: &Universe) -> ProbabilityUniverse {
+        let mut probabilities =
+            ProbabilityUniverse::new(universe.width(), universe.height()).unwrap();
+        for (row, col) in universe.iter_live_cells() {
+            let idx = probabilities.get_index(row, col);
+            probabilities.cells[idx] = 1.0;
+        }
+        probabilities
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The probability of each cell being alive, row by row, as a
+    /// `Float32Array`.
+    pub fn cells(&self) -> Vec<f32> {
+        self.cells.clone()
+    }
+
+    /// The probability of a cell being alive, or 0 outside the universe.
+    pub fn get(&self, row: u32, col: u32) -> f32 {
+        if row < self.height && col < self.width {
+            self.cells[self.get_index(row, col)]
+        } else {
+            0.0
+        }
+    }
+
+    /// Set the probability of a cell being alive, clamped between 0 and 1.
+    /// Does nothing outside the universe.
+    pub fn set(&mut self, row: u32, col: u32, probability: f32) {
+        if row < self.height && col < self.width {
+            let idx = self.get_index(row, col);
+            self.cells[idx] = if probability > 0.0 {
+                probability.min(1.0)
+            } else {
+                0.0
+            };
+        }
+    }
+
+    /// The expected number of live cells.
+    pub fn expected_population(&self) -> f32 {
+        self.cells.iter().sum()
+    }
+
+    pub fn steepness(&self) -> f32 {
+        self.steepness
+    }
+
+    /// Set how sharply the survival and birth windows change at their
+    /// edges. The default is 8: lower values blur the universe into soft
+    /// clouds, while higher values get closer to the discrete rule.
+    pub fn set_steepness(&mut self, steepness: f32) {
+        self.steepness = steepness.max(0.0);
+    }
+
+    /// Give every cell a probability chosen uniformly between 0 and 1.
+    pub fn randomise(&mut self) {
+        for probability in self.cells.iter_mut() {
+            *probability = self.rng.next_f64() as f32;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(0.0);
+    }
+
+    pub fn tick(&mut self) {
+        self.tick_many(1);
+    }
+
+    pub fn tick_many(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            trace_span!("tick");
+            let mut next = Vec::with_capacity(self.cells.len());
+            for row in 0..self.height {
+                for col in 0..self.width {
+                    let alive = self.cells[self.get_index(row, col)];
+                    let count = self.expected_neighbors(row, col);
+                    next.push(
+                        alive * window(count, SURVIVE, self.steepness)
+                            + (1.0 - alive) * window(count, BIRTH, self.steepness),
+                    );
+                }
+            }
+            self.cells = next;
+            self.generation += 1;
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Puzzles where the player edits cells and ticks a universe to turn a
+//! start state into a target state, with a limited number of each.
+//!
+//! Like a `Scenario`, a puzzle acts on a universe owned by the frontend, so
+//! the frontend draws it as usual, but edits and ticks go through the
+//! puzzle so they can be counted.
+
+use crate::Universe;
+use std::collections::BTreeSet;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PuzzleStatus {
+    Playing = 0,
+    /// The universe is close enough to the target.
+    Solved = 1,
+    /// Every edit and tick has been used without reaching the target.
+    OutOfMoves = 2,
+}
+
+#[wasm_bindgen]
+pub struct Puzzle {
+    width: u32,
+    height: u32,
+    start: BTreeSet<(u32, u32)>,
+    target: BTreeSet<(u32, u32)>,
+    max_edits: u32,
+    max_ticks: u32,
+    edits: u32,
+    ticks: u32,
+    /// The similarity to the target at which the puzzle is solved.
+    win_similarity: f64,
+}
+
+impl Puzzle {
+    fn live_cells(universe: &Universe) -> BTreeSet<(u32, u32)> {
+        universe.iter_live_cells().collect()
+    }
+
+    fn check_size(&self, universe: &Universe) -> Result<(), String> {
+        if (universe.width(), universe.height()) == (self.width, self.height) {
+            Ok(())
+        } else {
+            Err(format!(
+                "the puzzle is {}x{} but the universe is {}x{}",
+                self.width,
+                self.height,
+                universe.width(),
+                universe.height()
+            ))
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Puzzle {
+    /// Create a puzzle from the live cells of two universes of the same
+    /// size, allowing up to `max_edits` cell edits and `max_ticks` ticks.
+    pub fn new(
+        start: &Universe,
+        target: &Universe,
+        max_edits: u32,
+        max_ticks: u32,
+    ) -> Result<Puzzle, String> {
+        if (start.width(), start.height()) != (target.width(), target.height()) {
+            return Err("the start and target universes are different sizes".to_string());
+        }
+        Ok(Puzzle {
+            width: start.width(),
+            height: start.height(),
+            start: Puzzle::live_cells(start),
+            target: Puzzle::live_cells(target),
+            max_edits,
+            max_ticks,
+            edits: 0,
+            ticks: 0,
+            win_similarity: 1.0,
+        })
+    }
+
+    /// Set how similar the universe must be to the target to solve the
+    /// puzzle, from `similarity`. The default of 1 needs an exact match.
+    pub fn set_win_similarity(&mut self, similarity: f64) {
+        self.win_similarity = similarity;
+    }
+
+    /// Set a universe to the start state and give back every edit and
+    /// tick. The universe must be the same size as the puzzle.
+    pub fn reset(&mut self, universe: &mut Universe) -> Result<(), String> {
+        self.check_size(universe)?;
+        universe.clear();
+        let cells: Vec<_> = self
+            .start
+            .iter()
+            .map(|&(row, col)| (row as i32, col as i32))
+            .collect();
+        universe.set_cells(&cells);
+        self.edits = 0;
+        self.ticks = 0;
+        Ok(())
+    }
+
+    /// Toggle a cell, using up one edit. Fails without changing anything if
+    /// there are no edits left or the cell is outside the universe.
+    pub fn toggle_cell(
+        &mut self,
+        universe: &mut Universe,
+        row: u32,
+        col: u32,
+    ) -> Result<(), String> {
+        self.check_size(universe)?;
+        if self.edits >= self.max_edits {
+            return Err("there are no edits left".to_string());
+        }
+        universe
+            .toggle_cell(row, col)
+            .map_err(|err| err.message().to_string())?;
+        self.edits += 1;
+        Ok(())
+    }
+
+    /// Tick the universe once, using up one tick. Fails without ticking if
+    /// there are no ticks left.
+    pub fn tick(&mut self, universe: &mut Universe) -> Result<(), String> {
+        self.check_size(universe)?;
+        if self.ticks >= self.max_ticks {
+            return Err("there are no ticks left".to_string());
+        }
+        universe.tick();
+        self.ticks += 1;
+        Ok(())
+    }
+
+    pub fn edits_left(&self) -> u32 {
+        self.max_edits - self.edits
+    }
+
+    pub fn ticks_left(&self) -> u32 {
+        self.max_ticks - self.ticks
+    }
+
+    /// Number of edits and ticks used since the last reset.
+    pub fn moves(&self) -> u32 {
+        self.edits + self.ticks
+    }
+
+    /// How close a universe is to the target, from 0 to 1: the number of
+    /// cells alive in both divided by the number alive in either. Two empty
+    /// universes are identical.
+    pub fn similarity(&self, universe: &Universe) -> f64 {
+        let cells = Puzzle::live_cells(universe);
+        let union = cells.union(&self.target).count();
+        if union == 0 {
+            return 1.0;
+        }
+        cells.intersection(&self.target).count() as f64 / union as f64
+    }
+
+    pub fn status(&self, universe: &Universe) -> PuzzleStatus {
+        if self.similarity(universe) >= self.win_similarity {
+            PuzzleStatus::Solved
+        } else if self.edits >= self.max_edits && self.ticks >= self.max_ticks {
+            PuzzleStatus::OutOfMoves
+        } else {
+            PuzzleStatus::Playing
+        }
+    }
+
+    /// The live cells of the target as `[row, col, row, col, ...]`, for
+    /// drawing it faintly behind the universe.
+    pub fn target_cells(&self) -> Vec<u32> {
+        self.target
+            .iter()
+            .flat_map(|&(row, col)| vec![row, col])
+            .collect()
+    }
+}
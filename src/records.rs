@@ -0,0 +1,124 @@
+use crate::{CellStorage, Universe};
+use wasm_bindgen::prelude::*;
+
+/// Marks cells which are dead.
+const DEAD: u64 = u64::MAX;
+
+/// The most notable things seen while the universe has been running.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunRecords {
+    /// The highest population and the generation it was first reached in.
+    pub peak_population: u32,
+    pub peak_generation: u64,
+    /// The most generations a cell has stayed alive for, where it was and
+    /// the generation it had lived that long by.
+    pub longest_lived: u64,
+    pub longest_lived_row: u32,
+    pub longest_lived_col: u32,
+    pub longest_lived_generation: u64,
+    /// The most cells in a single object and the generation it was seen in.
+    pub largest_object: u32,
+    pub largest_object_generation: u64,
+}
+
+pub(crate) struct Records {
+    /// The generation each live cell has been alive since, laid out in the
+    /// same way as `cells`.
+    since: Vec<u64>,
+    report: RunRecords,
+}
+
+impl Records {
+    /// Forget which cells are alive, when the universe is resized.
+    pub(crate) fn resize(&mut self, size: usize) {
+        self.since = vec![DEAD; size];
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.since.capacity() * std::mem::size_of::<u64>()
+    }
+}
+
+impl Universe {
+    /// Check the universe at the end of a tick for new records.
+    pub(crate) fn update_records(&mut self) {
+        let mut records = match self.records.take() {
+            Some(records) => records,
+            None => return,
+        };
+        self.check_records(&mut records);
+        self.records = Some(records);
+    }
+
+    fn check_records(&self, records: &mut Records) {
+        let generation = self.generation;
+        let report = &mut records.report;
+        let population = self.cells.population() as u32;
+        if population > report.peak_population {
+            report.peak_population = population;
+            report.peak_generation = generation;
+        }
+
+        // Cells are counted from the first generation they are seen alive
+        // in, which also covers cells drawn in by the user.
+        let stride = (self.width + 2) as usize;
+        for (idx, since) in records.since.iter_mut().enumerate() {
+            if !self.cells.is_alive(idx) {
+                *since = DEAD;
+                continue;
+            }
+            if *since == DEAD {
+                *since = generation;
+            }
+            let lived = generation - *since;
+            if lived > report.longest_lived {
+                report.longest_lived = lived;
+                report.longest_lived_row = (idx / stride - 1) as u32;
+                report.longest_lived_col = (idx % stride - 1) as u32;
+                report.longest_lived_generation = generation;
+            }
+        }
+
+        let labels = self.label_components();
+        let mut sizes = Vec::new();
+        for &label in labels.iter().filter(|&&label| label != 0) {
+            let label = label as usize;
+            if sizes.len() < label {
+                sizes.resize(label, 0);
+            }
+            sizes[label - 1] += 1;
+        }
+        let largest = sizes.into_iter().max().unwrap_or(0);
+        if largest > report.largest_object {
+            report.largest_object = largest;
+            report.largest_object_generation = generation;
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start or stop keeping run records such as the peak population.
+    /// Starting again clears the records, and checks the current generation
+    /// straight away.
+    ///
+    /// Finding the largest object labels every object after each tick, so
+    /// this is best left off when speed matters.
+    pub fn set_record_tracking(&mut self, enabled: bool) {
+        self.records = None;
+        if enabled {
+            let mut records = Records {
+                since: vec![DEAD; self.cells.len()],
+                report: RunRecords::default(),
+            };
+            self.check_records(&mut records);
+            self.records = Some(records);
+        }
+    }
+
+    /// The records so far, or `undefined` if they aren't being kept.
+    pub fn run_records(&self) -> Option<RunRecords> {
+        self.records.as_ref().map(|records| records.report)
+    }
+}
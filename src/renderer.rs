@@ -0,0 +1,211 @@
+//! Drawing the universe onto a canvas from Rust, so a frontend only has to
+//! call `draw` once a frame instead of reading and drawing every cell.
+//!
+//! Cells are drawn the way the demo page draws them: `cell_size` pixel
+//! squares with a one pixel grid line around each. The renderer keeps the
+//! canvas pixels from the last frame and after each call to `tick_many`
+//! only repaints the cells it changed, then puts back the part of the canvas
+//! covering them. Edits and moves through history are told apart from ticks
+//! by the universe's count of changes to its cells, and repaint everything.
+//! The HUD is drawn over the canvas after the cells, and the area under it
+//! is put back along with the changed cells on the next draw.
+
+use crate::hud::{self, RenderOptions, SpeedMeter};
+use crate::{timing, CellStorage, Universe};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::{CanvasRenderingContext2d, ImageData};
+
+const GRID_COLOR: [u8; 4] = [0xcc, 0xcc, 0xcc, 255];
+const DEAD_COLOR: [u8; 4] = [0xff, 0xff, 0xff, 255];
+const ALIVE_COLOR: [u8; 4] = [0, 0, 0, 255];
+
+#[wasm_bindgen]
+pub struct Renderer {
+    cell_size: u32,
+    /// Size in cells of the universe last drawn.
+    width: u32,
+    height: u32,
+    /// RGBA pixels of the whole canvas as last drawn.
+    pixels: Vec<u8>,
+    /// RGBA pixels of the part of the canvas being put back, kept to avoid
+    /// reallocating them every frame.
+    dirty_pixels: Vec<u8>,
+    options: RenderOptions,
+    /// Measures the speed shown on the HUD.
+    speed_meter: SpeedMeter,
+    /// Width and height in pixels of the HUD as last drawn, which is put
+    /// back from `pixels` before the HUD is drawn again, so its translucent
+    /// backing doesn't build up.
+    hud_size: Option<(u32, u32)>,
+    /// The `cells_version` of the universe when last drawn, or `None` if
+    /// everything needs drawing.
+    version: Option<u64>,
+}
+
+impl Renderer {
+    fn canvas_size(&self, width: u32, height: u32) -> (u32, u32) {
+        (
+            (self.cell_size + 1) * width + 1,
+            (self.cell_size + 1) * height + 1,
+        )
+    }
+
+    /// Fill in the square of pixels for a cell.
+    fn paint_cell(&mut self, row: u32, col: u32, alive: bool) {
+        let canvas_width = self.canvas_size(self.width, self.height).0 as usize;
+        let color = if alive { ALIVE_COLOR } else { DEAD_COLOR };
+        let x = (col * (self.cell_size + 1) + 1) as usize;
+        let y = (row * (self.cell_size + 1) + 1) as usize;
+        for py in y..y + self.cell_size as usize {
+            let start = (py * canvas_width + x) * 4;
+            let end = start + self.cell_size as usize * 4;
+            for pixel in self.pixels[start..end].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Update the pixels to show a universe, returning the rectangle of
+    /// pixels which changed as `(x, y, w, h)`, if any did.
+    ///
+    /// If the universe has only been ticked by one call to `tick_many` since
+    /// the last draw, with no edits, just the cells it changed are
+    /// repainted. Otherwise every cell is.
+    fn paint(&mut self, universe: &Universe) -> Option<(u32, u32, u32, u32)> {
+        let (width, height) = (universe.width(), universe.height());
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.version = None;
+        }
+        let last = self.version.replace(universe.cells_version);
+        if let Some(cells) = last.and_then(|last| universe.changed_since(last)) {
+            // Changed cells as (top, left, bottom, right), inclusive.
+            let mut changed: Option<(u32, u32, u32, u32)> = None;
+            for &cell in cells {
+                let (row, col) = (cell / width, cell % width);
+                let alive = universe.cells.is_alive(universe.get_index(row, col));
+                self.paint_cell(row, col, alive);
+                changed = Some(match changed {
+                    Some((top, left, bottom, right)) => {
+                        (top.min(row), left.min(col), bottom.max(row), right.max(col))
+                    }
+                    None => (row, col, row, col),
+                });
+            }
+            return changed.map(|(top, left, bottom, right)| {
+                let step = self.cell_size + 1;
+                (
+                    left * step + 1,
+                    top * step + 1,
+                    (right - left) * step + self.cell_size,
+                    (bottom - top) * step + self.cell_size,
+                )
+            });
+        }
+        if last == Some(universe.cells_version) {
+            return None;
+        }
+
+        let (canvas_width, canvas_height) = self.canvas_size(width, height);
+        self.pixels = GRID_COLOR.repeat(canvas_width as usize * canvas_height as usize);
+        for (row, col) in (0..height).flat_map(|row| (0..width).map(move |col| (row, col))) {
+            let alive = universe.cells.is_alive(universe.get_index(row, col));
+            self.paint_cell(row, col, alive);
+        }
+        // The grid lines need drawing too.
+        Some((0, 0, canvas_width, canvas_height))
+    }
+
+    /// Copy a rectangle `(x, y, w, h)` of the pixels onto the canvas.
+    fn put_back(
+        &mut self,
+        ctx: &CanvasRenderingContext2d,
+        (x, y, w, h): (u32, u32, u32, u32),
+    ) -> Result<(), JsValue> {
+        let canvas_width = self.canvas_size(self.width, self.height).0 as usize;
+        self.dirty_pixels.clear();
+        for row in y as usize..(y + h) as usize {
+            let start = (row * canvas_width + x as usize) * 4;
+            self.dirty_pixels
+                .extend_from_slice(&self.pixels[start..start + w as usize * 4]);
+        }
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&self.dirty_pixels), w, h)?;
+        ctx.put_image_data(&image, x as f64, y as f64)
+    }
+}
+
+#[wasm_bindgen]
+impl Renderer {
+    /// Create a renderer drawing each cell as a `cell_size` pixel square.
+    #[wasm_bindgen(constructor)]
+    pub fn new(cell_size: u32) -> Renderer {
+        Renderer {
+            cell_size: cell_size.max(1),
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+            dirty_pixels: Vec::new(),
+            options: RenderOptions {
+                hud: false,
+                ..RenderOptions::new()
+            },
+            speed_meter: SpeedMeter::default(),
+            hud_size: None,
+            version: None,
+        }
+    }
+
+    /// Choose what to draw along with the cells, such as the HUD, which is
+    /// off until turned on here.
+    pub fn set_options(&mut self, options: &RenderOptions) {
+        self.options = *options;
+    }
+
+    /// Width of the canvas needed to draw a universe, in pixels.
+    pub fn canvas_width(&self, universe: &Universe) -> u32 {
+        self.canvas_size(universe.width(), universe.height()).0
+    }
+
+    /// Height of the canvas needed to draw a universe, in pixels.
+    pub fn canvas_height(&self, universe: &Universe) -> u32 {
+        self.canvas_size(universe.width(), universe.height()).1
+    }
+
+    /// Draw a universe onto a canvas, with its top-left corner at the
+    /// canvas's. When the universe has only ticked since the last draw, just
+    /// the cells which changed are redrawn, so `invalidate` needs calling
+    /// after the canvas is drawn over. The HUD, if turned on with
+    /// `set_options`, is drawn on top. Call this once per frame for the
+    /// HUD's speed to be measured.
+    pub fn draw(
+        &mut self,
+        universe: &Universe,
+        ctx: &CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        trace_span!("render");
+        if let Some(dirty) = self.paint(universe) {
+            self.put_back(ctx, dirty)?;
+        }
+        if let Some((w, h)) = self.hud_size.take() {
+            let (canvas_width, canvas_height) = self.canvas_size(self.width, self.height);
+            self.put_back(ctx, (0, 0, w.min(canvas_width), h.min(canvas_height)))?;
+        }
+        let speed = self
+            .speed_meter
+            .sample(timing::now(), universe.generation());
+        if self.options.hud {
+            let lines = universe.hud_text(&self.options, speed);
+            let (w, h) = hud::draw_hud(ctx, &lines, &self.options)?;
+            self.hud_size = Some((w.ceil() as u32, h.ceil() as u32));
+        }
+        Ok(())
+    }
+
+    /// Draw everything on the next `draw`, such as after the canvas has
+    /// been cleared or resized.
+    pub fn invalidate(&mut self) {
+        self.version = None;
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{CellStorage, Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// A resource which live cells consume and which regrows over time, with
+/// cells only able to be born where there is enough of it.
+pub(crate) struct Resources {
+    /// The amount of resource at each cell between 0 and 1, laid out in the
+    /// same way as the universe's cells.
+    levels: Vec<f32>,
+    consumption: f32,
+    regeneration: f32,
+    threshold: f32,
+}
+
+impl Resources {
+    /// Number of cells covered, which matches the universe's cells.
+    pub(crate) fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        std::mem::size_of_val(self.levels.as_slice())
+    }
+}
+
+impl Universe {
+    /// Whether a dead cell is allowed to be born under the resource rules.
+    pub(crate) fn birth_allowed(&self, idx: usize) -> bool {
+        self.resources
+            .as_ref()
+            .is_none_or(|resources| resources.levels[idx] > resources.threshold)
+    }
+
+    /// Let live cells consume resource and let it regrow everywhere.
+    pub(crate) fn update_resources(&mut self) {
+        if let Some(mut resources) = self.resources.take() {
+            for row in 0..self.height {
+                for col in 0..self.width {
+                    let idx = self.get_index(row, col);
+                    let mut level = resources.levels[idx] + resources.regeneration;
+                    if self.cells.is_alive(idx) {
+                        level -= resources.consumption;
+                    }
+                    resources.levels[idx] = level.clamp(0.0, 1.0);
+                }
+            }
+            self.resources = Some(resources);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Give every cell a resource level, starting full at 1. Each tick live
+    /// cells use up `consumption` of it and it regrows by `regeneration`
+    /// everywhere, and dead cells can only be born where the level is above
+    /// `threshold`.
+    pub fn enable_resources(&mut self, consumption: f32, regeneration: f32, threshold: f32) {
+        self.resources = Some(Resources {
+            levels: vec![1.0; self.cells.len()],
+            consumption,
+            regeneration,
+            threshold,
+        });
+    }
+
+    pub fn disable_resources(&mut self) {
+        self.resources = None;
+    }
+
+    /// The resource level at a cell, or 1 if resources are disabled.
+    pub fn resource_at(&self, row: u32, col: u32) -> Result<f32, UniverseError> {
+        let idx = self.checked_index("resource_at", row, col)?;
+        Ok(self
+            .resources
+            .as_ref()
+            .map_or(1.0, |resources| resources.levels[idx]))
+    }
+
+    /// The resource level in each cell, row by row, as a `Float32Array`.
+    /// Every level is 1 if resources are disabled.
+    pub fn resources(&self) -> Vec<f32> {
+        let resources = self.resources.as_ref();
+        self.each_cell(|idx| resources.map_or(1.0, |resources| resources.levels[idx]))
+    }
+}
@@ -0,0 +1,208 @@
+//! Life-like rules written in B/S notation, such as `B36/S23` for HighLife
+//! or `B3678/S34678` for Day & Night, which universes tick with in place of
+//! Conway's B3/S23. A rule can also give live cells several colours, as in
+//! `Immigration` or `B36/S23 QuadLife`, which needs a byte per cell.
+
+use crate::{LogLevel, Universe, UniverseError};
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// Well known rules which can be given by name instead of in B/S notation.
+/// The maze rules grow walls of live cells around corridors of dead ones,
+/// which `find_path` can then solve.
+const NAMED_RULES: &[(&str, &str)] = &[
+    ("Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day & Night", "B3678/S34678"),
+    ("Maze", "B3/S12345"),
+    ("Mazectric", "B3/S1234"),
+    ("Maze with mice", "B37/S12345"),
+    ("Mazectric with mice", "B37/S1234"),
+];
+
+/// Ways of colouring live cells, with the number of states each needs
+/// counting dead. Every colour counts as alive, and cells being born take
+/// the most common colour among their live neighbours. Each can be given on
+/// its own for Conway's rule or after a rule, as in `B36/S23 Immigration`.
+const COLOURINGS: &[(&str, u32)] = &[("Immigration", 3), ("QuadLife", 5)];
+
+/// The names of the rules which `set_rule` accepts by name, such as `Maze`.
+#[wasm_bindgen]
+pub fn rule_names() -> Vec<String> {
+    NAMED_RULES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .chain(COLOURINGS.iter().map(|(name, _)| name.to_string()))
+        .collect()
+}
+
+/// A rule in the standard B/S form, such as `B36/S23` for `23/36` or
+/// `HighLife`, or `undefined` if `set_rule` wouldn't accept it. Lets
+/// frontends check what the user types before applying it.
+#[wasm_bindgen]
+pub fn normalize_rule(rule: &str) -> Option<String> {
+    Rule::parse(rule)
+        .filter(|rule| rule.is_supported())
+        .map(|rule| rule.to_string())
+}
+
+/// A Life-like rule: the numbers of live neighbours for which a dead cell is
+/// born and a live cell survives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Rule {
+    /// Bit `n` is set if a dead cell with `n` live neighbours is born.
+    birth: u16,
+    /// Bit `n` is set if a live cell with `n` live neighbours survives.
+    survive: u16,
+    /// The number of states a cell can be in, which is 2 unless live cells
+    /// are coloured.
+    states: u32,
+}
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23.
+    pub(crate) const CONWAY: Rule = Rule {
+        birth: 1 << 3,
+        survive: 1 << 2 | 1 << 3,
+        states: 2,
+    };
+
+    /// Parse a rule from any of the ways patterns write it: `B3/S23`,
+    /// `S23/B3`, the older `23/3` (survival then birth) and any case, or
+    /// from one of the names in `NAMED_RULES`. A bounded grid given after a
+    /// `:`, as in `B3/S23:T64,64`, is ignored. Returns `None` for anything
+    /// else, such as rules for other neighbourhoods. Any of the
+    /// `COLOURINGS` can follow the rule or stand in for Conway's.
+    pub(crate) fn parse(rule: &str) -> Option<Rule> {
+        let trimmed = rule.trim();
+        for &(name, states) in COLOURINGS {
+            let split = trimmed.len().checked_sub(name.len());
+            if let Some(rest) = split.and_then(|split| trimmed.get(..split)) {
+                if trimmed[rest.len()..].eq_ignore_ascii_case(name) {
+                    let rest = if rest.trim().is_empty() {
+                        "B3/S23"
+                    } else {
+                        rest
+                    };
+                    return Rule::parse(rest).map(|rule| Rule { states, ..rule });
+                }
+            }
+        }
+        if let Some(&(_, named)) = NAMED_RULES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(rule.trim()))
+        {
+            return Rule::parse(named);
+        }
+        let rule = rule
+            .split(':')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_uppercase();
+        let mut parts = rule.splitn(2, '/');
+        let (first, second) = (parts.next()?.trim(), parts.next()?.trim());
+        let (birth, survive) = match (first.strip_prefix('B'), second.strip_prefix('S')) {
+            (Some(birth), Some(survive)) => (birth, survive),
+            _ => match (first.strip_prefix('S'), second.strip_prefix('B')) {
+                (Some(survive), Some(birth)) => (birth, survive),
+                _ => (second, first),
+            },
+        };
+        let counts = |counts: &str| -> Option<u16> {
+            counts
+                .chars()
+                .try_fold(0, |mask, ch| ch.to_digit(9).map(|count| mask | 1 << count))
+        };
+        Some(Rule {
+            birth: counts(birth)?,
+            survive: counts(survive)?,
+            states: 2,
+        })
+    }
+
+    /// Whether the universe can run the rule. Rules where cells with no live
+    /// neighbours are born, such as B0/S8, aren't supported: they bring the
+    /// whole of an empty universe to life, which sparse ticking can't do.
+    pub(crate) fn is_supported(self) -> bool {
+        self.birth & 1 == 0
+    }
+
+    /// The number of states a cell can be in, which chooses how the cells
+    /// are stored.
+    pub(crate) fn states(self) -> u32 {
+        self.states
+    }
+
+    /// The same births and survivals with live cells in `colours` colours.
+    pub(crate) fn with_colours(self, colours: u32) -> Rule {
+        Rule {
+            states: colours + 1,
+            ..self
+        }
+    }
+
+    /// Whether a cell is alive in the next generation.
+    pub(crate) fn next(self, alive: bool, live_neighbors: u8) -> bool {
+        let counts = if alive { self.survive } else { self.birth };
+        counts & 1 << live_neighbors != 0
+    }
+}
+
+impl fmt::Display for Rule {
+    /// Writes the rule in the standard form, such as `B3/S23`, followed by
+    /// how live cells are coloured, as in `B36/S23 Immigration`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = |mask: u16| -> String {
+            (0..=8)
+                .filter(|count| mask & 1 << count != 0)
+                .map(|count| char::from(b'0' + count as u8))
+                .collect()
+        };
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survive))?;
+        match COLOURINGS
+            .iter()
+            .find(|&&(_, states)| states == self.states)
+        {
+            Some((name, _)) => write!(f, " {}", name),
+            None => Ok(()),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// The active rule in B/S notation, such as `B3/S23` or
+    /// `B3/S23 Immigration`.
+    pub fn rule(&self) -> String {
+        self.rule_string()
+    }
+
+    /// Switch to another Life-like rule in B/S notation, such as `B3/S23`
+    /// for Conway's Game of Life or `B3678/S34678` for Day & Night, or by
+    /// one of the names from `rule_names`, such as `Maze`. This can
+    /// be called while the universe is running: the cells carry on from
+    /// where they are under the new rule.
+    ///
+    /// Following the rule with `Immigration` or `QuadLife` gives live cells
+    /// two or four colours, which switches the cells to a byte each.
+    ///
+    /// If an op log is running, the change is logged for the other clients
+    /// to receive from `ops_since`.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), UniverseError> {
+        let parsed = match Rule::parse(rule) {
+            Some(parsed) if parsed.is_supported() => parsed,
+            _ => {
+                return Err(self.error(
+                    "set_rule",
+                    format!("rule={}", rule),
+                    format!("the rule {} is not supported", rule),
+                ))
+            }
+        };
+        log!(LogLevel::Info, "Switching from {} to {}", self.rule, parsed);
+        self.apply_rule(parsed);
+        self.log_rule(parsed);
+        Ok(())
+    }
+}
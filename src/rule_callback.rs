@@ -0,0 +1,142 @@
+//! Rules written in JavaScript, for trying out rules the crate doesn't
+//! support.
+//!
+//! The crate still stores the cells, counts neighbours and renders: each
+//! tick the callback is called with a chunk of rows at a time as two
+//! `Uint8Array`s, the state of each cell and its number of live
+//! neighbours, and returns the new states in a `Uint8Array` or array of the
+//! same length. Calling into JavaScript is slow, so the larger the chunks
+//! the better, up to the whole universe at once.
+
+use crate::{Cell, CellStorage, LogLevel, TickStats, Universe};
+use wasm_bindgen::prelude::*;
+
+pub(crate) struct RuleCallback {
+    function: js_sys::Function,
+    /// Number of rows passed to each call.
+    chunk_rows: u32,
+    /// Buffers for the states, neighbour counts and new states of a chunk,
+    /// kept to avoid reallocating them for every call.
+    states: Vec<u8>,
+    counts: Vec<u8>,
+    new_states: Vec<u8>,
+}
+
+impl Universe {
+    /// The state and number of live neighbours of each cell in `rows`, row
+    /// by row. Walls count as neighbours if they are marked.
+    fn chunk_inputs(&self, rows: std::ops::Range<u32>, states: &mut Vec<u8>, counts: &mut Vec<u8>) {
+        let stride = (self.width + 2) as usize;
+        let alive = |idx: usize| self.cells.is_alive(idx) as u8;
+        states.clear();
+        counts.clear();
+        for row in rows {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                states.push(self.cells.get(idx).state());
+                counts.push(
+                    [
+                        idx - stride - 1,
+                        idx - stride,
+                        idx - stride + 1,
+                        idx - 1,
+                        idx + 1,
+                        idx + stride - 1,
+                        idx + stride,
+                        idx + stride + 1,
+                    ]
+                    .iter()
+                    .map(|&neighbor| alive(neighbor))
+                    .sum(),
+                );
+            }
+        }
+    }
+
+    /// Tick by calling the JavaScript rule, leaving any chunk where the
+    /// call fails or returns the wrong number of states as it was.
+    pub(crate) fn step_callback(&mut self) -> TickStats {
+        let mut callback = self.rule_callback.take().unwrap();
+        self.refresh_halo();
+        let mut next = self.next_cells();
+        let mut stats = self.fresh_stats();
+        let mut top = 0;
+        while top < self.height {
+            let bottom = (top + callback.chunk_rows).min(self.height);
+            let (states, counts) = (&mut callback.states, &mut callback.counts);
+            self.chunk_inputs(top..bottom, states, counts);
+            let result = callback.function.call2(
+                &JsValue::NULL,
+                &js_sys::Uint8Array::from(&states[..]),
+                &js_sys::Uint8Array::from(&counts[..]),
+            );
+            let returned = match result {
+                Ok(value) => js_sys::Uint8Array::new(&value),
+                Err(err) => {
+                    log!(LogLevel::Error, "rule callback failed: {:?}", err);
+                    top = bottom;
+                    continue;
+                }
+            };
+            if returned.length() as usize != states.len() {
+                log!(
+                    LogLevel::Error,
+                    "rule callback returned {} states for {} cells",
+                    returned.length(),
+                    states.len()
+                );
+            } else {
+                let new_states = &mut callback.new_states;
+                new_states.resize(states.len(), 0);
+                returned.copy_to(new_states);
+                for (i, (&state, &new_state)) in states.iter().zip(new_states.iter()).enumerate() {
+                    let row = top + i as u32 / self.width;
+                    let col = i as u32 % self.width;
+                    let idx = self.get_index(row, col);
+                    let new_state = new_state.min(self.rule.states() as u8 - 1);
+                    if new_state == state || self.is_wall(idx) || self.is_frozen(row, col) {
+                        continue;
+                    }
+                    next.set(idx, Cell(new_state));
+                    if (state != 0) != (new_state != 0) {
+                        stats.record_change(row, col, new_state != 0);
+                    }
+                }
+            }
+            top = bottom;
+        }
+        self.swap_cells(next);
+        self.clear_halo();
+        stats.population = self.cells.population();
+        self.rule_callback = Some(callback);
+        stats
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Tick with a JavaScript function `(states, neighborCounts) =>
+    /// newStates` instead of the rule, called with `chunk_rows` rows of
+    /// cells at a time. See the `rule_callback` module for details.
+    ///
+    /// New states above the highest state are lowered to it. Walls and
+    /// cells in frozen time zones don't change, as with the built-in rules.
+    pub fn set_rule_callback(&mut self, callback: js_sys::Function, chunk_rows: u32) {
+        self.rule_callback = Some(RuleCallback {
+            function: callback,
+            chunk_rows: chunk_rows.max(1),
+            states: Vec::new(),
+            counts: Vec::new(),
+            new_states: Vec::new(),
+        });
+    }
+
+    /// Go back to ticking with the rule.
+    pub fn clear_rule_callback(&mut self) {
+        self.rule_callback = None;
+    }
+
+    pub fn has_rule_callback(&self) -> bool {
+        self.rule_callback.is_some()
+    }
+}
@@ -0,0 +1,40 @@
+//! Switching rules on a running universe, so a soup can carry on from where
+//! it is under the new rule rather than starting again.
+
+use crate::rule::Rule;
+use crate::{Cell, CellStorage, Cells, Universe};
+
+impl Universe {
+    /// Switch to a rule, keeping the cells as they are.
+    ///
+    /// If the rule has a different number of states, the cells move to
+    /// storage for them, with states the new rule doesn't have lowered to
+    /// its last state, and history is cleared as it holds cells in the old
+    /// storage. During a duel the rule keeps the players' two colours.
+    pub(crate) fn apply_rule(&mut self, rule: Rule) {
+        let rule = match self.duel {
+            Some(_) => rule.with_colours(2),
+            None => rule,
+        };
+        let old_states = self.rule.states();
+        self.rule = rule;
+        if rule.states() != old_states {
+            self.cells = self.restate_cells(&self.cells);
+            self.initial_cells = self.restate_cells(&self.initial_cells);
+            self.cells_edited();
+            if let Some(history) = &mut self.history {
+                history.clear();
+            }
+        }
+    }
+
+    /// A copy of cells in storage for the active rule's states.
+    fn restate_cells(&self, cells: &Cells) -> Cells {
+        let last = self.rule.states() as u8 - 1;
+        let mut restated = Cells::with_states(self.rule.states(), cells.len());
+        for idx in cells.iter_live_indices() {
+            restated.set(idx, Cell(cells.get(idx).state().min(last)));
+        }
+        restated
+    }
+}
@@ -0,0 +1,212 @@
+//! A falling sand toy, where each cell holds a material which moves under
+//! simple gravity-like rules.
+//!
+//! Each tick cells are visited from the bottom row up. Sand falls straight
+//! down, or else slides down to either side, sinking through water on the
+//! way. Water does the same and otherwise spreads sideways. Walls and empty
+//! cells stay put, and the edges of the box act as walls. Where a grain
+//! could go either way, the side is chosen at random.
+
+use crate::rng::{self, Rng};
+use crate::{Cell, CellStorage, Cells, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// What fills a cell, stored as the cell's state.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Material {
+    Empty = 0,
+    Sand = 1,
+    Water = 2,
+    Wall = 3,
+}
+
+impl Material {
+    const ALL: [Material; 4] = [
+        Material::Empty,
+        Material::Sand,
+        Material::Water,
+        Material::Wall,
+    ];
+
+    fn from_cell(cell: Cell) -> Material {
+        Material::ALL[cell.state() as usize]
+    }
+
+    /// The colour of the material as RGBA.
+    fn color(self) -> [u8; 4] {
+        match self {
+            Material::Empty => [0, 0, 0, 0],
+            Material::Sand => [0xE0, 0xC0, 0x70, 0xFF],
+            Material::Water => [0x30, 0x70, 0xE0, 0xFF],
+            Material::Wall => [0x60, 0x60, 0x60, 0xFF],
+        }
+    }
+
+    /// Whether a grain of this material can move into a cell holding
+    /// `other`, swapping places with it.
+    fn displaces(self, other: Material) -> bool {
+        matches!(
+            (self, other),
+            (_, Material::Empty) | (Material::Sand, Material::Water)
+        )
+    }
+}
+
+#[wasm_bindgen]
+pub struct FallingSand {
+    width: u32,
+    height: u32,
+    /// Number of ticks since the box was created.
+    generation: u64,
+    /// A `Material` for each cell, row by row.
+    cells: Cells,
+    rng: Rng,
+}
+
+impl FallingSand {
+    fn get_index(&self, row: u32, col: u32) -> usize {
+        (row * self.width + col) as usize
+    }
+
+    /// The material at a position, with walls beyond the edges.
+    fn material(&self, row: i32, col: i32) -> Material {
+        if row < 0 || col < 0 || row >= self.height as i32 || col >= self.width as i32 {
+            return Material::Wall;
+        }
+        Material::from_cell(self.cells.get(self.get_index(row as u32, col as u32)))
+    }
+
+    /// Where a grain at a cell moves to this tick, if anywhere.
+    fn destination(&mut self, row: i32, col: i32, grain: Material) -> Option<(i32, i32)> {
+        let side = if self.rng.next_u64() & 1 == 0 { -1 } else { 1 };
+        let mut moves = vec![(row + 1, col), (row + 1, col + side), (row + 1, col - side)];
+        if grain == Material::Water {
+            moves.extend_from_slice(&[(row, col + side), (row, col - side)]);
+        }
+        moves
+            .into_iter()
+            .find(|&(row, col)| grain.displaces(self.material(row, col)))
+    }
+}
+
+seedable! {
+    /// Seed the random number generator which chooses which way grains go,
+    /// so the same seed gives the same ticks.
+    FallingSand
+}
+
+#[wasm_bindgen]
+impl FallingSand {
+    /// Create an empty box.
+    pub fn new(width: u32, height: u32) -> Result<FallingSand, UniverseError> {
+        crate::utils::set_panic_hook();
+        let size = UniverseError::checked_size("FallingSand::new", &[width, height])?;
+        Ok(FallingSand {
+            width,
+            height,
+            generation: 0,
+            cells: Cells::with_states(Material::ALL.len() as u32, size),
+            rng: Rng::new(rng::random_seed()),
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The material in a cell, or `None` outside the box.
+    pub fn get_material(&self, row: u32, col: u32) -> Option<Material> {
+        if row < self.height && col < self.width {
+            Some(self.material(row as i32, col as i32))
+        } else {
+            None
+        }
+    }
+
+    /// Fill the square of cells within `radius` of a cell with a material,
+    /// clipped to the box.
+    pub fn paint(&mut self, row: u32, col: u32, radius: u32, material: Material) {
+        let top = row.saturating_sub(radius);
+        let left = col.saturating_sub(radius);
+        let bottom = row.saturating_add(radius).min(self.height - 1);
+        let right = col.saturating_add(radius).min(self.width - 1);
+        for row in top..=bottom {
+            for col in left..=right {
+                let idx = self.get_index(row, col);
+                self.cells.set(idx, Cell(material as u8));
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells = Cells::with_states(Material::ALL.len() as u32, self.cells.len());
+    }
+
+    /// Number of cells holding a material.
+    pub fn count(&self, material: Material) -> u32 {
+        (0..self.cells.len())
+            .filter(|&idx| self.cells.get(idx).state() == material as u8)
+            .count() as u32
+    }
+
+    /// The material of each cell, row by row, one byte per cell.
+    pub fn cells(&self) -> Vec<u8> {
+        self.cells.as_bytes().to_vec()
+    }
+
+    pub fn tick(&mut self) {
+        self.tick_many(1);
+    }
+
+    pub fn tick_many(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            trace_span!("tick");
+            // Grains only move once a tick, even if they land somewhere which
+            // is still to be visited.
+            let mut moved = vec![false; self.cells.len()];
+            for row in (0..self.height as i32).rev() {
+                // Sweep alternate rows in opposite directions so that water
+                // doesn't drift to one side.
+                let cols: Vec<i32> = if (row as u64 + self.generation) & 1 == 0 {
+                    (0..self.width as i32).collect()
+                } else {
+                    (0..self.width as i32).rev().collect()
+                };
+                for col in cols {
+                    let idx = self.get_index(row as u32, col as u32);
+                    let grain = Material::from_cell(self.cells.get(idx));
+                    if moved[idx] || !matches!(grain, Material::Sand | Material::Water) {
+                        continue;
+                    }
+                    if let Some((to_row, to_col)) = self.destination(row, col, grain) {
+                        let to = self.get_index(to_row as u32, to_col as u32);
+                        let displaced = self.cells.get(to);
+                        self.cells.set(to, Cell(grain as u8));
+                        self.cells.set(idx, displaced);
+                        moved[to] = true;
+                        moved[idx] = true;
+                    }
+                }
+            }
+            self.generation += 1;
+        }
+    }
+
+    /// The box as RGBA bytes with one pixel per cell, ready to be put into
+    /// an `ImageData`, with empty cells transparent.
+    pub fn image(&self) -> Vec<u8> {
+        trace_span!("render");
+        (0..self.cells.len())
+            .flat_map(|idx| Material::from_cell(self.cells.get(idx)).color())
+            .collect()
+    }
+}
@@ -0,0 +1,317 @@
+//! Scripted lessons which drive a universe step by step, for building
+//! guided tutorials on top of the crate.
+//!
+//! A scenario is a list of steps: load a pattern, run some generations,
+//! wait for the user to do something, check a condition such as
+//! `population > 100`, or show a message. The frontend calls `advance`,
+//! which carries out steps until one needs the frontend, and reports what
+//! to do next as a `ScenarioEvent`.
+
+use crate::versioning::{self, Format};
+use crate::{parse_pattern, CellStorage, Universe};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// A number read from the universe by a condition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Quantity {
+    Population,
+    Generation,
+}
+
+/// A condition on the universe, such as `population > 100`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Condition {
+    quantity: Quantity,
+    comparison: &'static str,
+    value: u64,
+}
+
+const COMPARISONS: [&str; 6] = ["<=", ">=", "==", "!=", "<", ">"];
+
+impl Condition {
+    fn holds(self, universe: &Universe) -> bool {
+        let actual = match self.quantity {
+            Quantity::Population => universe.cells.population() as u64,
+            Quantity::Generation => universe.generation,
+        };
+        match self.comparison {
+            "<=" => actual <= self.value,
+            ">=" => actual >= self.value,
+            "==" => actual == self.value,
+            "!=" => actual != self.value,
+            "<" => actual < self.value,
+            _ => actual > self.value,
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    /// Parse a condition written as a quantity, a comparison and a number,
+    /// such as `population > 100` or `generation >= 30`.
+    fn from_str(condition: &str) -> Result<Condition, String> {
+        let invalid = || {
+            format!(
+                "the condition {} is not `population` or `generation` compared with a number",
+                condition
+            )
+        };
+        let (at, comparison) = COMPARISONS
+            .iter()
+            .filter_map(|&comparison| condition.find(comparison).map(|at| (at, comparison)))
+            .min_by_key(|&(at, comparison)| (at, std::cmp::Reverse(comparison.len())))
+            .ok_or_else(invalid)?;
+        let quantity = match condition[..at].trim() {
+            "population" => Quantity::Population,
+            "generation" => Quantity::Generation,
+            _ => return Err(invalid()),
+        };
+        let value = condition[at + comparison.len()..]
+            .trim()
+            .parse()
+            .map_err(|_| invalid())?;
+        Ok(Condition {
+            quantity,
+            comparison,
+            value,
+        })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let quantity = match self.quantity {
+            Quantity::Population => "population",
+            Quantity::Generation => "generation",
+        };
+        write!(f, "{} {} {}", quantity, self.comparison, self.value)
+    }
+}
+
+/// One step of a scenario, saved as JSON tagged by `step`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+enum Step {
+    /// Clear the universe and place a stamp, saved pattern or pattern text
+    /// with its top-left corner at a cell.
+    Load { pattern: String, row: u32, col: u32 },
+    /// Tick the universe a number of times.
+    Run { generations: u32 },
+    /// Wait until the frontend reports an action with `user_action`.
+    Wait { action: String },
+    /// Stop until a condition holds.
+    Check { condition: String },
+    /// Have the frontend show the message with an id.
+    Message { id: String },
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScenarioStatus {
+    /// Show the message named by the event's detail, then advance again.
+    Message = 0,
+    /// Wait for the action named by the event's detail, reported with
+    /// `user_action`.
+    Waiting = 1,
+    /// The condition in the event's detail doesn't hold yet, or a pattern
+    /// couldn't be loaded. Advancing again checks it again.
+    Blocked = 2,
+    /// Every step has been carried out.
+    Finished = 3,
+}
+
+/// What a scenario needs from the frontend before it can carry on.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioEvent {
+    status: ScenarioStatus,
+    /// The message id, action, condition or error, depending on the status.
+    detail: String,
+}
+
+#[wasm_bindgen]
+impl ScenarioEvent {
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> ScenarioStatus {
+        self.status
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn detail(&self) -> String {
+        self.detail.clone()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scenario {
+    steps: Vec<Step>,
+    /// Index of the next step to carry out.
+    position: usize,
+}
+
+impl Scenario {
+    fn event(status: ScenarioStatus, detail: &str) -> ScenarioEvent {
+        ScenarioEvent {
+            status,
+            detail: detail.to_string(),
+        }
+    }
+
+    fn load(universe: &mut Universe, pattern: &str, row: u32, col: u32) -> Result<(), String> {
+        universe.clear();
+        if universe.stamp_offsets(pattern).is_some() {
+            if !universe.stamp(pattern, row, col) {
+                return Err(format!("the pattern {} doesn't fit", pattern));
+            }
+        } else {
+            let parsed = parse_pattern(pattern)?;
+            universe.insert_pattern(&parsed, row, col);
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl Scenario {
+    /// Create a scenario with no steps, to be added in order.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Scenario {
+        Scenario::default()
+    }
+
+    /// Add a step which clears the universe and places a pattern with its
+    /// top-left corner at a cell. The pattern is the name of a stamp or
+    /// saved pattern, or pattern text as accepted by `parse_pattern`.
+    pub fn load_pattern(&mut self, pattern: &str, row: u32, col: u32) {
+        self.steps.push(Step::Load {
+            pattern: pattern.to_string(),
+            row,
+            col,
+        });
+    }
+
+    /// Add a step which ticks the universe a number of times.
+    pub fn run(&mut self, generations: u32) {
+        self.steps.push(Step::Run { generations });
+    }
+
+    /// Add a step which waits for the frontend to report an action, such as
+    /// `"clicked-play"`, with `user_action`.
+    pub fn wait_for(&mut self, action: &str) {
+        self.steps.push(Step::Wait {
+            action: action.to_string(),
+        });
+    }
+
+    /// Add a step which stops the scenario until a condition holds, such as
+    /// `population > 100` or `generation >= 30`. The quantity is
+    /// `population` or `generation`, and the comparison one of `<`, `<=`,
+    /// `==`, `!=`, `>=` and `>`.
+    pub fn check(&mut self, condition: &str) -> Result<(), String> {
+        let parsed: Condition = condition.parse()?;
+        self.steps.push(Step::Check {
+            condition: parsed.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Add a step which has the frontend show a message, given by an id
+    /// which the frontend looks up so lessons can be translated.
+    pub fn show_message(&mut self, id: &str) {
+        self.steps.push(Step::Message { id: id.to_string() });
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Index of the next step to carry out.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Go back to the first step.
+    pub fn restart(&mut self) {
+        self.position = 0;
+    }
+
+    /// Carry out steps on a universe until one needs the frontend: a
+    /// message to show, an action to wait for or a condition which doesn't
+    /// hold. Messages are passed once they are returned, while waits and
+    /// conditions stay until they are met.
+    pub fn advance(&mut self, universe: &mut Universe) -> ScenarioEvent {
+        while let Some(step) = self.steps.get(self.position) {
+            match step {
+                Step::Load { pattern, row, col } => {
+                    if let Err(err) = Scenario::load(universe, pattern, *row, *col) {
+                        return Scenario::event(ScenarioStatus::Blocked, &err);
+                    }
+                }
+                Step::Run { generations } => {
+                    for _ in 0..*generations {
+                        universe.tick();
+                    }
+                }
+                Step::Wait { action } => {
+                    return Scenario::event(ScenarioStatus::Waiting, action);
+                }
+                Step::Check { condition } => {
+                    // Conditions are checked when they are added or loaded.
+                    let holds = condition
+                        .parse::<Condition>()
+                        .is_ok_and(|parsed| parsed.holds(universe));
+                    if !holds {
+                        return Scenario::event(ScenarioStatus::Blocked, condition);
+                    }
+                }
+                Step::Message { id } => {
+                    self.position += 1;
+                    return Scenario::event(ScenarioStatus::Message, id);
+                }
+            }
+            self.position += 1;
+        }
+        Scenario::event(ScenarioStatus::Finished, "")
+    }
+
+    /// Report an action by the user. Returns true and moves past the wait
+    /// if the scenario is waiting for that action.
+    pub fn user_action(&mut self, action: &str) -> bool {
+        match self.steps.get(self.position) {
+            Some(Step::Wait { action: waiting }) if waiting == action => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The steps as JSON, with a `version` and a `steps` array, so lessons
+    /// can be written by hand or saved.
+    pub fn to_json(&self) -> String {
+        trace_span!("serialize");
+        serde_json::to_string(&versioning::wrap(Format::Scenario, &self.steps)).unwrap()
+    }
+
+    /// Read a scenario from JSON made by `to_json`, starting at its first
+    /// step. Each step is an object such as `{"step": "run",
+    /// "generations": 10}`, with `load`, `wait`, `check` and `message` steps
+    /// taking the same arguments as the methods which add them.
+    pub fn from_json(json: &str) -> Result<Scenario, String> {
+        trace_span!("deserialize");
+        let steps: Vec<Step> = serde_json::from_str(json)
+            .map_err(|err| err.to_string())
+            .and_then(|document| versioning::unwrap(Format::Scenario, document))?;
+        for step in steps.iter() {
+            if let Step::Check { condition } = step {
+                condition.parse::<Condition>()?;
+            }
+        }
+        Ok(Scenario { steps, position: 0 })
+    }
+}
@@ -0,0 +1,244 @@
+use crate::paint::line_cells;
+use crate::pattern::Pattern;
+use crate::rng::Rng;
+use crate::{Cell, CellStorage, Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// Live cells lifted out of the universe so they can be moved around before
+/// being dropped back in.
+pub(crate) struct Selection {
+    /// Positions of the selected cells, which may lie outside the universe
+    /// until they are wrapped back onto it when dropped.
+    cells: Vec<(i32, i32)>,
+}
+
+impl Selection {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.cells.capacity() * std::mem::size_of::<(i32, i32)>()
+    }
+}
+
+impl Universe {
+    /// The selected cells as a pattern, at their current positions.
+    pub(crate) fn selection_pattern(&self) -> Option<Pattern> {
+        self.selection
+            .as_ref()
+            .and_then(|selection| Pattern::from_cells(&selection.cells, None).ok())
+    }
+
+    /// The live cells connected to a cell through any of their eight
+    /// neighbours, wrapping around the edges.
+    pub(crate) fn connected_cells(&self, row: u32, col: u32) -> Vec<(u32, u32)> {
+        let mut found = Vec::new();
+        if !self.cells.is_alive(self.get_index(row, col)) {
+            return found;
+        }
+        let mut seen = vec![false; self.cells.len()];
+        seen[self.get_index(row, col)] = true;
+        let mut stack = vec![(row, col)];
+        while let Some((row, col)) = stack.pop() {
+            found.push((row, col));
+            for delta_row in [self.height - 1, 0, 1].iter().cloned() {
+                for delta_col in [self.width - 1, 0, 1].iter().cloned() {
+                    let neighbor_row = (row + delta_row) % self.height;
+                    let neighbor_col = (col + delta_col) % self.width;
+                    let idx = self.get_index(neighbor_row, neighbor_col);
+                    if !seen[idx] && self.cells.is_alive(idx) {
+                        seen[idx] = true;
+                        stack.push((neighbor_row, neighbor_col));
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Lift the object under a cell out of the universe so it can be moved
+    /// with `move_selection` and put back with `drop_selection`. The object
+    /// is every live cell connected to the cell, including diagonally.
+    ///
+    /// Any existing selection is dropped first. Returns false if the cell is
+    /// dead.
+    pub fn grab_object(&mut self, row: u32, col: u32) -> Result<bool, UniverseError> {
+        self.checked_index("grab_object", row, col)?;
+        self.drop_selection();
+        let cells = self.connected_cells(row, col);
+        if cells.is_empty() {
+            return Ok(false);
+        }
+        for &(row, col) in cells.iter() {
+            let idx = self.get_index(row, col);
+            self.cells.set(idx, Cell::DEAD);
+        }
+        self.cells_edited();
+        let cells = cells
+            .into_iter()
+            .map(|(row, col)| (row as i32, col as i32))
+            .collect();
+        self.selection = Some(Selection { cells });
+        Ok(true)
+    }
+
+    /// Move the selection by a number of rows and columns.
+    pub fn move_selection(&mut self, d_row: i32, d_col: i32) {
+        if let Some(selection) = &mut self.selection {
+            for cell in selection.cells.iter_mut() {
+                cell.0 += d_row;
+                cell.1 += d_col;
+            }
+        }
+    }
+
+    /// Put the selected cells back into the universe at their current
+    /// position, on top of whatever is already there.
+    pub fn drop_selection(&mut self) {
+        if let Some(selection) = self.selection.take() {
+            self.set_cells(&selection.cells);
+        }
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.selection.is_some()
+    }
+
+    /// The selected cells as `[row, col, row, col, ...]`, wrapped onto the
+    /// universe, for drawing the selection while it is being moved.
+    pub fn selection_cells(&self) -> Vec<u32> {
+        self.selection.as_ref().map_or(Vec::new(), |selection| {
+            selection
+                .cells
+                .iter()
+                .flat_map(|&(row, col)| {
+                    vec![
+                        row.rem_euclid(self.height as i32) as u32,
+                        col.rem_euclid(self.width as i32) as u32,
+                    ]
+                })
+                .collect()
+        })
+    }
+}
+
+/// The cells inside a polygon with vertices at the given cells, including
+/// the cells along its edges, clipped to a `width` by `height` universe.
+fn polygon_cells(vertices: &[(i32, i32)], width: u32, height: u32) -> Vec<(u32, u32)> {
+    let mut inside = vec![false; (width * height) as usize];
+    let mut mark = |row: i32, col: i32| {
+        if row >= 0 && col >= 0 && (row as u32) < height && (col as u32) < width {
+            inside[(row as u32 * width + col as u32) as usize] = true;
+        }
+    };
+    for (i, &from) in vertices.iter().enumerate() {
+        let to = vertices[(i + 1) % vertices.len()];
+        for (row, col) in line_cells(from, to) {
+            mark(row, col);
+        }
+    }
+    // Fill between pairs of the points where each row crosses the edges,
+    // counting a vertex on the row once by treating edges as half open.
+    for row in 0..height as i32 {
+        let mut crossings: Vec<f64> = Vec::new();
+        for (i, &(row_a, col_a)) in vertices.iter().enumerate() {
+            let (row_b, col_b) = vertices[(i + 1) % vertices.len()];
+            if (row_a <= row) != (row_b <= row) {
+                let t = (row - row_a) as f64 / (row_b - row_a) as f64;
+                crossings.push(col_a as f64 + t * (col_b - col_a) as f64);
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in crossings.chunks(2) {
+            if let [start, end] = *pair {
+                for col in start.ceil() as i32..=end.floor() as i32 {
+                    mark(row, col);
+                }
+            }
+        }
+    }
+    (0..height)
+        .flat_map(|row| (0..width).map(move |col| (row, col)))
+        .filter(|&(row, col)| inside[(row * width + col) as usize])
+        .collect()
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Select the region inside a polygon given as `[row, col, row, col,
+    /// ...]` for the region operations such as `fill_region`. The region
+    /// includes the cells along the polygon's edges.
+    pub fn select_polygon(&mut self, points: &[i32]) {
+        let vertices: Vec<_> = points
+            .chunks_exact(2)
+            .map(|point| (point[0], point[1]))
+            .collect();
+        self.region = if vertices.is_empty() {
+            None
+        } else {
+            Some(polygon_cells(&vertices, self.width, self.height))
+        };
+    }
+
+    pub fn deselect_region(&mut self) {
+        self.region = None;
+    }
+
+    /// The selected region as `[row, col, row, col, ...]`.
+    pub fn region_cells(&self) -> Vec<u32> {
+        self.region.as_ref().map_or(Vec::new(), |region| {
+            region
+                .iter()
+                .flat_map(|&(row, col)| vec![row, col])
+                .collect()
+        })
+    }
+
+    /// Make every cell in the selected region alive.
+    pub fn fill_region(&mut self) {
+        self.fill_selected_region(|_| Cell::ALIVE);
+    }
+
+    /// Kill every cell in the selected region.
+    pub fn clear_region(&mut self) {
+        self.fill_selected_region(|_| Cell::DEAD);
+    }
+
+    /// Set each cell in the selected region to be alive with probability
+    /// `density`, using the universe's seeded random number generator.
+    pub fn randomize_region(&mut self, density: f64) {
+        self.fill_selected_region(|rng| (rng.next_f64() < density).into());
+    }
+
+    /// Copy the live cells in the selected region into a new selection, as
+    /// if grabbed by `grab_object` but leaving the originals in place.
+    /// Returns false if the region has no live cells.
+    pub fn copy_region(&mut self) -> bool {
+        self.drop_selection();
+        let cells: Vec<_> = self.region.as_ref().map_or(Vec::new(), |region| {
+            region
+                .iter()
+                .filter(|&&(row, col)| self.cells.is_alive(self.get_index(row, col)))
+                .map(|&(row, col)| (row as i32, col as i32))
+                .collect()
+        });
+        if cells.is_empty() {
+            return false;
+        }
+        self.selection = Some(Selection { cells });
+        true
+    }
+}
+
+impl Universe {
+    fn fill_selected_region(&mut self, mut cell: impl FnMut(&mut Rng) -> Cell) {
+        if let Some(region) = self.region.take() {
+            for &(row, col) in region.iter() {
+                let idx = self.get_index(row, col);
+                self.cells.set(idx, cell(&mut self.rng));
+            }
+            self.region = Some(region);
+            self.cells_edited();
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! A frame of cells laid out for a renderer in another thread to read
+//! straight out of wasm memory, when the memory is a `SharedArrayBuffer`.
+//!
+//! The frame is an array of `u32` words:
+//!
+//! | Word | Contents |
+//! |------|----------|
+//! | 0 | Layout version, currently 1 |
+//! | 1 | Sequence number, odd while the frame is being written |
+//! | 2, 3 | Width and height |
+//! | 4, 5 | Generation, low word then high word |
+//! | 6–9 | Dirty rectangle since the last frame: top, left, bottom and right, with bottom and right exclusive |
+//! | 10 | Number of cell words |
+//! | 11 | Number of changes |
+//! | 12 | 1 if the changes were too many to list and everything should be redrawn |
+//! | 13–15 | Reserved |
+//! | 16… | Cell words, one bit per cell in row-major order, lowest bit first |
+//! | after the cells | Changes, each `(row * width + col) << 1` plus 1 if the cell was born |
+//!
+//! The reader should load the sequence number with `Atomics.load`, skip the
+//! frame if it is odd, copy what it needs and load the sequence number
+//! again, trying again if it has changed in between.
+
+use crate::{CellStorage, TickStats, Universe};
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+use wasm_bindgen::prelude::*;
+
+const VERSION: u32 = 1;
+const HEADER_WORDS: usize = 16;
+
+const SEQUENCE: usize = 1;
+const WIDTH: usize = 2;
+const HEIGHT: usize = 3;
+const GENERATION: usize = 4;
+const DIRTY: usize = 6;
+const CELL_WORDS: usize = 10;
+const CHANGES: usize = 11;
+const REDRAW: usize = 12;
+
+pub(crate) struct SharedFrame {
+    words: Vec<AtomicU32>,
+    /// The most changes listed in a frame.
+    change_capacity: usize,
+    /// Changes since the last frame was written, encoded as in the frame.
+    pending: Vec<u32>,
+    /// Set when the pending changes don't cover everything which changed.
+    redraw: bool,
+}
+
+impl SharedFrame {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        (self.words.capacity() + self.pending.capacity()) * std::mem::size_of::<u32>()
+    }
+
+    fn set(&self, word: usize, value: u32) {
+        self.words[word].store(value, Ordering::Relaxed);
+    }
+}
+
+impl Universe {
+    pub(crate) fn update_shared_frame(&mut self, stats: &TickStats) {
+        let width = self.width;
+        let frame = match &mut self.shared_frame {
+            Some(frame) => frame,
+            None => return,
+        };
+        if frame.pending.len() + stats.changes.len() > frame.change_capacity {
+            frame.redraw = true;
+        }
+        if !frame.redraw {
+            frame.pending.extend(
+                stats
+                    .changes
+                    .iter()
+                    .map(|&(row, col, born)| (row * width + col) << 1 | born as u32),
+            );
+        }
+    }
+
+    /// Write the current generation to the shared frame, if there is one.
+    pub(crate) fn write_shared_frame(&mut self) {
+        let mut frame = match self.shared_frame.take() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let cell_words = (self.width as usize * self.height as usize).div_ceil(32);
+        let len = HEADER_WORDS + cell_words + frame.change_capacity;
+        if frame.words.len() != len {
+            frame.words = (0..len).map(|_| AtomicU32::new(0)).collect();
+            frame.set(0, VERSION);
+        }
+        let size = |word: usize| frame.words[word].load(Ordering::Relaxed);
+        if size(WIDTH) != self.width || size(HEIGHT) != self.height {
+            frame.redraw = true;
+        }
+
+        let sequence = frame.words[SEQUENCE].load(Ordering::Relaxed);
+        frame.set(SEQUENCE, sequence.wrapping_add(1));
+        fence(Ordering::Release);
+
+        frame.set(WIDTH, self.width);
+        frame.set(HEIGHT, self.height);
+        frame.set(GENERATION, self.generation as u32);
+        frame.set(GENERATION + 1, (self.generation >> 32) as u32);
+        let dirty = if frame.redraw {
+            [0, 0, self.height, self.width]
+        } else {
+            let (rows, cols) = frame
+                .pending
+                .iter()
+                .map(|&change| ((change >> 1) / self.width, (change >> 1) % self.width))
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+            match (rows.iter().min(), cols.iter().min()) {
+                (Some(&top), Some(&left)) => [
+                    top,
+                    left,
+                    rows.iter().max().unwrap() + 1,
+                    cols.iter().max().unwrap() + 1,
+                ],
+                _ => [0; 4],
+            }
+        };
+        for (i, &edge) in dirty.iter().enumerate() {
+            frame.set(DIRTY + i, edge);
+        }
+        frame.set(CELL_WORDS, cell_words as u32);
+
+        let mut word = 0;
+        let mut bit = 0;
+        let mut next = HEADER_WORDS;
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.cells.is_alive(self.get_index(row, col)) {
+                    word |= 1 << bit;
+                }
+                bit += 1;
+                if bit == 32 {
+                    frame.set(next, word);
+                    next += 1;
+                    word = 0;
+                    bit = 0;
+                }
+            }
+        }
+        if bit > 0 {
+            frame.set(next, word);
+        }
+
+        let changes = if frame.redraw {
+            &[][..]
+        } else {
+            &frame.pending[..]
+        };
+        for (i, &change) in changes.iter().enumerate() {
+            frame.set(HEADER_WORDS + cell_words + i, change);
+        }
+        frame.set(CHANGES, changes.len() as u32);
+        frame.set(REDRAW, frame.redraw as u32);
+
+        frame.words[SEQUENCE].store(sequence.wrapping_add(2), Ordering::Release);
+        frame.pending.clear();
+        frame.redraw = false;
+        self.shared_frame = Some(frame);
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Keep a shared frame, laid out as described in the `shared_frame`
+    /// module, which is written after every call to `tick_many`. Frames list
+    /// up to `change_capacity` changed cells before asking for a full
+    /// redraw instead.
+    pub fn enable_shared_frame(&mut self, change_capacity: usize) {
+        self.shared_frame = Some(SharedFrame {
+            words: Vec::new(),
+            change_capacity,
+            pending: Vec::new(),
+            redraw: true,
+        });
+        self.write_shared_frame();
+    }
+
+    pub fn disable_shared_frame(&mut self) {
+        self.shared_frame = None;
+    }
+
+    /// Write the shared frame now, such as after editing cells, asking the
+    /// reader to redraw everything.
+    pub fn publish_shared_frame(&mut self) {
+        if let Some(frame) = &mut self.shared_frame {
+            frame.redraw = true;
+        }
+        self.write_shared_frame();
+    }
+
+    /// Pointer to the shared frame, or null if there is none. The frame
+    /// moves when the universe is resized, so this needs to be read again
+    /// afterwards.
+    pub fn shared_frame(&self) -> *const u32 {
+        match &self.shared_frame {
+            Some(frame) => frame.words.as_ptr() as *const u32,
+            None => std::ptr::null(),
+        }
+    }
+
+    /// Length of the shared frame in words.
+    pub fn shared_frame_len(&self) -> usize {
+        self.shared_frame
+            .as_ref()
+            .map_or(0, |frame| frame.words.len())
+    }
+}
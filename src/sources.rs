@@ -0,0 +1,95 @@
+use crate::{Cell, CellStorage, TickStats, Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+/// A cell whose state is forced after every tick.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    None = 0,
+    /// Always alive.
+    Source = 1,
+    /// Always dead.
+    Sink = 2,
+}
+
+impl Universe {
+    /// Force the cells at sources and sinks back to their states after the
+    /// rule has been applied. Walls are left as they are.
+    pub(crate) fn apply_anchors(&mut self, stats: &mut TickStats) {
+        let anchors = match self.anchors.take() {
+            Some(anchors) => anchors,
+            None => return,
+        };
+        let stride = (self.width + 2) as usize;
+        for (idx, &anchor) in anchors.iter().enumerate() {
+            let alive = match anchor {
+                1 => true,
+                2 => false,
+                _ => continue,
+            };
+            if self.cells.is_alive(idx) != alive && !self.is_wall(idx) {
+                self.cells.set(idx, alive.into());
+                stats.record_change((idx / stride - 1) as u32, (idx % stride - 1) as u32, alive);
+                if alive {
+                    stats.population += 1;
+                } else {
+                    stats.population -= 1;
+                }
+            }
+        }
+        self.anchors = Some(anchors);
+    }
+
+    fn set_anchor(&mut self, row: i32, col: i32, anchor: Anchor) {
+        let idx = self.get_index(
+            row.rem_euclid(self.height as i32) as u32,
+            col.rem_euclid(self.width as i32) as u32,
+        );
+        if self.is_wall(idx) {
+            return;
+        }
+        let size = self.cells.len();
+        self.anchors.get_or_insert_with(|| vec![0; size])[idx] = anchor as u8;
+        match anchor {
+            Anchor::Source => self.cells.set(idx, Cell::ALIVE),
+            Anchor::Sink => self.cells.set(idx, Cell::DEAD),
+            Anchor::None => {}
+        }
+        self.cells_edited();
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Make the cells under the brush centred on a cell into sources, which
+    /// are alive after every tick, or sinks, which are dead after every
+    /// tick. `Anchor::None` turns them back into ordinary cells. Walls are
+    /// skipped, and placing a wall removes any source or sink under it.
+    pub fn paint_anchor_at(&mut self, row: u32, col: u32, anchor: Anchor) {
+        for (d_row, d_col) in self.brush.offsets() {
+            self.set_anchor(row as i32 + d_row, col as i32 + d_col, anchor);
+        }
+    }
+
+    pub fn anchor_at(&self, row: u32, col: u32) -> Result<Anchor, UniverseError> {
+        let idx = self.checked_index("anchor_at", row, col)?;
+        Ok(
+            match self.anchors.as_ref().map_or(0, |anchors| anchors[idx]) {
+                1 => Anchor::Source,
+                2 => Anchor::Sink,
+                _ => Anchor::None,
+            },
+        )
+    }
+
+    pub fn clear_anchors(&mut self) {
+        self.anchors = None;
+    }
+
+    /// The sources and sinks, a byte per cell holding an `Anchor`, row by
+    /// row, as a `Uint8Array`.
+    pub fn anchors(&self) -> Vec<u8> {
+        let anchors = self.anchors.as_ref();
+        self.each_cell(|idx| anchors.map_or(0, |anchors| anchors[idx]))
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{CellStorage, TickStats, Universe};
+use wasm_bindgen::prelude::*;
+
+/// Colour of live cells behind the sparks, dimmed so the sparks stand out.
+const BACKGROUND_COLOR: [u8; 4] = [64, 64, 64, 255];
+const BIRTH_COLOR: [u8; 4] = [255, 255, 192, 255];
+const DEATH_COLOR: [u8; 4] = [160, 32, 32, 255];
+
+/// The cells which were born or died during the latest tick.
+#[derive(Default)]
+pub(crate) struct Sparks {
+    births: Vec<(u32, u32)>,
+    deaths: Vec<(u32, u32)>,
+}
+
+impl Sparks {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        (self.births.capacity() + self.deaths.capacity()) * std::mem::size_of::<(u32, u32)>()
+    }
+}
+
+impl Universe {
+    pub(crate) fn update_sparks(&mut self, stats: &TickStats) {
+        let sparks = match &mut self.sparks {
+            Some(sparks) => sparks,
+            None => return,
+        };
+        sparks.births.clear();
+        sparks.deaths.clear();
+        let mut changes = stats.changes.clone();
+        // Sorting is stable, so each cell's changes stay in order and only
+        // the first and last are needed to tell whether it ended up changed.
+        changes.sort_by_key(|&(row, col, _)| (row, col));
+        for cell_changes in changes.chunk_by(|a, b| (a.0, a.1) == (b.0, b.1)) {
+            let (row, col, first) = cell_changes[0];
+            let (_, _, last) = cell_changes[cell_changes.len() - 1];
+            match (first, last) {
+                (true, true) => sparks.births.push((row, col)),
+                (false, false) => sparks.deaths.push((row, col)),
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn clear_sparks(&mut self) {
+        if let Some(sparks) = &mut self.sparks {
+            *sparks = Sparks::default();
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Start or stop keeping the births and deaths of each tick, for drawing
+    /// the latest activity with `spark_image`.
+    pub fn set_spark_mode(&mut self, enabled: bool) {
+        self.sparks = if enabled {
+            Some(Sparks::default())
+        } else {
+            None
+        };
+    }
+
+    /// The cells born during the latest tick as `[row, col, row, col,
+    /// ...]`, or nothing if spark mode is off.
+    pub fn spark_births(&self) -> Vec<u32> {
+        self.sparks.as_ref().map_or(Vec::new(), |sparks| {
+            sparks
+                .births
+                .iter()
+                .flat_map(|&(row, col)| vec![row, col])
+                .collect()
+        })
+    }
+
+    /// The cells which died during the latest tick as `[row, col, row, col,
+    /// ...]`, or nothing if spark mode is off.
+    pub fn spark_deaths(&self) -> Vec<u32> {
+        self.sparks.as_ref().map_or(Vec::new(), |sparks| {
+            sparks
+                .deaths
+                .iter()
+                .flat_map(|&(row, col)| vec![row, col])
+                .collect()
+        })
+    }
+
+    /// The universe as RGBA bytes with one pixel per cell, ready to be put
+    /// into an `ImageData`. Live cells are dimmed and dead cells are
+    /// transparent, while cells born during the latest tick are bright and
+    /// cells which died are dark red, so activity stands out on large
+    /// universes.
+    pub fn spark_image(&self) -> Vec<u8> {
+        trace_span!("render");
+        let width = self.width as usize;
+        let stride = width + 2;
+        let mut pixels = vec![0; width * self.height as usize * 4];
+        for idx in self.cells.live_indices() {
+            let i = ((idx / stride - 1) * width + idx % stride - 1) * 4;
+            pixels[i..i + 4].copy_from_slice(&BACKGROUND_COLOR);
+        }
+        if let Some(sparks) = &self.sparks {
+            let mut paint = |cells: &[(u32, u32)], color: &[u8; 4]| {
+                for &(row, col) in cells {
+                    let i = (row as usize * width + col as usize) * 4;
+                    pixels[i..i + 4].copy_from_slice(color);
+                }
+            };
+            paint(&sparks.births, &BIRTH_COLOR);
+            paint(&sparks.deaths, &DEATH_COLOR);
+        }
+        pixels
+    }
+}
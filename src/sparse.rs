@@ -0,0 +1,116 @@
+use crate::{Cell, CellStorage, TickStats, Universe};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Universes with fewer cells than this are always ticked densely.
+const MIN_SPARSE_CELLS: u32 = 256 * 256;
+
+/// Density below which a large universe switches to sparse ticking.
+const ENTER_SPARSE_DENSITY: f64 = 0.02;
+
+/// Density above which a sparse universe switches back to dense ticking.
+/// This is higher than `ENTER_SPARSE_DENSITY` so universes hovering around
+/// the threshold don't flip back and forth every tick.
+const LEAVE_SPARSE_DENSITY: f64 = 0.05;
+
+impl Universe {
+    /// Choose how to tick the next generation based on the population.
+    pub(crate) fn update_backend(&mut self, population: usize) {
+        let size = self.width * self.height;
+        let density = population as f64 / size.max(1) as f64;
+        self.sparse = size >= MIN_SPARSE_CELLS
+            && if self.sparse {
+                density <= LEAVE_SPARSE_DENSITY
+            } else {
+                density < ENTER_SPARSE_DENSITY
+            };
+    }
+
+    /// Advance by one generation by only visiting live cells and their
+    /// neighbours.
+    pub(crate) fn step_sparse(&mut self) -> TickStats {
+        let stride = (self.width + 2) as usize;
+        let live = self.cells.live_indices();
+
+        // Each live cell counts towards the neighbours of itself and of any
+        // copies of it which the boundaries place in the halo.
+        let (height, width) = (self.height as i32, self.width as i32);
+        let mut counts: HashMap<usize, u8> = HashMap::with_capacity(live.len() * 4);
+        for &idx in live.iter() {
+            let row = (idx / stride - 1) as i32;
+            let col = (idx % stride - 1) as i32;
+            let rows = self.row_boundary.images(row, self.height);
+            let cols = self.col_boundary.images(col, self.width);
+            for image_row in rows.iter().flatten() {
+                for image_col in cols.iter().flatten() {
+                    for delta_row in -1..=1 {
+                        for delta_col in -1..=1 {
+                            if delta_row == 0 && delta_col == 0 {
+                                continue;
+                            }
+                            let neighbor_row = image_row + delta_row;
+                            let neighbor_col = image_col + delta_col;
+                            if neighbor_row < 0
+                                || neighbor_row >= height
+                                || neighbor_col < 0
+                                || neighbor_col >= width
+                            {
+                                continue;
+                            }
+                            let neighbor = self.get_index(neighbor_row as u32, neighbor_col as u32);
+                            *counts.entry(neighbor).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        let deaths: Vec<usize> = live
+            .iter()
+            .cloned()
+            .filter(|&idx| {
+                !self.is_wall(idx)
+                    && !self.is_frozen((idx / stride - 1) as u32, (idx % stride - 1) as u32)
+                    && !self.rule.next(true, counts.get(&idx).cloned().unwrap_or(0))
+            })
+            .collect();
+        let births: Vec<usize> = counts
+            .iter()
+            .filter(|&(&idx, &count)| {
+                !self.cells.is_alive(idx)
+                    && !self.is_wall(idx)
+                    && self.rule.next(false, count)
+                    && self.birth_allowed(idx)
+                    && !self.is_frozen((idx / stride - 1) as u32, (idx % stride - 1) as u32)
+            })
+            .map(|(&idx, _)| idx)
+            .collect();
+        // Work out the states of the new cells before any cells change, so
+        // they only depend on the previous generation.
+        let born: Vec<Cell> = births
+            .iter()
+            .map(|&idx| self.born_cell((idx / stride - 1) as u32, (idx % stride - 1) as u32))
+            .collect();
+        let mut stats = TickStats::default();
+        for &idx in deaths.iter() {
+            self.cells.set(idx, Cell::DEAD);
+            stats.record_change((idx / stride - 1) as u32, (idx % stride - 1) as u32, false);
+        }
+        for (&idx, &cell) in births.iter().zip(born.iter()) {
+            self.cells.set(idx, cell);
+            stats.record_change((idx / stride - 1) as u32, (idx % stride - 1) as u32, true);
+        }
+        stats.population = live.len() - deaths.len() + births.len();
+        stats
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Whether the universe is currently sparse enough that ticks only
+    /// visit live cells and their neighbours. This is chosen automatically
+    /// and doesn't change the results of ticking.
+    pub fn is_sparse(&self) -> bool {
+        self.sparse
+    }
+}
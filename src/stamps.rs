@@ -0,0 +1,124 @@
+use crate::{Boundary, Universe};
+use wasm_bindgen::prelude::*;
+
+/// The live cells of a built-in pattern as offsets from its anchor, the cell
+/// it is placed at.
+pub(crate) fn stamp_cells(name: &str) -> Option<Vec<(i32, i32)>> {
+    match name {
+        // Anchored on its bottom-right cell.
+        "glider" => Some(vec![(-2, -1), (-1, 0), (0, -2), (0, -1), (0, 0)]),
+        // Anchored on its centre.
+        "pulsar" => {
+            let quadrant = [
+                (1, 2),
+                (1, 3),
+                (1, 4),
+                (2, 1),
+                (3, 1),
+                (4, 1),
+                (6, 2),
+                (6, 3),
+                (6, 4),
+                (2, 6),
+                (3, 6),
+                (4, 6),
+            ];
+            let mut cells = Vec::new();
+            for (q_row, q_col) in [(1, 1), (1, -1), (-1, 1), (-1, -1)].iter().cloned() {
+                cells.extend(
+                    quadrant
+                        .iter()
+                        .map(|&(row, col)| (q_row * row, q_col * col)),
+                );
+            }
+            Some(cells)
+        }
+        _ => None,
+    }
+}
+
+/// Where a stamp would land if placed, for drawing guides before it is
+/// committed.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct PlacementGuide {
+    /// Row of the top edge of the bounding box. This can be negative or
+    /// beyond the bottom edge, in which case the stamp wraps.
+    pub row: i32,
+    /// Column of the left edge of the bounding box, which can also wrap.
+    pub col: i32,
+    pub width: u32,
+    pub height: u32,
+    /// Offset of the anchor cell from the top-left corner of the bounding
+    /// box.
+    pub anchor_row: u32,
+    pub anchor_col: u32,
+    /// Whether the stamp can be placed here without overlapping itself or
+    /// crossing an edge which doesn't wrap.
+    pub fits: bool,
+}
+
+impl Universe {
+    /// The cells of a pattern saved in the library or, failing that, a
+    /// built-in stamp.
+    pub(crate) fn stamp_offsets(&self, name: &str) -> Option<Vec<(i32, i32)>> {
+        match self.patterns.get(name) {
+            Some(pattern) => Some(pattern.offsets()),
+            None => stamp_cells(name),
+        }
+    }
+
+    fn placement(&self, cells: &[(i32, i32)], row: u32, col: u32) -> PlacementGuide {
+        let min_row = cells.iter().map(|&(row, _)| row).min().unwrap_or(0);
+        let max_row = cells.iter().map(|&(row, _)| row).max().unwrap_or(0);
+        let min_col = cells.iter().map(|&(_, col)| col).min().unwrap_or(0);
+        let max_col = cells.iter().map(|&(_, col)| col).max().unwrap_or(0);
+        let width = (max_col - min_col + 1) as u32;
+        let height = (max_row - min_row + 1) as u32;
+        let (top, left) = (row as i32 + min_row, col as i32 + min_col);
+        // Stamps can only wrap across edges which are joined.
+        let fits_axis = |start: i32, len: u32, size: u32, boundary: Boundary| {
+            len <= size
+                && (boundary == Boundary::Wrap || (start >= 0 && start as u32 + len <= size))
+        };
+        PlacementGuide {
+            row: top,
+            col: left,
+            width,
+            height,
+            anchor_row: -min_row as u32,
+            anchor_col: -min_col as u32,
+            fits: fits_axis(top, height, self.height, self.row_boundary)
+                && fits_axis(left, width, self.width, self.col_boundary),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Where the named stamp or saved pattern would be placed with its
+    /// anchor on a cell, or `undefined` if there is nothing with that name.
+    pub fn placement_guide(&self, name: &str, row: u32, col: u32) -> Option<PlacementGuide> {
+        self.stamp_offsets(name)
+            .map(|cells| self.placement(&cells, row, col))
+    }
+
+    /// Place the named stamp or saved pattern with its anchor on a cell.
+    /// Returns false without changing anything if the name is unknown or the
+    /// stamp doesn't fit.
+    pub fn stamp(&mut self, name: &str, row: u32, col: u32) -> bool {
+        let cells = match self.stamp_offsets(name) {
+            Some(cells) => cells,
+            None => return false,
+        };
+        if !self.placement(&cells, row, col).fits {
+            return false;
+        }
+        let cells: Vec<_> = cells
+            .iter()
+            .map(|&(d_row, d_col)| (row as i32 + d_row, col as i32 + d_col))
+            .collect();
+        self.set_cells(&cells);
+        true
+    }
+}
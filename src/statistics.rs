@@ -0,0 +1,53 @@
+//! Live statistics for frontends to show while the universe runs.
+
+use crate::{CellStorage, TickStats, Universe};
+use wasm_bindgen::prelude::*;
+
+impl Universe {
+    pub(crate) fn update_last_tick(&mut self, stats: &TickStats) {
+        self.last_births = stats.births as u32;
+        self.last_deaths = stats.deaths as u32;
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Number of live cells.
+    pub fn population(&self) -> u32 {
+        self.cells.population() as u32
+    }
+
+    /// Number of cells born during the latest tick.
+    pub fn births_last_tick(&self) -> u32 {
+        self.last_births
+    }
+
+    /// Number of cells which died during the latest tick.
+    pub fn deaths_last_tick(&self) -> u32 {
+        self.last_deaths
+    }
+
+    /// The smallest rectangle containing every live cell as `[x, y, w, h]`,
+    /// with its top-left corner at column `x` and row `y`, or `undefined` if
+    /// there are no live cells. A pattern which wraps around an edge gives a
+    /// box stretching across the universe.
+    pub fn live_bounding_box(&self) -> Option<Vec<u32>> {
+        let mut live = self.iter_live_cells();
+        let (row, col) = live.next()?;
+        let (top, left, bottom, right) = live.fold(
+            (row, col, row, col),
+            |(top, left, bottom, right), (row, col)| {
+                (top.min(row), left.min(col), bottom.max(row), right.max(col))
+            },
+        );
+        Some(vec![left, top, right - left + 1, bottom - top + 1])
+    }
+
+    /// Whether the universe has settled into a still life or an oscillator
+    /// with a period no longer than the number of generations remembered by
+    /// `set_period_detection`, which must be on. Frontends can pause when
+    /// this becomes true, and show `detected_period` as the period.
+    pub fn is_stable(&self) -> bool {
+        self.detected_period().is_some()
+    }
+}
@@ -0,0 +1,291 @@
+use crate::Cell;
+
+/// A buffer holding the state of every cell in a universe.
+///
+/// Two state rules only ever use `Cell::ALIVE` and can be stored a bit per
+/// cell, while multi-state rules need a byte per cell.
+pub trait CellStorage {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, idx: usize) -> Cell;
+
+    fn set(&mut self, idx: usize, cell: Cell);
+
+    fn is_alive(&self, idx: usize) -> bool {
+        self.get(idx).is_alive()
+    }
+
+    /// Number of cells in a state other than dead.
+    fn population(&self) -> usize;
+
+    /// Number of cells in a state other than dead with indices from `start`
+    /// up to but not including `end`.
+    fn population_between(&self, start: usize, end: usize) -> usize {
+        (start..end).filter(|&idx| self.is_alive(idx)).count()
+    }
+
+    /// Indices of the cells in a state other than dead, in ascending order.
+    fn live_indices(&self) -> Vec<usize> {
+        self.iter_live_indices().collect()
+    }
+
+    /// Iterate over the indices of the cells in a state other than dead, in
+    /// ascending order, without collecting them first.
+    fn iter_live_indices(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new((0..self.len()).filter(move |&idx| self.is_alive(idx)))
+    }
+
+    /// Number of bits each cell occupies in `as_bytes`.
+    fn bits_per_cell(&self) -> u32;
+
+    /// Size in bytes of the words the buffer is made up of.
+    fn word_size(&self) -> u32;
+
+    /// The underlying buffer, for frontends reading the cells directly out
+    /// of wasm memory.
+    fn as_bytes(&self) -> &[u8];
+}
+
+/// A bit per cell, packed into 64-bit words so that whole words of cells can
+/// be skipped or counted at once.
+///
+/// Cell `i` is bit `i % 64` of word `i / 64`, so on little-endian targets
+/// such as wasm it is also bit `i % 8` of byte `i / 8` of `as_bytes`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitCells {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitCells {
+    const WORD_BITS: usize = 64;
+
+    /// Storage for `len` dead cells.
+    pub fn with_len(len: usize) -> BitCells {
+        BitCells {
+            words: vec![0; len.div_ceil(Self::WORD_BITS)],
+            len,
+        }
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+impl CellStorage for BitCells {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, idx: usize) -> Cell {
+        assert!(idx < self.len, "cell index {} out of bounds", idx);
+        Cell((self.words[idx / Self::WORD_BITS] >> (idx % Self::WORD_BITS)) as u8 & 1)
+    }
+
+    /// Any state other than dead is stored as alive.
+    fn set(&mut self, idx: usize, cell: Cell) {
+        assert!(idx < self.len, "cell index {} out of bounds", idx);
+        let mask = 1 << (idx % Self::WORD_BITS);
+        if cell.is_alive() {
+            self.words[idx / Self::WORD_BITS] |= mask;
+        } else {
+            self.words[idx / Self::WORD_BITS] &= !mask;
+        }
+    }
+
+    fn population(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Counts whole words at a time, masking off the cells outside the range
+    /// in the first and last words.
+    fn population_between(&self, start: usize, end: usize) -> usize {
+        if start >= end {
+            return 0;
+        }
+        let (first, last) = (start / Self::WORD_BITS, (end - 1) / Self::WORD_BITS);
+        let mut count = 0;
+        for i in first..=last {
+            let mut word = self.words[i];
+            if i == first {
+                word &= !0 << (start % Self::WORD_BITS);
+            }
+            if i == last {
+                word &= !0 >> (Self::WORD_BITS - 1 - (end - 1) % Self::WORD_BITS);
+            }
+            count += word.count_ones() as usize;
+        }
+        count
+    }
+
+    /// Skips over whole words of dead cells at a time.
+    fn iter_live_indices(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(
+            self.words
+                .iter()
+                .enumerate()
+                .filter(|&(_, &word)| word != 0)
+                .flat_map(|(i, &word)| {
+                    let mut word = word;
+                    std::iter::from_fn(move || {
+                        if word == 0 {
+                            return None;
+                        }
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        Some(i * Self::WORD_BITS + bit)
+                    })
+                }),
+        )
+    }
+
+    fn bits_per_cell(&self) -> u32 {
+        1
+    }
+
+    fn word_size(&self) -> u32 {
+        std::mem::size_of::<u64>() as u32
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        let len = std::mem::size_of_val(self.words.as_slice());
+        unsafe { std::slice::from_raw_parts(self.words.as_ptr() as *const u8, len) }
+    }
+}
+
+impl CellStorage for Vec<u8> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn get(&self, idx: usize) -> Cell {
+        Cell(self[idx])
+    }
+
+    fn set(&mut self, idx: usize, cell: Cell) {
+        self[idx] = cell.state();
+    }
+
+    fn population(&self) -> usize {
+        self.iter().filter(|&&state| state != 0).count()
+    }
+
+    fn bits_per_cell(&self) -> u32 {
+        8
+    }
+
+    fn word_size(&self) -> u32 {
+        1
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// The storage backing a universe, chosen by the number of states its rule
+/// needs.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cells {
+    Bits(BitCells),
+    Bytes(Vec<u8>),
+}
+
+impl Cells {
+    /// Storage for `size` dead cells, each able to take one of `states`
+    /// states.
+    pub fn with_states(states: u32, size: usize) -> Cells {
+        if states <= 2 {
+            Cells::Bits(BitCells::with_len(size))
+        } else {
+            Cells::Bytes(vec![0; size])
+        }
+    }
+
+    /// Make this a copy of `source`, reusing the existing buffer when the
+    /// two are the same kind and size so that nothing is allocated.
+    pub fn copy_from(&mut self, source: &Cells) {
+        match (self, source) {
+            (Cells::Bits(bits), Cells::Bits(source)) if bits.len == source.len => {
+                bits.words.copy_from_slice(&source.words)
+            }
+            (Cells::Bytes(bytes), Cells::Bytes(source)) if bytes.len() == source.len() => {
+                bytes.copy_from_slice(source)
+            }
+            (this, source) => *this = source.clone(),
+        }
+    }
+}
+
+impl CellStorage for Cells {
+    fn len(&self) -> usize {
+        match self {
+            Cells::Bits(bits) => CellStorage::len(bits),
+            Cells::Bytes(bytes) => CellStorage::len(bytes),
+        }
+    }
+
+    fn get(&self, idx: usize) -> Cell {
+        match self {
+            Cells::Bits(bits) => CellStorage::get(bits, idx),
+            Cells::Bytes(bytes) => CellStorage::get(bytes, idx),
+        }
+    }
+
+    fn set(&mut self, idx: usize, cell: Cell) {
+        match self {
+            Cells::Bits(bits) => CellStorage::set(bits, idx, cell),
+            Cells::Bytes(bytes) => CellStorage::set(bytes, idx, cell),
+        }
+    }
+
+    fn population(&self) -> usize {
+        match self {
+            Cells::Bits(bits) => bits.population(),
+            Cells::Bytes(bytes) => bytes.population(),
+        }
+    }
+
+    fn population_between(&self, start: usize, end: usize) -> usize {
+        match self {
+            Cells::Bits(bits) => bits.population_between(start, end),
+            Cells::Bytes(bytes) => bytes.population_between(start, end),
+        }
+    }
+
+    fn iter_live_indices(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            Cells::Bits(bits) => bits.iter_live_indices(),
+            Cells::Bytes(bytes) => bytes.iter_live_indices(),
+        }
+    }
+
+    fn bits_per_cell(&self) -> u32 {
+        match self {
+            Cells::Bits(bits) => bits.bits_per_cell(),
+            Cells::Bytes(bytes) => bytes.bits_per_cell(),
+        }
+    }
+
+    fn word_size(&self) -> u32 {
+        match self {
+            Cells::Bits(bits) => bits.word_size(),
+            Cells::Bytes(bytes) => bytes.word_size(),
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            Cells::Bits(bits) => bits.as_bytes(),
+            Cells::Bytes(bytes) => bytes.as_bytes(),
+        }
+    }
+}
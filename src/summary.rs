@@ -0,0 +1,135 @@
+use crate::{CellStorage, TickStats, Universe};
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// Number of `u32` fields in each summary record:
+///
+/// 0. generation (lowest 32 bits)
+/// 1. population
+/// 2. births
+/// 3. deaths
+/// 4. state hash (lowest 32 bits)
+/// 5. state hash (highest 32 bits)
+/// 6. column of the top-left corner of the cells which changed
+/// 7. row of the top-left corner of the cells which changed
+/// 8. width of the cells which changed, or 0 if nothing changed
+/// 9. height of the cells which changed, or 0 if nothing changed
+const SUMMARY_FIELDS: usize = 10;
+
+/// A ring buffer of packed per-generation records.
+pub(crate) struct Summary {
+    capacity: usize,
+    records: VecDeque<[u32; SUMMARY_FIELDS]>,
+}
+
+impl Summary {
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.records.capacity() * std::mem::size_of::<[u32; SUMMARY_FIELDS]>()
+    }
+}
+
+impl Universe {
+    pub(crate) fn update_summary(&mut self, stats: &TickStats) {
+        if self.summary.is_none() {
+            return;
+        }
+        let hash = self.state_hash();
+        let (x, y, w, h) = match stats.dirty {
+            Some((min_row, min_col, max_row, max_col)) => (
+                min_col,
+                min_row,
+                max_col - min_col + 1,
+                max_row - min_row + 1,
+            ),
+            None => (0, 0, 0, 0),
+        };
+        let record = [
+            self.generation as u32,
+            stats.population as u32,
+            stats.births as u32,
+            stats.deaths as u32,
+            hash as u32,
+            (hash >> 32) as u32,
+            x,
+            y,
+            w,
+            h,
+        ];
+        if let Some(summary) = &mut self.summary {
+            if summary.records.len() == summary.capacity {
+                summary.records.pop_front();
+            }
+            summary.records.push_back(record);
+        }
+    }
+
+    /// A 64-bit FNV-1a hash of the cells.
+    pub(crate) fn state_hash(&self) -> u64 {
+        self.cells
+            .as_bytes()
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            })
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Keep a summary record for each of the last `capacity` generations.
+    /// A capacity of 0 turns the summary off.
+    pub fn set_summary_capacity(&mut self, capacity: usize) {
+        self.summary = if capacity == 0 {
+            None
+        } else {
+            Some(Summary {
+                capacity,
+                records: VecDeque::with_capacity(capacity),
+            })
+        };
+    }
+
+    /// Number of `u32` fields in each record returned by `summary`.
+    pub fn summary_fields(&self) -> usize {
+        SUMMARY_FIELDS
+    }
+
+    /// The summary records, oldest first, packed one after another.
+    ///
+    /// Each record holds the generation, population, births, deaths, the
+    /// state hash as two 32-bit halves (low first), and the bounding box of
+    /// the cells which changed as `x, y, width, height`.
+    pub fn summary(&self) -> Vec<u32> {
+        self.summary.as_ref().map_or(Vec::new(), |summary| {
+            summary
+                .records
+                .iter()
+                .flat_map(|record| record.to_vec())
+                .collect()
+        })
+    }
+
+    /// The summary records as CSV with a header row, oldest first, for
+    /// analysis in a spreadsheet. The hash is written as 16 hex digits.
+    pub fn export_stats_csv(&self) -> String {
+        trace_span!("serialize");
+        let mut csv = String::from("generation,population,births,deaths,hash,x,y,width,height\n");
+        let records = self.summary.as_ref().map(|summary| &summary.records);
+        for record in records.into_iter().flatten() {
+            let hash = (record[5] as u64) << 32 | record[4] as u64;
+            csv.push_str(&format!(
+                "{},{},{},{},{:016x},{},{},{},{}\n",
+                record[0],
+                record[1],
+                record[2],
+                record[3],
+                hash,
+                record[6],
+                record[7],
+                record[8],
+                record[9]
+            ));
+        }
+        csv
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{CellStorage, TickStats, Universe};
+use wasm_bindgen::prelude::*;
+
+impl Universe {
+    /// Flip each cell with probability `temperature`.
+    ///
+    /// Rather than drawing a random number for every cell, the gaps between
+    /// flipped cells are drawn from a geometric distribution, so low
+    /// temperatures are cheap even in large universes.
+    pub(crate) fn apply_temperature(&mut self, stats: &mut TickStats) {
+        let p = self.temperature;
+        if p <= 0.0 {
+            return;
+        }
+        let size = self.width as usize * self.height as usize;
+        let mut i = 0;
+        loop {
+            if i >= size {
+                return;
+            }
+            if p < 1.0 {
+                let u = 1.0 - self.rng.next_f64();
+                // Kept as a float until it is known to fit, since tiny
+                // temperatures give gaps beyond the range of any integer.
+                let gap = u.ln() / (-p).ln_1p();
+                if gap >= (size - i) as f64 {
+                    return;
+                }
+                i += gap as usize;
+            }
+            let (row, col) = (i as u32 / self.width, i as u32 % self.width);
+            let idx = self.get_index(row, col);
+            if !self.is_wall(idx) {
+                let alive = !self.cells.is_alive(idx);
+                self.cells.set(idx, alive.into());
+                stats.record_change(row, col, alive);
+                if alive {
+                    stats.population += 1;
+                } else {
+                    stats.population -= 1;
+                }
+            }
+            i += 1;
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Flip each cell with probability `p` after every tick, using the
+    /// seeded random number generator, to see how much noise patterns can
+    /// survive. A temperature of 0 turns the noise off.
+    pub fn set_temperature(&mut self, p: f64) {
+        self.temperature = p.clamp(0.0, 1.0);
+    }
+
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+}
@@ -0,0 +1,53 @@
+use crate::Universe;
+use wasm_bindgen::prelude::*;
+
+/// A rectangle of cells which only change every `period` ticks.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TimeZone {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    /// 0 freezes the zone entirely.
+    period: u32,
+}
+
+impl Universe {
+    /// Whether a cell is held in its current state for the coming tick.
+    ///
+    /// Cells in a zone still count as neighbours of the cells around them,
+    /// so the border between zones behaves as if time outside had stopped
+    /// for the frozen cells.
+    pub(crate) fn is_frozen(&self, row: u32, col: u32) -> bool {
+        let tick = self.generation + 1;
+        self.time_zones.iter().any(|zone| {
+            (zone.period == 0 || !tick.is_multiple_of(zone.period as u64))
+                && row >= zone.y
+                && row - zone.y < zone.h
+                && col >= zone.x
+                && col - zone.x < zone.w
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Make the cells in the `w` by `h` rectangle with its top-left corner
+    /// at column `x` and row `y` only change every `period` ticks. A period
+    /// of 0 freezes them, for example while editing elsewhere.
+    pub fn add_time_zone(&mut self, x: u32, y: u32, w: u32, h: u32, period: u32) {
+        self.time_zones.push(TimeZone { x, y, w, h, period });
+    }
+
+    pub fn clear_time_zones(&mut self) {
+        self.time_zones.clear();
+    }
+
+    /// The time zones as `[x, y, w, h, period, x, y, w, h, period, ...]`.
+    pub fn time_zones(&self) -> Vec<u32> {
+        self.time_zones
+            .iter()
+            .flat_map(|zone| vec![zone.x, zone.y, zone.w, zone.h, zone.period])
+            .collect()
+    }
+}
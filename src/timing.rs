@@ -0,0 +1,98 @@
+use crate::Universe;
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// Number of the most recent tick durations kept for working out
+/// percentiles.
+const SAMPLES: usize = 1024;
+
+/// Durations of ticks in milliseconds.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimingStats {
+    /// Number of ticks timed since the statistics were last reset.
+    pub count: u32,
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+    /// Percentiles of the most recent ticks.
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+/// Tick durations collected since the statistics were last reset.
+#[derive(Default)]
+pub(crate) struct TickTimer {
+    count: u32,
+    total: f64,
+    min: f64,
+    max: f64,
+    recent: VecDeque<f64>,
+}
+
+impl TickTimer {
+    pub(crate) fn record(&mut self, duration: f64) {
+        if self.count == 0 {
+            self.min = duration;
+            self.max = duration;
+        } else {
+            self.min = self.min.min(duration);
+            self.max = self.max.max(duration);
+        }
+        self.count += 1;
+        self.total += duration;
+        if self.recent.len() == SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(duration);
+    }
+
+    fn stats(&self) -> TimingStats {
+        if self.count == 0 {
+            return TimingStats::default();
+        }
+        let mut sorted: Vec<f64> = self.recent.iter().cloned().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        // Nearest-rank percentiles.
+        let percentile = |p: usize| sorted[(p * sorted.len()).div_ceil(100).max(1) - 1];
+        TimingStats {
+            count: self.count,
+            min: self.min,
+            mean: self.total / self.count as f64,
+            max: self.max,
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// The current time in milliseconds, from `Performance.now` in the browser.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// How long ticks have taken since the universe was created or
+    /// `reset_timing_stats` was last called.
+    pub fn timing_stats(&self) -> TimingStats {
+        self.tick_timer.stats()
+    }
+
+    pub fn reset_timing_stats(&mut self) {
+        self.tick_timer = TickTimer::default();
+    }
+}
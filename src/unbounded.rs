@@ -0,0 +1,195 @@
+//! Life on an unbounded plane, where spaceships fly off forever instead of
+//! wrapping around.
+//!
+//! Only the live cells are stored, so the size of the plane is limited only
+//! by memory. Positions are given as `i32` rows and columns, which can be
+//! negative, and frontends pan and zoom by asking for the cells in the
+//! rectangle they are showing with `cells_in_rect`.
+
+use crate::rule::Rule;
+use crate::{Pattern, Universe};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct SparseUniverse {
+    rule: Rule,
+    /// Number of ticks since the universe was created.
+    generation: u64,
+    /// The `(row, col)` of each live cell. These are wider than positions
+    /// given to methods so that stepping never overflows.
+    cells: HashSet<(i64, i64)>,
+    /// Buffers reused from tick to tick: the live neighbours of each cell
+    /// which could be alive next, and the set the next generation is built
+    /// in.
+    counts: HashMap<(i64, i64), u8>,
+    next_cells: HashSet<(i64, i64)>,
+}
+
+impl Default for SparseUniverse {
+    fn default() -> SparseUniverse {
+        SparseUniverse {
+            rule: Rule::CONWAY,
+            generation: 0,
+            cells: HashSet::new(),
+            counts: HashMap::new(),
+            next_cells: HashSet::new(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl SparseUniverse {
+    /// Create an empty plane running Conway's Game of Life.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SparseUniverse {
+        crate::utils::set_panic_hook();
+        SparseUniverse::default()
+    }
+
+    /// Create a plane with the live cells and rule of a universe, with its
+    /// top-left corner at row and column 0.
+    pub fn from_universe(universe: &Universe) -> SparseUniverse {
+        SparseUniverse {
+            rule: universe.rule,
+            generation: universe.generation,
+            cells: universe
+                .iter_live_cells()
+                .map(|(row, col)| (row as i64, col as i64))
+                .collect(),
+            ..SparseUniverse::default()
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u32 {
+        self.cells.len() as u32
+    }
+
+    /// The rule in the standard form, such as `B3/S23`.
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    /// Switch to a rule, written in any of the forms `set_rule` on a
+    /// universe accepts, keeping the cells as they are.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), String> {
+        self.rule = Rule::parse(rule)
+            .filter(|rule| rule.is_supported())
+            .ok_or_else(|| format!("the rule {} is not supported", rule))?;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn get_cell(&self, row: i32, col: i32) -> bool {
+        self.cells.contains(&(row as i64, col as i64))
+    }
+
+    pub fn set_cell(&mut self, row: i32, col: i32, alive: bool) {
+        let cell = (row as i64, col as i64);
+        if alive {
+            self.cells.insert(cell);
+        } else {
+            self.cells.remove(&cell);
+        }
+    }
+
+    pub fn toggle_cell(&mut self, row: i32, col: i32) {
+        let cell = (row as i64, col as i64);
+        if !self.cells.remove(&cell) {
+            self.cells.insert(cell);
+        }
+    }
+
+    /// Make the live cells of a pattern alive with the pattern's top-left
+    /// corner at a cell.
+    pub fn insert_pattern(&mut self, pattern: &Pattern, row: i32, col: i32) {
+        self.cells.extend(
+            pattern
+                .offsets()
+                .into_iter()
+                .map(|(d_row, d_col)| (row as i64 + d_row as i64, col as i64 + d_col as i64)),
+        );
+    }
+
+    pub fn tick(&mut self) {
+        self.tick_many(1);
+    }
+
+    pub fn tick_many(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            trace_span!("tick");
+            // Only live cells and their neighbours can be alive next.
+            let counts = &mut self.counts;
+            counts.clear();
+            for &(row, col) in self.cells.iter() {
+                counts.entry((row, col)).or_insert(0);
+                for d_row in -1..=1 {
+                    for d_col in -1..=1 {
+                        if (d_row, d_col) != (0, 0) {
+                            *counts.entry((row + d_row, col + d_col)).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+            let (cells, rule) = (&self.cells, self.rule);
+            let mut next = std::mem::take(&mut self.next_cells);
+            next.clear();
+            next.extend(
+                counts
+                    .iter()
+                    .filter(|&(cell, &count)| rule.next(cells.contains(cell), count))
+                    .map(|(&cell, _)| cell),
+            );
+            self.next_cells = std::mem::replace(&mut self.cells, next);
+            self.generation += 1;
+        }
+    }
+
+    /// The live cells in the `w` by `h` rectangle with its top-left corner
+    /// at column `x` and row `y`, as `[row, col, row, col, ...]` from that
+    /// corner, in reading order.
+    pub fn cells_in_rect(&self, x: i32, y: i32, w: u32, h: u32) -> Vec<u32> {
+        let (left, top) = (x as i64, y as i64);
+        let mut cells: Vec<(u32, u32)> = self
+            .cells
+            .iter()
+            .filter(|&&(row, col)| {
+                (top..top + h as i64).contains(&row) && (left..left + w as i64).contains(&col)
+            })
+            .map(|&(row, col)| ((row - top) as u32, (col - left) as u32))
+            .collect();
+        cells.sort_unstable();
+        cells
+            .into_iter()
+            .flat_map(|(row, col)| vec![row, col])
+            .collect()
+    }
+
+    /// The smallest rectangle containing every live cell as `[x, y, w, h]`,
+    /// for fitting the view to the pattern, or `undefined` if there are no
+    /// live cells. Positions beyond the range of `i32` are clamped to it.
+    pub fn live_bounding_box(&self) -> Option<Vec<i32>> {
+        let mut cells = self.cells.iter();
+        let &(row, col) = cells.next()?;
+        let (top, left, bottom, right) = cells.fold(
+            (row, col, row, col),
+            |(top, left, bottom, right), &(row, col)| {
+                (top.min(row), left.min(col), bottom.max(row), right.max(col))
+            },
+        );
+        let clamp = |value: i64| value.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        Some(vec![
+            clamp(left),
+            clamp(top),
+            clamp(right - left + 1),
+            clamp(bottom - top + 1),
+        ])
+    }
+}
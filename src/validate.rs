@@ -0,0 +1,114 @@
+use crate::{CellStorage, Universe, UniverseError};
+use wasm_bindgen::prelude::*;
+
+impl Universe {
+    /// The first internal invariant which doesn't hold, if any.
+    fn broken_invariant(&self) -> Option<String> {
+        let size = (self.width as usize + 2) * (self.height as usize + 2);
+        let bits_per_cell = if self.rule.states() <= 2 { 1 } else { 8 };
+        for (name, cells) in [
+            ("cells", &self.cells),
+            ("initial cells", &self.initial_cells),
+        ] {
+            if cells.len() != size {
+                return Some(format!(
+                    "the {} hold {} cells, not {}",
+                    name,
+                    cells.len(),
+                    size
+                ));
+            }
+            if cells.bits_per_cell() != bits_per_cell {
+                return Some(format!(
+                    "the {} use {} bits per cell under a {} state rule",
+                    name,
+                    cells.bits_per_cell(),
+                    self.rule.states()
+                ));
+            }
+        }
+
+        let stride = self.width as usize + 2;
+        for idx in self.cells.iter_live_indices() {
+            let (row, col) = (idx / stride, idx % stride);
+            if row == 0 || col == 0 || row > self.height as usize || col > self.width as usize {
+                return Some(format!("the halo cell at index {} is alive", idx));
+            }
+            let state = self.cells.get(idx).state() as u32;
+            if state >= self.rule.states() {
+                return Some(format!(
+                    "the cell at row={}, col={} has state {} under a {} state rule",
+                    row - 1,
+                    col - 1,
+                    state,
+                    self.rule.states()
+                ));
+            }
+        }
+
+        let layers = [
+            ("walls", self.walls.as_ref().map(|walls| walls.len())),
+            ("anchors", self.anchors.as_ref().map(Vec::len)),
+            ("ages", self.ages.as_ref().map(Vec::len)),
+            ("activity", self.activity.as_ref().map(Vec::len)),
+            (
+                "lifetimes",
+                self.lifetimes.as_ref().map(|lifetimes| lifetimes.len()),
+            ),
+            (
+                "resources",
+                self.resources.as_ref().map(|resources| resources.len()),
+            ),
+        ];
+        for (name, len) in layers.iter() {
+            match len {
+                Some(len) if *len != size => {
+                    return Some(format!("the {} cover {} cells, not {}", name, len, size))
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(history) = &self.history {
+            let packed = self.pack_cells(&self.cells).len();
+            let snapshots = history.recent(history.len());
+            if let Some(i) = snapshots
+                .iter()
+                .position(|snapshot| snapshot.len() != packed)
+            {
+                return Some(format!(
+                    "history snapshot {} of {} has {} bytes, not {}",
+                    i + 1,
+                    snapshots.len(),
+                    snapshots[i].len(),
+                    packed
+                ));
+            }
+        }
+
+        let (row, col) = self.cursor;
+        if row >= self.height || col >= self.width {
+            return Some(format!(
+                "the cursor at row={}, col={} is outside the universe",
+                row, col
+            ));
+        }
+        None
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Check the universe's internal invariants, such as every per-cell
+    /// buffer matching its size and the history matching its cells, and
+    /// fail describing the first which doesn't hold.
+    ///
+    /// This is meant for debugging frontends and for checking a state after
+    /// importing or syncing it, before relying on it.
+    pub fn validate(&self) -> Result<(), UniverseError> {
+        match self.broken_invariant() {
+            Some(message) => Err(self.error("validate", String::new(), message)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Version numbers embedded in everything the crate saves, and migrations
+//! which bring documents saved by older builds up to date before they are
+//! loaded.
+//!
+//! Fields which can be left out, such as ones added since a format was
+//! introduced, don't need a new version. The version is raised when a
+//! field changes meaning or moves, with a step added to `Format::upgrade`.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// A kind of saved document, each with its own version history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Format {
+    /// The universe, from `export_json` and `export_msgpack`, which keeps
+    /// its version among its own fields.
+    State,
+    /// The pattern library, from `export_patterns`.
+    Patterns,
+    /// The markers, from `export_markers`.
+    Markers,
+    /// A single pattern, from `Pattern::to_msgpack`.
+    Pattern,
+    /// The steps of a scenario, from `Scenario::to_json`.
+    Scenario,
+}
+
+impl Format {
+    /// The version written by this build.
+    pub(crate) fn current(self) -> u64 {
+        match self {
+            Format::State => 1,
+            Format::Patterns | Format::Markers | Format::Pattern | Format::Scenario => 1,
+        }
+    }
+
+    /// The field holding the data, for formats which wrap it alongside the
+    /// version.
+    fn field(self) -> Option<&'static str> {
+        match self {
+            Format::State => None,
+            Format::Patterns => Some("patterns"),
+            Format::Markers => Some("markers"),
+            Format::Pattern => Some("pattern"),
+            Format::Scenario => Some("steps"),
+        }
+    }
+
+    /// Turn a document of one version into the next version.
+    fn upgrade(self, version: u64, document: Value) -> Value {
+        match (self.field(), version) {
+            // Before version 1 the data was saved bare.
+            (Some(field), 0) => json!({ "version": 1, field: document }),
+            _ => unreachable!("no upgrade from version {} of {:?}", version, self),
+        }
+    }
+}
+
+/// Wrap data with the current version of a format.
+pub(crate) fn wrap<T: Serialize>(format: Format, data: &T) -> Value {
+    let field = format.field().expect("the format keeps its own version");
+    json!({ "version": format.current(), field: data })
+}
+
+/// Read data saved in any version of a format up to the current one.
+pub(crate) fn unwrap<T: DeserializeOwned>(
+    format: Format,
+    mut document: Value,
+) -> Result<T, String> {
+    let mut version = match document.get("version").map(Value::as_u64) {
+        Some(Some(version)) => version,
+        Some(None) => return Err("the version is not a number".to_string()),
+        None if format.field().is_some() => 0,
+        None => return Err("there is no version".to_string()),
+    };
+    if version > format.current() {
+        return Err(format!(
+            "version {} is newer than this build supports",
+            version
+        ));
+    }
+    // Formats which keep their own version have always had one, starting
+    // from 1.
+    if version == 0 && format.field().is_none() {
+        return Err("version 0 is not supported".to_string());
+    }
+    while version < format.current() {
+        document = format.upgrade(version, document);
+        version += 1;
+    }
+    let data = match format.field() {
+        Some(field) => document
+            .get_mut(field)
+            .map(Value::take)
+            .ok_or_else(|| format!("there is no `{}` field", field))?,
+        None => document,
+    };
+    serde_json::from_value(data).map_err(|err| err.to_string())
+}
@@ -0,0 +1,132 @@
+use crate::paint::line_cells;
+use crate::{Cell, CellStorage, Universe, UniverseError};
+use fixedbitset::FixedBitSet;
+use wasm_bindgen::prelude::*;
+
+/// Cells which are never born and never die, such as the walls of a maze.
+pub(crate) struct Walls {
+    /// Laid out in the same way as the universe's cells.
+    cells: FixedBitSet,
+    /// Whether walls count as live neighbours of the cells around them.
+    count_as_alive: bool,
+}
+
+impl Walls {
+    /// Number of cells covered, which matches the universe's cells.
+    pub(crate) fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        std::mem::size_of_val(self.cells.as_slice())
+    }
+}
+
+impl Universe {
+    pub(crate) fn is_wall(&self, idx: usize) -> bool {
+        self.walls.as_ref().is_some_and(|walls| walls.cells[idx])
+    }
+
+    /// Make walls which count as alive show up as live cells, so they are
+    /// counted as neighbours while ticking. Returns the number of walls
+    /// which were marked.
+    pub(crate) fn mark_walls(&mut self) -> usize {
+        match &self.walls {
+            Some(walls) if walls.count_as_alive => {
+                for idx in walls.cells.ones() {
+                    self.cells.set(idx, Cell::ALIVE);
+                }
+                walls.cells.count_ones(..)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Undo `mark_walls`, leaving every wall dead.
+    pub(crate) fn unmark_walls(&mut self) {
+        if let Some(walls) = &self.walls {
+            for idx in walls.cells.ones() {
+                self.cells.set(idx, Cell::DEAD);
+            }
+        }
+    }
+
+    fn walls_mut(&mut self) -> &mut Walls {
+        let size = self.cells.len();
+        self.walls.get_or_insert_with(|| Walls {
+            cells: FixedBitSet::with_capacity(size),
+            count_as_alive: false,
+        })
+    }
+
+    /// Remove every wall, for when the universe is resized.
+    pub(crate) fn reset_walls(&mut self) {
+        let size = self.cells.len();
+        if let Some(walls) = &mut self.walls {
+            walls.cells = FixedBitSet::with_capacity(size);
+        }
+    }
+
+    fn set_wall(&mut self, row: i32, col: i32, wall: bool) {
+        let idx = self.get_index(
+            row.rem_euclid(self.height as i32) as u32,
+            col.rem_euclid(self.width as i32) as u32,
+        );
+        self.walls_mut().cells.set(idx, wall);
+        self.cells.set(idx, Cell::DEAD);
+        self.cells_edited();
+        // A wall replaces any source or sink under it.
+        if let (true, Some(anchors)) = (wall, &mut self.anchors) {
+            anchors[idx] = 0;
+        }
+    }
+
+    fn apply_wall_brush(&mut self, row: i32, col: i32, wall: bool) {
+        for (d_row, d_col) in self.brush.offsets() {
+            self.set_wall(row + d_row, col + d_col, wall);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Place walls under the brush centred on a cell, killing any live cells
+    /// there.
+    pub fn paint_wall_at(&mut self, row: u32, col: u32) {
+        self.apply_wall_brush(row as i32, col as i32, true);
+    }
+
+    /// Place walls under the brush at every point along a line.
+    pub fn paint_wall_line(&mut self, from_row: u32, from_col: u32, to_row: u32, to_col: u32) {
+        let from = (from_row as i32, from_col as i32);
+        for (row, col) in line_cells(from, (to_row as i32, to_col as i32)) {
+            self.apply_wall_brush(row, col, true);
+        }
+    }
+
+    /// Remove any walls under the brush centred on a cell.
+    pub fn erase_wall_at(&mut self, row: u32, col: u32) {
+        self.apply_wall_brush(row as i32, col as i32, false);
+    }
+
+    pub fn is_wall_at(&self, row: u32, col: u32) -> Result<bool, UniverseError> {
+        let idx = self.checked_index("is_wall_at", row, col)?;
+        Ok(self.is_wall(idx))
+    }
+
+    pub fn clear_walls(&mut self) {
+        self.reset_walls();
+    }
+
+    /// Choose whether walls count as live or dead neighbours of the cells
+    /// around them. They count as dead by default.
+    pub fn set_walls_count_as_alive(&mut self, alive: bool) {
+        self.walls_mut().count_as_alive = alive;
+    }
+
+    /// The walls, a byte per cell which is 1 for a wall and 0 otherwise,
+    /// row by row, as a `Uint8Array`.
+    pub fn walls(&self) -> Vec<u8> {
+        self.each_cell(|idx| self.is_wall(idx) as u8)
+    }
+}
@@ -2,8 +2,8 @@
 
 #![cfg(target_arch = "wasm32")]
 
-use wasm_game_of_life::Universe;
 use wasm_bindgen_test::*;
+use wasm_game_of_life::{Boundary, CellStorage, PatternFit, ResizeAnchor, Universe, Universe3D};
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -12,7 +12,7 @@ pub fn input_spaceship() -> Universe {
     let mut universe = Universe::new();
     universe.set_width(6);
     universe.set_height(6);
-    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]);
+    universe.set_cells(&[(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)]);
     universe
 }
 
@@ -21,7 +21,7 @@ pub fn expected_spaceship() -> Universe {
     let mut universe = Universe::new();
     universe.set_width(6);
     universe.set_height(6);
-    universe.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]);
+    universe.set_cells(&[(2, 1), (2, 3), (3, 2), (3, 3), (4, 2)]);
     universe
 }
 
@@ -38,3 +38,287 @@ pub fn test_tick() {
     input_universe.tick();
     assert_eq!(&input_universe.get_cells(), &expected_universe.get_cells());
 }
+
+#[wasm_bindgen_test]
+pub fn test_blinker_swaps_buffers() {
+    // Each tick writes into the spare buffer and swaps it in, so a period
+    // two oscillator shows whether the buffers take turns properly.
+    let mut universe = Universe::new();
+    universe.set_width(5);
+    universe.set_height(5);
+    universe.clear();
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+    let horizontal = universe.get_cells().clone();
+
+    universe.tick();
+    let live: Vec<_> = universe.iter_live_cells().collect();
+    assert_eq!(live, vec![(1, 2), (2, 2), (3, 2)]);
+    universe.tick();
+    assert_eq!(universe.get_cells(), &horizontal);
+    universe.tick_many(2);
+    assert_eq!(universe.get_cells(), &horizontal);
+}
+
+#[wasm_bindgen_test]
+pub fn test_glider_detection() {
+    let mut universe = Universe::new();
+    universe.clear();
+    universe.add_glider(10, 10);
+    universe.add_glider(50, 20);
+
+    // Each glider should only be counted once however many scans see it.
+    universe.enable_glider_detection(4);
+    universe.tick_many(100);
+    assert_eq!(universe.glider_count(), 2);
+}
+
+#[wasm_bindgen_test]
+pub fn test_absorbing_boundary() {
+    let mut universe = Universe::new();
+    universe.clear();
+    universe.set_boundary(Boundary::Absorbing, Boundary::Absorbing);
+    universe.add_glider(10, 10);
+
+    // Rather than wrapping around, the glider crashes into the corner and
+    // leaves a block behind.
+    universe.tick_many(400);
+    assert_eq!(universe.get_cells().population(), 4);
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_rule() {
+    let mut universe = Universe::new();
+    universe.set_width(6);
+    universe.set_height(6);
+    universe.set_cells(&[(1, 1), (1, 2), (1, 3), (2, 1), (2, 3), (3, 1)]);
+
+    // The middle cell has six live neighbours, which only HighLife brings
+    // to life.
+    universe.set_rule("23/36").unwrap();
+    assert_eq!(universe.rule(), "B36/S23");
+    universe.tick();
+    assert_eq!(universe.get_cell_state(2, 2).unwrap(), 1);
+    assert!(universe.set_rule("B0/S8").is_err());
+    assert_eq!(
+        wasm_game_of_life::normalize_rule("s32/B3"),
+        Some("B3/S23".to_string())
+    );
+    assert_eq!(wasm_game_of_life::normalize_rule("B3/S9"), None);
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_rule_mid_run() {
+    let mut first = input_spaceship();
+    let mut second = input_spaceship();
+    first.start_op_log(1);
+    second.start_op_log(2);
+    first.tick();
+    let population = first.get_cells().population();
+
+    // The cells carry on from where they are, and the change reaches the
+    // other client through the op log.
+    first.set_rule("B3678/S34678").unwrap();
+    assert_eq!(first.get_cells().population(), population);
+    let ops = first.ops_since("{}").unwrap();
+    assert_eq!(second.merge_ops(&ops).unwrap(), 1);
+    assert_eq!(second.rule(), "B3678/S34678");
+}
+
+#[wasm_bindgen_test]
+pub fn test_coloured_rule() {
+    let mut universe = Universe::new();
+    universe.set_width(6);
+    universe.set_height(6);
+    universe.set_cells(&[(1, 1), (1, 2), (1, 3)]);
+
+    // Colouring the rule moves the cells to a byte each, keeping them.
+    universe.set_rule("Immigration").unwrap();
+    assert_eq!(universe.rule(), "B3/S23 Immigration");
+    assert_eq!(universe.state_count(), 3);
+    assert_eq!(universe.bits_per_cell(), 8);
+    assert_eq!(universe.get_cell_state(1, 2).unwrap(), 1);
+
+    // A blinker with two cells of the second colour turns that colour.
+    universe.set_cell_state(1, 1, 2).unwrap();
+    universe.set_cell_state(1, 3, 2).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell_state(0, 2).unwrap(), 2);
+    assert_eq!(universe.get_cell_state(2, 2).unwrap(), 2);
+
+    // Going back to two states leaves every colour alive.
+    universe.set_rule("B3/S23").unwrap();
+    assert_eq!(universe.state_count(), 2);
+    assert_eq!(universe.bits_per_cell(), 1);
+    assert_eq!(universe.get_cells().population(), 3);
+}
+
+#[wasm_bindgen_test]
+pub fn test_3d_glider() {
+    let mut universe = Universe3D::new(8, 8, 8).unwrap();
+    universe.stamp("glider", 0, 0, 2);
+
+    // Bays' glider moves one cell along x and y every four generations.
+    universe.tick_many(4);
+    assert_eq!(universe.population(), 10);
+    assert!(universe.get_cell(2, 1, 2));
+    assert!(universe.get_cell(2, 1, 3));
+    assert!(!universe.get_cell(1, 0, 2));
+}
+
+#[wasm_bindgen_test]
+pub fn test_rule_callback() {
+    // A JavaScript version of Conway's rule, given two rows at a time,
+    // should tick the same way as the built-in one.
+    let conway = js_sys::Function::new_with_args(
+        "states, counts",
+        "return states.map((state, i) => counts[i] == 3 || (state && counts[i] == 2) ? 1 : 0);",
+    );
+    let mut input_universe = input_spaceship();
+    input_universe.set_rule_callback(conway, 2);
+    input_universe.tick();
+    assert_eq!(
+        &input_universe.get_cells(),
+        &expected_spaceship().get_cells()
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_op_logs_converge() {
+    let mut first = Universe::new();
+    first.clear();
+    first.start_op_log(1);
+    let mut second = Universe::new();
+    second.clear();
+    second.start_op_log(2);
+
+    // Both clients edit the same cell at once, and the first also edits
+    // another cell afterwards.
+    let kill = first.log_cell_state(1, 1, 0).unwrap();
+    let birth = first.log_cell_state(3, 3, 1).unwrap();
+    let concurrent = second.log_cell_state(1, 1, 1).unwrap();
+
+    // Edits arrive out of order and more than once, but both boards end up
+    // the same, with the tie going to the client with the higher id.
+    assert_eq!(second.merge_ops(&birth).unwrap(), 1);
+    assert_eq!(second.merge_ops(&kill).unwrap(), 1);
+    assert_eq!(second.merge_ops(&birth).unwrap(), 0);
+    assert_eq!(first.merge_ops(&concurrent).unwrap(), 1);
+    assert_eq!(first.merge_ops(&concurrent).unwrap(), 0);
+    assert_eq!(&first.get_cells(), &second.get_cells());
+    assert_eq!(first.get_cell_state(1, 1).unwrap(), 1);
+    assert_eq!(first.get_cell_state(3, 3).unwrap(), 1);
+
+    // A state the rule doesn't have is rejected rather than merged.
+    assert!(first
+        .merge_ops(&concurrent.replace("\"state\":1", "\"state\":200"))
+        .is_err());
+    assert!(first.log_cell_state(1, 1, 200).is_err());
+}
+
+#[cfg(test)]
+pub fn corners_universe() -> Universe {
+    let mut universe = Universe::new();
+    universe.set_width(6);
+    universe.set_height(6);
+    universe.set_cells(&[(0, 0), (2, 3), (5, 5)]);
+    universe
+}
+
+#[wasm_bindgen_test]
+pub fn test_resize() {
+    let live = |universe: &Universe| universe.iter_live_cells().collect::<Vec<_>>();
+
+    // Growing from the top-left corner leaves every cell where it was.
+    let mut universe = corners_universe();
+    universe.resize(10, 8, ResizeAnchor::TopLeft).unwrap();
+    assert_eq!((universe.width(), universe.height()), (10, 8));
+    assert_eq!(live(&universe), vec![(0, 0), (2, 3), (5, 5)]);
+
+    // Growing from the centre moves cells half of the way out.
+    let mut universe = corners_universe();
+    universe.resize(10, 8, ResizeAnchor::Centre).unwrap();
+    assert_eq!(live(&universe), vec![(1, 2), (3, 5), (6, 7)]);
+
+    // Shrinking crops the cells beyond the anchored part.
+    let mut universe = corners_universe();
+    universe.resize(3, 3, ResizeAnchor::TopLeft).unwrap();
+    assert_eq!(live(&universe), vec![(0, 0)]);
+    let mut universe = corners_universe();
+    universe.resize(4, 4, ResizeAnchor::Centre).unwrap();
+    assert_eq!(live(&universe), vec![(1, 2)]);
+
+    // Sizes which can't be allocated are rejected, leaving the universe as
+    // it was.
+    assert!(universe.resize(u32::MAX, 2, ResizeAnchor::TopLeft).is_err());
+    assert!(universe.resize(0, 3, ResizeAnchor::Centre).is_err());
+    assert_eq!((universe.width(), universe.height()), (4, 4));
+}
+
+#[wasm_bindgen_test]
+pub fn test_rescale() {
+    // Doubling a glider turns each of its cells into a two by two block,
+    // and halving it again with majority votes gives the glider back.
+    let mut universe = input_spaceship();
+    universe.rescale(12, 12, false).unwrap();
+    assert_eq!(universe.get_cells().population(), 20);
+    assert_eq!(universe.get_cell_state(2, 4).unwrap(), 1);
+    assert_eq!(universe.get_cell_state(3, 5).unwrap(), 1);
+    assert_eq!(universe.get_cell_state(2, 6).unwrap(), 0);
+    universe.rescale(6, 6, true).unwrap();
+    assert_eq!(&universe.get_cells(), &input_spaceship().get_cells());
+
+    assert!(universe.rescale(u32::MAX, 2, false).is_err());
+    assert_eq!((universe.width(), universe.height()), (6, 6));
+}
+
+#[wasm_bindgen_test]
+pub fn test_rle_round_trip() {
+    // Exporting the spaceship and importing it into an empty universe of
+    // the same size puts every cell back where it was.
+    let universe = input_spaceship();
+    let rle = universe.export_rle();
+    let mut imported = Universe::new();
+    imported.set_width(6);
+    imported.set_height(6);
+    imported.clear();
+    assert_eq!(
+        imported.import_rle(&rle, 0, 0, PatternFit::Crop).unwrap(),
+        5
+    );
+    assert_eq!(&imported.get_cells(), &universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_interchange_round_trip() {
+    let mut universe = input_spaceship();
+    universe.tick();
+
+    let json = universe.export_json();
+    let from_json = Universe::from_json(&json).unwrap();
+    assert_eq!(&from_json.get_cells(), &universe.get_cells());
+    assert_eq!(from_json.generation(), 1);
+    assert_eq!(from_json.rule(), "B3/S23");
+
+    let bytes = universe.export_msgpack();
+    assert!(bytes.len() < json.len());
+    let mut from_msgpack = Universe::new();
+    from_msgpack.import_msgpack(&bytes).unwrap();
+    assert_eq!((from_msgpack.width(), from_msgpack.height()), (6, 6));
+    assert_eq!(&from_msgpack.get_cells(), &universe.get_cells());
+    assert_eq!(from_msgpack.generation(), 1);
+}
+
+#[wasm_bindgen_test]
+pub fn test_interchange_rejects_oversized_universes() {
+    // A universe too large to allocate is rejected before anything is
+    // changed.
+    let mut universe = input_spaceship();
+    let json = universe
+        .export_json()
+        .replace("\"width\":6", "\"width\":100000")
+        .replace("\"height\":6", "\"height\":100000");
+    assert!(Universe::from_json(&json).is_err());
+    assert!(universe.import_json(&json).is_err());
+    assert_eq!((universe.width(), universe.height()), (6, 6));
+    assert_eq!(&universe.get_cells(), &input_spaceship().get_cells());
+}
@@ -1,10 +1,6 @@
-import { Universe, Cell } from "wasm-game-of-life";
-import { memory, __wbindgen_malloc, __wbindgen_free } from "wasm-game-of-life/wasm_game_of_life_bg";
+import { Universe, Cell, Renderer } from "wasm-game-of-life";
 
 const CELL_SIZE = 5; // px
-const GRID_COLOR = "#CCCCCC";
-const DEAD_COLOR = "#FFFFFF";
-const ALIVE_COLOR = "#000000";
 
 
 // Construct the universe, and get its width and height.
@@ -14,9 +10,10 @@ const height = universe.height();
 
 // Give the canvas room for all of our cells and a 1px border
 // around each of them.
+const renderer = new Renderer(CELL_SIZE);
 const canvas = document.getElementById("game-of-life-canvas");
-canvas.height = (CELL_SIZE + 1) * height + 1;
-canvas.width = (CELL_SIZE + 1) * width + 1;
+canvas.height = renderer.canvas_height(universe);
+canvas.width = renderer.canvas_width(universe);
 
 const ctx = canvas.getContext("2d");
 
@@ -53,64 +50,9 @@ const renderOne = () => {
   draw();
 };
 
+// The renderer only redraws the cells which changed since the last draw.
 const draw = () => {
-  drawGrid();
-  drawCells();
-}
-
-const drawGrid = () => {
-  ctx.beginPath();
-  ctx.strokeStyle = GRID_COLOR;
-
-  // Vertical lines.
-  for (let i = 0; i <= width; i++) {
-    ctx.moveTo(i * (CELL_SIZE + 1) + 1, 0);
-    ctx.lineTo(i * (CELL_SIZE + 1) + 1, (CELL_SIZE + 1) * height + 1);
-  }
-
-  // Horizontal lines.
-  for (let j = 0; j <= height; j++) {
-    ctx.moveTo(0,                           j * (CELL_SIZE + 1) + 1);
-    ctx.lineTo((CELL_SIZE + 1) * width + 1, j * (CELL_SIZE + 1) + 1);
-  }
-
-  ctx.stroke();
-};
-
-const getIndex = (row, column) => {
-  return row * width + column;
-};
-
-const bitIsSet = (n, arr) => {
-  const byte = Math.floor(n / 8);
-  const mask = 1 << (n % 8);
-  return (arr[byte] & mask) === mask;
-};
-
-const drawCells = () => {
-  const cellsPtr = universe.cells();
-  const cells = new Uint8Array(memory.buffer, cellsPtr, Math.ceil(width * height / 8));
-
-  ctx.beginPath();
-
-  for (let row = 0; row < height; row++) {
-    for (let col = 0; col < width; col++) {
-      const idx = getIndex(row, col);
-
-      ctx.fillStyle = bitIsSet(idx, cells)
-        ? ALIVE_COLOR
-        : DEAD_COLOR;
-
-      ctx.fillRect(
-        col * (CELL_SIZE + 1) + 1,
-        row * (CELL_SIZE + 1) + 1,
-        CELL_SIZE,
-        CELL_SIZE
-      );
-    }
-  }
-
-  ctx.stroke();
+  renderer.draw(universe, ctx);
 };
 
 const isPaused = () => animationId === null;
@@ -179,17 +121,18 @@ document.getElementById("clear").addEventListener("click", () => {
   draw();
 });
 
-document.getElementById("save-current").addEventListener("click", () => save(universe.cells()));
+document.getElementById("save-current").addEventListener(
+  "click",
+  () => save(universe.cells_as_bytes())
+);
 
 document.getElementById("save-initial").addEventListener(
   "click",
-  () => save(universe.initial_cells())
+  () => save(universe.initial_cells_as_bytes())
 );
 
-const save = cellsPtr => {
-  const cells = new Uint8Array(memory.buffer, cellsPtr, Math.ceil(width * height / 8));
-  const buffer = cells.buffer.slice(cells.byteOffset, cells.byteLength + cells.byteOffset);
-  const blob = new Blob([buffer], {type: "application/octet-stream"});
+const save = bytes => {
+  const blob = new Blob([bytes], {type: "application/octet-stream"});
 
   const url = URL.createObjectURL(blob);
   const link = document.createElement("a");
@@ -207,12 +150,11 @@ loadInput.addEventListener("change", () => {
   const file = loadInput.files[0];
   const reader = new FileReader();
   reader.addEventListener("loadend", () => {
-    const data = new Uint8Array(reader.result);
-    const ptr = __wbindgen_malloc(data.length);
-    const buffer = new Uint8Array(memory.buffer);
-    buffer.set(data, ptr);
-    universe.set_state(ptr, data.length);
-    __wbindgen_free(ptr, data.length);
+    try {
+      universe.set_state(new Uint8Array(reader.result));
+    } catch (err) {
+      alert(err);
+    }
     draw();
   });
   reader.readAsArrayBuffer(file);